@@ -0,0 +1,247 @@
+//! Benchmarks comparing the integer, array and vector kmer types at the kmer sizes where they overlap,
+//! the ways of canonicalising vector kmers, the strategies for removing duplicate kmers, and the ways of reading the characters of an input.
+//!
+//! Run with `cargo bench`.
+
+// The module is compiled without most of its users, and with the imports of its unit tests, but not the tests themselves.
+#[allow(dead_code, unused_imports)]
+#[path = "../src/kmer.rs"]
+mod kmer;
+
+// The helpers that the unit tests of the module import, not all of which they use.
+#[cfg(test)]
+#[allow(dead_code)]
+#[path = "../src/test_util.rs"]
+mod test_util;
+
+use criterion::{black_box, criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion};
+use kmer::{BitPackedArrayKmer, BitPackedKmer, BitPackedVectorKmer, Kmer};
+use std::collections::{BTreeSet, HashSet};
+use std::io::{BufReader, Read};
+
+/// The number of kmers in each synthetic kmer set.
+const KMER_COUNT: usize = 100_000;
+
+/// Pseudo-random kmers generated by a linear congruential generator, such that all runs use the same kmers.
+fn random_kmers<KmerType: FromIterator<u8>>(k: usize) -> Vec<KmerType> {
+    let mut state = 0x2545f4914f6cdd1du64;
+    (0..KMER_COUNT)
+        .map(|_| {
+            (0..k)
+                .map(|_| {
+                    state = state
+                        .wrapping_mul(6364136223846793005)
+                        .wrapping_add(1442695040888963407);
+                    [b'A', b'C', b'G', b'T'][(state >> 62) as usize]
+                })
+                .collect()
+        })
+        .collect()
+}
+
+fn bench_kmer_type<KmerType: FromIterator<u8> + Kmer>(
+    criterion: &mut Criterion,
+    k: usize,
+    name: &str,
+) {
+    let kmers = random_kmers::<KmerType>(k);
+    let parameter = format!("k={k}");
+
+    criterion
+        .benchmark_group("reverse_complement")
+        .bench_with_input(
+            BenchmarkId::new(name, &parameter),
+            &kmers,
+            |bencher, kmers| {
+                bencher.iter(|| {
+                    for kmer in kmers {
+                        black_box(kmer.reverse_complement());
+                    }
+                })
+            },
+        );
+
+    criterion.benchmark_group("canonical").bench_with_input(
+        BenchmarkId::new(name, &parameter),
+        &kmers,
+        |bencher, kmers| {
+            bencher.iter(|| {
+                for kmer in kmers {
+                    black_box(kmer.canonical());
+                }
+            })
+        },
+    );
+
+    criterion.benchmark_group("sort").bench_with_input(
+        BenchmarkId::new(name, &parameter),
+        &kmers,
+        |bencher, kmers| {
+            bencher.iter_batched_ref(
+                || kmers.clone(),
+                |kmers| kmers.sort_unstable(),
+                BatchSize::LargeInput,
+            )
+        },
+    );
+}
+
+fn bench_kmer_types(criterion: &mut Criterion) {
+    // The most common kmer size.
+    bench_kmer_type::<BitPackedKmer<31, u64>>(criterion, 31, "u64");
+    bench_kmer_type::<BitPackedVectorKmer>(criterion, 31, "vector");
+
+    // The largest kmer size stored in a `u128`, above which the array type is used.
+    bench_kmer_type::<BitPackedKmer<64, u128>>(criterion, 64, "u128");
+    bench_kmer_type::<BitPackedArrayKmer<64, 2>>(criterion, 64, "array");
+    bench_kmer_type::<BitPackedVectorKmer>(criterion, 64, "vector");
+
+    // The largest kmer size stored in an array, above which the vector type is used.
+    bench_kmer_type::<BitPackedArrayKmer<128, 4>>(criterion, 128, "array");
+    bench_kmer_type::<BitPackedVectorKmer>(criterion, 128, "vector");
+}
+
+/// Compare canonicalising vector kmers via a separately allocated reverse complement against doing it in place.
+fn bench_vector_canonical(criterion: &mut Criterion) {
+    let kmers = random_kmers::<BitPackedVectorKmer>(80);
+    let mut group = criterion.benchmark_group("vector_canonical");
+
+    group.bench_with_input("allocating", &kmers, |bencher, kmers| {
+        bencher.iter_batched(
+            || kmers.clone(),
+            |kmers| {
+                kmers
+                    .into_iter()
+                    .map(|kmer| {
+                        let reverse_complement = kmer.reverse_complement();
+                        if reverse_complement < kmer {
+                            reverse_complement
+                        } else {
+                            kmer.clone()
+                        }
+                    })
+                    .collect::<Vec<_>>()
+            },
+            BatchSize::LargeInput,
+        )
+    });
+    group.bench_with_input("in_place", &kmers, |bencher, kmers| {
+        bencher.iter_batched(
+            || kmers.clone(),
+            |kmers| {
+                kmers
+                    .into_iter()
+                    .map(Kmer::into_canonical)
+                    .collect::<Vec<_>>()
+            },
+            BatchSize::LargeInput,
+        )
+    });
+}
+
+/// Compare the strategies of `--dedup-strategy` on kmers with few duplicates, as is typical for unitigs.
+fn bench_dedup_strategies(criterion: &mut Criterion) {
+    let kmers: Vec<_> = random_kmers::<BitPackedKmer<31, u64>>(31)
+        .into_iter()
+        .map(Kmer::into_canonical)
+        .collect();
+    let mut group = criterion.benchmark_group("dedup");
+
+    group.bench_with_input("sort", &kmers, |bencher, kmers| {
+        bencher.iter_batched(
+            || kmers.clone(),
+            |mut kmers| {
+                kmers.sort_unstable();
+                kmers.dedup();
+                kmers
+            },
+            BatchSize::LargeInput,
+        )
+    });
+    group.bench_with_input("hashset", &kmers, |bencher, kmers| {
+        bencher.iter(|| {
+            let mut kmers: Vec<_> = kmers
+                .iter()
+                .copied()
+                .collect::<HashSet<_>>()
+                .into_iter()
+                .collect();
+            kmers.sort_unstable();
+            kmers
+        })
+    });
+    group.bench_with_input("btreeset", &kmers, |bencher, kmers| {
+        bencher.iter(|| {
+            kmers
+                .iter()
+                .copied()
+                .collect::<BTreeSet<_>>()
+                .into_iter()
+                .collect::<Vec<_>>()
+        })
+    });
+}
+
+/// The size of the synthetic fasta file read by [`bench_read_characters`].
+const FASTA_SIZE: usize = 16 * 1024 * 1024;
+
+/// The size of the chunks that the kmer iterator reads its input in.
+const CHUNK_SIZE: usize = 16 * 1024 * 1024;
+
+/// Compare reading single bytes from a `BufReader` against reading characters from a chunk like the kmer iterator does.
+///
+/// The inputs are boxed like the readers returned when opening the input files.
+fn bench_read_characters(criterion: &mut Criterion) {
+    let fasta: Vec<u8> = random_kmers::<Vec<u8>>(160)
+        .into_iter()
+        .enumerate()
+        .flat_map(|(index, sequence)| {
+            format!(">{index}\n")
+                .into_bytes()
+                .into_iter()
+                .chain(sequence)
+                .chain([b'\n'])
+        })
+        .take(FASTA_SIZE)
+        .collect();
+    let mut group = criterion.benchmark_group("read_characters");
+
+    group.bench_with_input("buf_reader", &fasta, |bencher, fasta| {
+        bencher.iter(|| {
+            let input: Box<dyn Read> = Box::new(fasta.as_slice());
+            let mut input = BufReader::with_capacity(CHUNK_SIZE, input);
+            let mut character = [0];
+            let mut checksum = 0u64;
+            while input.read(&mut character).unwrap() == 1 {
+                checksum = checksum.wrapping_add(u64::from(character[0]));
+            }
+            checksum
+        })
+    });
+    group.bench_with_input("chunk", &fasta, |bencher, fasta| {
+        bencher.iter(|| {
+            let mut input: Box<dyn Read> = Box::new(fasta.as_slice());
+            let mut chunk = vec![0; CHUNK_SIZE];
+            let mut checksum = 0u64;
+            loop {
+                let chunk_length = input.read(&mut chunk).unwrap();
+                if chunk_length == 0 {
+                    break;
+                }
+                for &character in &chunk[..chunk_length] {
+                    checksum = checksum.wrapping_add(u64::from(character));
+                }
+            }
+            checksum
+        })
+    });
+}
+
+criterion_group!(
+    benches,
+    bench_kmer_types,
+    bench_vector_canonical,
+    bench_dedup_strategies,
+    bench_read_characters
+);
+criterion_main!(benches);
@@ -0,0 +1,266 @@
+use crate::kmer::Kmer;
+use crate::kmer_iterator::InputCounts;
+use std::fmt::{Display, Formatter};
+use std::io::{Read, Write};
+
+/// The first bytes of each kmer dump.
+const MAGIC: &[u8; 8] = b"VSKCDUMP";
+
+/// The version of the dump format, which is incremented on every incompatible change.
+const VERSION: u32 = 1;
+
+/// An error that occurs when reading a kmer dump.
+#[derive(Debug)]
+pub enum DumpError {
+    Io(std::io::Error),
+    /// The file does not start with the magic bytes of a kmer dump.
+    NotADump,
+    /// The dump was written by an incompatible version.
+    UnsupportedVersion {
+        version: u32,
+    },
+    /// The dump was written with a different kmer size.
+    WrongKmerSize {
+        expected: usize,
+        actual: u64,
+    },
+    /// The dump contains canonical kmers but forward kmers are expected, or vice versa.
+    WrongStrandSpecificity {
+        expected: bool,
+    },
+    /// The kmers in the dump are not sorted and unique.
+    Unsorted,
+}
+
+impl Display for DumpError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DumpError::Io(error) => write!(f, "{error}"),
+            DumpError::NotADump => write!(f, "not a kmer dump"),
+            DumpError::UnsupportedVersion { version } => write!(
+                f,
+                "the dump has version {version}, but only version {VERSION} is supported"
+            ),
+            DumpError::WrongKmerSize { expected, actual } => write!(
+                f,
+                "the dump was written with k = {actual}, but k = {expected} was given"
+            ),
+            DumpError::WrongStrandSpecificity { expected: true } => write!(
+                f,
+                "the dump contains canonical kmers, but --strand-specific was given"
+            ),
+            DumpError::WrongStrandSpecificity { expected: false } => write!(
+                f,
+                "the dump was written with --strand-specific, which was not given"
+            ),
+            DumpError::Unsorted => write!(f, "the kmers are not sorted and unique"),
+        }
+    }
+}
+
+impl std::error::Error for DumpError {}
+
+impl From<std::io::Error> for DumpError {
+    fn from(error: std::io::Error) -> Self {
+        Self::Io(error)
+    }
+}
+
+/// Write sorted unique kmers with the counts of the input they were read from.
+///
+/// The dump starts with a header that identifies the format version, the kmer size and
+/// whether the kmers are canonical, followed by the bit-packed kmers.
+pub fn write_kmer_dump<KmerType: Kmer>(
+    mut output: impl Write,
+    kmers: &[KmerType],
+    counts: InputCounts,
+    k: usize,
+    strand_specific: bool,
+) -> std::io::Result<()> {
+    debug_assert!(kmers.is_sorted());
+    debug_assert!(kmers.iter().all(|kmer| kmer.length() == k));
+
+    output.write_all(MAGIC)?;
+    output.write_all(&VERSION.to_le_bytes())?;
+    output.write_all(&(k as u64).to_le_bytes())?;
+    output.write_all(&[u8::from(strand_specific)])?;
+    for count in [
+        kmers.len(),
+        counts.character_count,
+        counts.sequence_count,
+        counts.kmer_position_count,
+    ] {
+        output.write_all(&(count as u64).to_le_bytes())?;
+    }
+
+    for kmer in kmers {
+        kmer.write_packed(&mut output)?;
+    }
+    output.flush()
+}
+
+/// Read a kmer dump written by [`write_kmer_dump`], rejecting it if it does not match the given arguments.
+pub fn read_kmer_dump<KmerType: Kmer>(
+    mut input: impl Read,
+    k: usize,
+    strand_specific: bool,
+) -> Result<(Vec<KmerType>, InputCounts), DumpError> {
+    let mut magic = [0; 8];
+    if input.read_exact(&mut magic).is_err() || &magic != MAGIC {
+        return Err(DumpError::NotADump);
+    }
+
+    let version = u32::from_le_bytes(read_bytes(&mut input)?);
+    if version != VERSION {
+        return Err(DumpError::UnsupportedVersion { version });
+    }
+
+    let dump_k = u64::from_le_bytes(read_bytes(&mut input)?);
+    if dump_k != k as u64 {
+        return Err(DumpError::WrongKmerSize {
+            expected: k,
+            actual: dump_k,
+        });
+    }
+
+    let [dump_strand_specific] = read_bytes(&mut input)?;
+    if (dump_strand_specific != 0) != strand_specific {
+        return Err(DumpError::WrongStrandSpecificity {
+            expected: strand_specific,
+        });
+    }
+
+    let kmer_count = read_count(&mut input)?;
+    let counts = InputCounts {
+        character_count: read_count(&mut input)?,
+        sequence_count: read_count(&mut input)?,
+        kmer_position_count: read_count(&mut input)?,
+        ..InputCounts::default()
+    };
+
+    let kmers = (0..kmer_count)
+        .map(|_| KmerType::read_packed(&mut input, k))
+        .collect::<Result<Vec<_>, _>>()?;
+    if !kmers.is_sorted_by(|a, b| a < b) {
+        return Err(DumpError::Unsorted);
+    }
+
+    Ok((kmers, counts))
+}
+
+/// Write sorted unique kmers as FASTA, with one record named `kmer_<index>` per kmer.
+///
+/// The kmers are written one by one, so the output is never held in memory.
+pub fn write_kmer_fasta<KmerType: Kmer + Display>(
+    mut output: impl Write,
+    kmers: &[KmerType],
+) -> std::io::Result<()> {
+    debug_assert!(kmers.is_sorted());
+
+    for (index, kmer) in kmers.iter().enumerate() {
+        writeln!(output, ">kmer_{index}\n{kmer}")?;
+    }
+    output.flush()
+}
+
+fn read_count(input: &mut impl Read) -> std::io::Result<usize> {
+    Ok(u64::from_le_bytes(read_bytes(input)?) as usize)
+}
+
+fn read_bytes<const N: usize>(input: &mut impl Read) -> std::io::Result<[u8; N]> {
+    let mut bytes = [0; N];
+    input.read_exact(&mut bytes)?;
+    Ok(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::dump::{read_kmer_dump, write_kmer_dump, write_kmer_fasta, DumpError};
+    use crate::kmer::{BitPackedArrayKmer, BitPackedKmer, BitPackedVectorKmer, Kmer};
+    use crate::kmer_iterator::InputCounts;
+
+    fn round_trip<KmerType: Kmer + std::fmt::Debug>(k: usize, kmers: &[&str]) {
+        let mut kmers: Vec<_> = kmers
+            .iter()
+            .map(|kmer| KmerType::from_ascii(kmer.as_bytes()).unwrap())
+            .collect();
+        kmers.sort();
+        let counts = InputCounts {
+            character_count: 10,
+            sequence_count: 2,
+            kmer_position_count: 7,
+            ..InputCounts::default()
+        };
+
+        let mut dump = Vec::new();
+        write_kmer_dump(&mut dump, &kmers, counts, k, false).unwrap();
+        let (read_kmers, read_counts) =
+            read_kmer_dump::<KmerType>(dump.as_slice(), k, false).unwrap();
+        assert_eq!(read_kmers, kmers);
+        assert_eq!(read_counts, counts);
+    }
+
+    #[test]
+    fn test_round_trip() {
+        round_trip::<BitPackedKmer<3, u8>>(3, &["AAA", "ACG", "TTT", "GCA"]);
+        round_trip::<BitPackedKmer<31, u64>>(
+            31,
+            &[
+                "ACGTACGTACGTACGTACGTACGTACGTACG",
+                "TTTTTTTTTTTTTTTTTTTTTTTTTTTTTTT",
+            ],
+        );
+        round_trip::<BitPackedKmer<33, u128>>(33, &["ACGTACGTACGTACGTACGTACGTACGTACGTA"]);
+        round_trip::<BitPackedArrayKmer<70, 3>>(70, &[&"ACGTTGCA".repeat(9)[..70]]);
+        round_trip::<BitPackedVectorKmer>(13, &["ACGTACGTACGTA", "GGGGCCCCAAAAT"]);
+    }
+
+    #[test]
+    fn test_rejected_dumps() {
+        let kmers = vec![BitPackedKmer::<3, u8>::from_ascii(b"ACG").unwrap()];
+        let mut dump = Vec::new();
+        write_kmer_dump(&mut dump, &kmers, InputCounts::default(), 3, false).unwrap();
+
+        assert!(matches!(
+            read_kmer_dump::<BitPackedKmer<3, u8>>(dump.as_slice(), 4, false),
+            Err(DumpError::WrongKmerSize {
+                expected: 4,
+                actual: 3
+            })
+        ));
+        assert!(matches!(
+            read_kmer_dump::<BitPackedKmer<3, u8>>(dump.as_slice(), 3, true),
+            Err(DumpError::WrongStrandSpecificity { expected: true })
+        ));
+        assert!(matches!(
+            read_kmer_dump::<BitPackedKmer<3, u8>>(&dump[1..], 3, false),
+            Err(DumpError::NotADump)
+        ));
+        assert!(matches!(
+            read_kmer_dump::<BitPackedKmer<3, u8>>(&dump[..dump.len() - 1], 3, false),
+            Err(DumpError::Io(_))
+        ));
+
+        dump[8] = 2;
+        assert!(matches!(
+            read_kmer_dump::<BitPackedKmer<3, u8>>(dump.as_slice(), 3, false),
+            Err(DumpError::UnsupportedVersion { version: 2 })
+        ));
+    }
+
+    #[test]
+    fn test_write_kmer_fasta() {
+        let mut kmers: Vec<_> = ["TTG", "ACG", "CAA"]
+            .iter()
+            .map(|kmer| BitPackedKmer::<3, u8>::from_ascii(kmer.as_bytes()).unwrap())
+            .collect();
+        kmers.sort();
+
+        let mut fasta = Vec::new();
+        write_kmer_fasta(&mut fasta, &kmers).unwrap();
+        assert_eq!(
+            String::from_utf8(fasta).unwrap(),
+            ">kmer_0\nACG\n>kmer_1\nCAA\n>kmer_2\nTTG\n"
+        );
+    }
+}
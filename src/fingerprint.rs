@@ -0,0 +1,86 @@
+use crate::kmer::Kmer;
+use std::fmt::{Display, Formatter};
+
+/// An order-independent fingerprint of a set of unique kmers.
+///
+/// It is the wrapping sum of a 64-bit hash of the packed encoding of each kmer,
+/// so it does not depend on the order of the kmers.
+/// The hash function is fixed, such that fingerprints can be compared across invocations,
+/// as long as they use the same k and the same `--strand-specific` setting.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct KmerSetFingerprint(u64);
+
+impl KmerSetFingerprint {
+    /// Compute the fingerprint of the given kmers, which must not contain duplicates.
+    pub fn new<KmerType: Kmer>(kmers: &[KmerType]) -> Self {
+        let mut bytes = Vec::new();
+        let fingerprint = kmers.iter().fold(0u64, |fingerprint, kmer| {
+            fingerprint.wrapping_add(hash_kmer(kmer, 0, &mut bytes))
+        });
+        Self(fingerprint)
+    }
+}
+
+/// A seeded 64-bit hash of the packed encoding of a kmer, which is the same across invocations.
+///
+/// The buffer is used for encoding the kmer, such that it can be reused across calls.
+pub fn hash_kmer(kmer: &impl Kmer, seed: u64, buffer: &mut Vec<u8>) -> u64 {
+    buffer.clear();
+    kmer.write_packed(buffer).unwrap();
+    hash(buffer, seed)
+}
+
+/// FNV-1a with the seed mixed into its offset basis, followed by the finaliser of splitmix64,
+/// such that each bit of the hash depends on all input bits.
+fn hash(bytes: &[u8], seed: u64) -> u64 {
+    let mut hash = bytes
+        .iter()
+        .fold(0xcbf29ce484222325u64 ^ seed, |hash, &byte| {
+            (hash ^ u64::from(byte)).wrapping_mul(0x100000001b3)
+        });
+    hash = (hash ^ (hash >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+    hash = (hash ^ (hash >> 27)).wrapping_mul(0x94d049bb133111eb);
+    hash ^ (hash >> 31)
+}
+
+impl Display for KmerSetFingerprint {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:016x}", self.0)
+    }
+}
+
+/// Serialised as a hex string like in the plain output, since JSON numbers may not represent all 64-bit integers.
+#[cfg(feature = "json")]
+impl serde::Serialize for KmerSetFingerprint {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fingerprint::KmerSetFingerprint;
+    use crate::kmer::{BitPackedKmer, Kmer};
+
+    fn fingerprint(kmers: &[&str]) -> KmerSetFingerprint {
+        let kmers: Vec<_> = kmers
+            .iter()
+            .map(|kmer| BitPackedKmer::<3, u8>::from_ascii(kmer.as_bytes()).unwrap())
+            .collect();
+        KmerSetFingerprint::new(&kmers)
+    }
+
+    #[test]
+    fn test_fingerprint() {
+        assert_eq!(
+            fingerprint(&["AAA", "ACG", "GCA"]),
+            fingerprint(&["GCA", "AAA", "ACG"])
+        );
+        assert_ne!(
+            fingerprint(&["AAA", "ACG", "GCA"]),
+            fingerprint(&["AAA", "ACG", "GCC"])
+        );
+        assert_ne!(fingerprint(&["AAA", "ACG"]), fingerprint(&["AAA"]));
+        assert_eq!(fingerprint(&[]).to_string(), "0000000000000000");
+    }
+}
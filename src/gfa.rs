@@ -0,0 +1,214 @@
+use log::warn;
+use std::collections::HashMap;
+
+/// Spell the sequences of all paths (`P` lines) and walks (`W` lines) of a GFA file,
+/// and return them as fasta records.
+///
+/// Consecutive oriented segments are stitched together by removing the first `k - 1` characters
+/// of each segment except the first.
+/// Segments that are not part of any path or walk are ignored.
+///
+/// If the input is a fasta file, it is returned unchanged.
+pub fn spell_paths(input: &[u8], k: usize, panic_on_parse_error: bool) -> Vec<u8> {
+    if input
+        .iter()
+        .find(|character| !character.is_ascii_whitespace())
+        == Some(&b'>')
+    {
+        return input.to_vec();
+    }
+
+    let mut segments = HashMap::new();
+    let mut paths = Vec::new();
+    for line in input.split(|&character| character == b'\n') {
+        let line = line.strip_suffix(b"\r").unwrap_or(line);
+        let mut columns = line.split(|&character| character == b'\t');
+        match columns.next() {
+            Some(b"S") => {
+                let (Some(name), Some(sequence)) = (columns.next(), columns.next()) else {
+                    parse_error("Found an S line without sequence", panic_on_parse_error);
+                    continue;
+                };
+                segments.insert(name, sequence);
+            }
+            Some(b"P") => {
+                let (Some(name), Some(segment_names)) = (columns.next(), columns.next()) else {
+                    parse_error("Found a P line without segments", panic_on_parse_error);
+                    continue;
+                };
+                let steps = segment_names
+                    .split(|&character| character == b',')
+                    .map(|step| match step.split_last() {
+                        Some((b'+', name)) => Some((name, false)),
+                        Some((b'-', name)) => Some((name, true)),
+                        _ => None,
+                    })
+                    .collect();
+                paths.push((name, steps));
+            }
+            Some(b"W") => {
+                let (Some(name), Some(walk)) = (columns.nth(2), columns.nth(2)) else {
+                    parse_error("Found a W line without walk", panic_on_parse_error);
+                    continue;
+                };
+                let mut steps = Vec::new();
+                let mut step_start = None;
+                for (index, &character) in walk.iter().enumerate() {
+                    if matches!(character, b'>' | b'<') {
+                        if let Some((start, reverse)) = step_start {
+                            steps.push(Some((&walk[start..index], reverse)));
+                        }
+                        step_start = Some((index + 1, character == b'<'));
+                    } else if step_start.is_none() {
+                        steps.push(None);
+                    }
+                }
+                if let Some((start, reverse)) = step_start {
+                    steps.push(Some((&walk[start..], reverse)));
+                }
+                paths.push((name, steps));
+            }
+            _ => {}
+        }
+    }
+
+    let mut output = Vec::new();
+    'paths: for (name, steps) in paths {
+        let mut spelled = Vec::new();
+        for (index, step) in steps.into_iter().enumerate() {
+            let Some((segment_name, reverse)) = step else {
+                parse_error(
+                    &format!(
+                        "Path {} contains a malformed step",
+                        String::from_utf8_lossy(name)
+                    ),
+                    panic_on_parse_error,
+                );
+                continue 'paths;
+            };
+            let Some(&sequence) = segments.get(segment_name) else {
+                parse_error(
+                    &format!(
+                        "Path {} contains unknown segment {}",
+                        String::from_utf8_lossy(name),
+                        String::from_utf8_lossy(segment_name)
+                    ),
+                    panic_on_parse_error,
+                );
+                continue 'paths;
+            };
+
+            let overlap = if index == 0 { 0 } else { k - 1 };
+            if sequence.len() < overlap {
+                parse_error(
+                    &format!(
+                        "Segment {} is shorter than the overlap of k - 1 = {overlap}",
+                        String::from_utf8_lossy(segment_name)
+                    ),
+                    panic_on_parse_error,
+                );
+                continue 'paths;
+            }
+
+            if reverse {
+                spelled.extend(
+                    sequence
+                        .iter()
+                        .rev()
+                        .skip(overlap)
+                        .map(|&character| complement(character)),
+                );
+            } else {
+                spelled.extend_from_slice(&sequence[overlap..]);
+            }
+        }
+
+        output.push(b'>');
+        output.extend_from_slice(name);
+        output.push(b'\n');
+        output.extend_from_slice(&spelled);
+        output.push(b'\n');
+    }
+
+    output
+}
+
+/// Parse the kmer size from the first line of a GFA or fasta file.
+///
+/// In a GFA file, the first line must be an `H` line with a `KL:i:<k>` or `k:i:<k>` tag.
+/// In a fasta file, the first line must be a header whose comment contains one of these tags,
+/// separated from the other comment fields by whitespace.
+/// Returns `None` if there is no such tag.
+pub fn k_from_header(first_line: &[u8]) -> Option<usize> {
+    let first_line = first_line.trim_ascii_end();
+    let tags = if let Some(tags) = first_line.strip_prefix(b"H\t") {
+        tags
+    } else if let Some(header) = first_line.strip_prefix(b">") {
+        let start = header.iter().position(u8::is_ascii_whitespace)?;
+        &header[start..]
+    } else {
+        return None;
+    };
+
+    tags.split(u8::is_ascii_whitespace).find_map(|tag| {
+        let value = tag
+            .strip_prefix(b"KL:i:")
+            .or_else(|| tag.strip_prefix(b"k:i:"))?;
+        std::str::from_utf8(value).ok()?.parse().ok()
+    })
+}
+
+fn parse_error(message: &str, panic_on_parse_error: bool) {
+    if panic_on_parse_error {
+        panic!("{message}");
+    } else {
+        warn!("{message}");
+    }
+}
+
+/// Returns the complement of a DNA or IUPAC character.
+fn complement(character: u8) -> u8 {
+    match character.to_ascii_uppercase() {
+        b'A' => b'T',
+        b'C' => b'G',
+        b'G' => b'C',
+        b'T' | b'U' => b'A',
+        b'R' => b'Y',
+        b'Y' => b'R',
+        b'K' => b'M',
+        b'M' => b'K',
+        b'B' => b'V',
+        b'V' => b'B',
+        b'D' => b'H',
+        b'H' => b'D',
+        other => other,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::gfa::{k_from_header, spell_paths};
+
+    #[test]
+    fn test_spell_paths() {
+        let gfa = "H\tVN:Z:1.0\nS\t1\tAACG\nS\t2\tCGTT\nS\t3\tGGCG\nL\t1\t+\t2\t+\t2M\nP\tp\t1+,2+,3-\t2M,2M\nW\tsample\t0\tchr\t0\t6\t>1<2\n";
+        assert_eq!(
+            String::from_utf8(spell_paths(gfa.as_bytes(), 3, true)).unwrap(),
+            ">p\nAACGTTCC\n>chr\nAACGCG\n"
+        );
+
+        let fasta = ">a\nACGT\n";
+        assert_eq!(spell_paths(fasta.as_bytes(), 3, true), fasta.as_bytes());
+    }
+
+    #[test]
+    fn test_k_from_header() {
+        assert_eq!(k_from_header(b"H\tVN:Z:1.0\tKL:i:31\n"), Some(31));
+        assert_eq!(k_from_header(b"H\tk:i:5"), Some(5));
+        assert_eq!(k_from_header(b">0 LN:i:33 k:i:21 KC:i:4\r\n"), Some(21));
+        assert_eq!(k_from_header(b"H\tVN:Z:1.0\n"), None);
+        assert_eq!(k_from_header(b">k:i:21\n"), None);
+        assert_eq!(k_from_header(b"S\t1\tACGT\tKL:i:3\n"), None);
+        assert_eq!(k_from_header(b"H\tKL:i:x\n"), None);
+    }
+}
@@ -0,0 +1,124 @@
+use crate::kmer::Kmer;
+
+/// The sorted unique kmers of the ground truth, against which the kmers of test tigs are checked.
+///
+/// The kmers are canonical unless they are strand specific.
+/// Once built, the index answers membership queries by binary search,
+/// such that chunks of test kmers can be checked one after the other without reloading the ground truth.
+#[derive(Debug, Clone)]
+pub struct GroundTruthIndex<KmerType> {
+    kmers: Vec<KmerType>,
+    strand_specific: bool,
+}
+
+/// The outcome of checking a chunk of test kmers against a [`GroundTruthIndex`].
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq)]
+pub struct ChunkReport {
+    /// The number of kmers in the chunk, including duplicates.
+    pub kmer_count: usize,
+    /// The number of kmers in the chunk that are missing in the ground truth, including duplicates.
+    pub superfluous_kmer_count: usize,
+}
+
+impl<KmerType: Kmer> GroundTruthIndex<KmerType> {
+    /// Build the index from sorted unique kmers, which must be canonical unless `strand_specific` is true.
+    pub fn new(kmers: Vec<KmerType>, strand_specific: bool) -> Self {
+        debug_assert!(kmers.is_sorted());
+        Self {
+            kmers,
+            strand_specific,
+        }
+    }
+
+    /// The sorted unique kmers of the ground truth.
+    pub fn kmers(&self) -> &[KmerType] {
+        &self.kmers
+    }
+
+    /// Returns true if the kmer is contained in the ground truth.
+    ///
+    /// The kmer must be canonical unless the index is strand specific.
+    pub fn contains(&self, kmer: &KmerType) -> bool {
+        self.kmers.binary_search(kmer).is_ok()
+    }
+
+    /// Returns true if the kmer has a predecessor or successor in the ground truth.
+    ///
+    /// Unless the index is strand specific, the reverse complements of the predecessors and successors are considered as well.
+    pub fn has_superstring(&self, kmer: &KmerType) -> bool {
+        kmer.predecessors()
+            .into_iter()
+            .chain(kmer.successors())
+            .any(|neighbor| {
+                self.contains(&neighbor)
+                    || (!self.strand_specific && self.contains(&neighbor.reverse_complement()))
+            })
+    }
+
+    /// Check a chunk of test kmers against the ground truth.
+    ///
+    /// The kmers do not need to be sorted, unique or canonical.
+    pub fn verify_chunk(&self, chunk: &[KmerType]) -> ChunkReport {
+        let superfluous_kmer_count = chunk
+            .iter()
+            .filter(|&kmer| {
+                if self.strand_specific {
+                    !self.contains(kmer)
+                } else {
+                    !self.contains(&kmer.canonical())
+                }
+            })
+            .count();
+
+        ChunkReport {
+            kmer_count: chunk.len(),
+            superfluous_kmer_count,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ground_truth::{ChunkReport, GroundTruthIndex};
+    use crate::kmer::BitPackedKmer;
+
+    fn kmer(kmer: &str) -> BitPackedKmer<3, u8> {
+        BitPackedKmer::from_iter(kmer.bytes())
+    }
+
+    #[test]
+    fn test_ground_truth_index() {
+        // The canonical kmers of TAAACTG and GGG.
+        let mut kmers = ["TAA", "AAA", "AAC", "AGT", "CAG", "CCC"]
+            .map(kmer)
+            .to_vec();
+        kmers.sort();
+        let index = GroundTruthIndex::new(kmers, false);
+
+        assert!(index.contains(&kmer("AAC")));
+        assert!(!index.contains(&kmer("GTT")));
+        assert!(index.has_superstring(&kmer("AAC")));
+        assert!(index.has_superstring(&kmer("CAG")));
+        assert!(!index.has_superstring(&kmer("GCG")));
+
+        assert_eq!(
+            index.verify_chunk(&["GTT", "TAA", "ACG", "CTG", "ACG"].map(kmer)),
+            ChunkReport {
+                kmer_count: 5,
+                superfluous_kmer_count: 2,
+            }
+        );
+        assert_eq!(index.verify_chunk(&[]), ChunkReport::default());
+
+        let index = GroundTruthIndex::new(vec![kmer("AAC"), kmer("ACT")], true);
+        assert!(index.has_superstring(&kmer("AAC")));
+        assert!(!index.has_superstring(&kmer("GTT")));
+        assert_eq!(
+            index.verify_chunk(&[kmer("AAC"), kmer("GTT")]),
+            ChunkReport {
+                kmer_count: 2,
+                superfluous_kmer_count: 1,
+            }
+        );
+    }
+}
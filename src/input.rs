@@ -0,0 +1,352 @@
+use crate::Config;
+use std::fs::File;
+use std::io::{BufRead, BufReader, Read};
+use std::path::{Path, PathBuf};
+
+/// Opens the input files, optionally attaching a progress indicator to each of them.
+pub struct InputOpener {
+    #[cfg(feature = "progress")]
+    progress: Option<indicatif::MultiProgress>,
+    #[cfg(feature = "mmap")]
+    mmap: bool,
+}
+
+impl InputOpener {
+    #[cfg_attr(
+        not(any(feature = "progress", feature = "mmap")),
+        allow(unused_variables)
+    )]
+    pub fn new(config: &Config) -> Self {
+        Self {
+            #[cfg(feature = "progress")]
+            progress: config.progress.then(indicatif::MultiProgress::new),
+            #[cfg(feature = "mmap")]
+            mmap: config.mmap,
+        }
+    }
+
+    /// Open the file at the given path, decompressing it if necessary.
+    ///
+    /// The `argument_name` is used to report errors.
+    pub fn open(&self, path: &Path, argument_name: &str) -> Input {
+        let file = File::open(path)
+            .unwrap_or_else(|_| panic!("{argument_name} points to a file: {path:?}"));
+        let length = file_length(&file);
+
+        #[cfg(feature = "mmap")]
+        if self.mmap {
+            // Safety: the file must not be modified while it is mapped, which is documented for `--mmap`.
+            let mmap = unsafe { memmap2::Mmap::map(&file) }
+                .unwrap_or_else(|error| panic!("Mapping {path:?} failed: {error}"));
+            let input = std::io::Cursor::new(mmap);
+
+            #[cfg(feature = "progress")]
+            if let Some(progress) = &self.progress {
+                let input = progress_bar(progress, path, &file).wrap_read(input);
+                return decompress(BufReader::new(input), length)
+                    .unwrap_or_else(|error| panic!("Reading {path:?} failed: {error}"));
+            }
+
+            return decompress(input, length)
+                .unwrap_or_else(|error| panic!("Reading {path:?} failed: {error}"));
+        }
+
+        #[cfg(feature = "progress")]
+        if let Some(progress) = &self.progress {
+            let file = progress_bar(progress, path, &file).wrap_read(file);
+            return decompress(BufReader::new(file), length)
+                .unwrap_or_else(|error| panic!("Reading {path:?} failed: {error}"));
+        }
+
+        decompress(BufReader::new(file), length)
+            .unwrap_or_else(|error| panic!("Reading {path:?} failed: {error}"))
+    }
+}
+
+/// An opened input file, which may know its length in advance.
+pub struct Input {
+    reader: Box<dyn Read + Send>,
+    length: Option<u64>,
+}
+
+impl Input {
+    pub fn new(reader: Box<dyn Read + Send>, length: Option<u64>) -> Self {
+        Self { reader, length }
+    }
+
+    /// Read the inputs one after the other as a single input, with a line break after each of them.
+    ///
+    /// The line breaks ensure that the last line of an input does not continue into the next input.
+    /// The length is known if the lengths of all inputs are known.
+    pub fn chain(inputs: impl IntoIterator<Item = Input>) -> Self {
+        let mut reader: Box<dyn Read + Send> = Box::new(std::io::empty());
+        let mut length = Some(0);
+        for input in inputs {
+            length = length
+                .zip(input.length)
+                .map(|(length, input_length)| length + input_length + 1);
+            reader = Box::new(reader.chain(input.reader).chain(&b"\n"[..]));
+        }
+        Self { reader, length }
+    }
+}
+
+impl Read for Input {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        self.reader.read(buf)
+    }
+}
+
+/// A reader that may know how many bytes it yields, such that the number of kmers can be estimated in advance.
+pub trait LengthHint {
+    /// The total number of bytes yielded by the reader, if it is known.
+    fn length_hint(&self) -> Option<u64>;
+}
+
+impl LengthHint for Input {
+    fn length_hint(&self) -> Option<u64> {
+        self.length
+    }
+}
+
+impl LengthHint for &[u8] {
+    fn length_hint(&self) -> Option<u64> {
+        Some(self.len() as u64)
+    }
+}
+
+/// The length of the file, or `None` if it is not a regular file, e.g. a pipe.
+fn file_length(file: &File) -> Option<u64> {
+    file.metadata()
+        .ok()
+        .filter(|metadata| metadata.is_file())
+        .map(|metadata| metadata.len())
+}
+
+/// Read the paths listed in a `--test-tigs-list` manifest, one per line.
+///
+/// Leading and trailing whitespace is removed from each line, and blank lines and lines starting with `#` are ignored.
+pub fn read_test_tigs_list(input: impl BufRead) -> std::io::Result<Vec<PathBuf>> {
+    let mut paths = Vec::new();
+    for line in input.lines() {
+        let line = line?;
+        let line = line.trim();
+        if !line.is_empty() && !line.starts_with('#') {
+            paths.push(PathBuf::from(line));
+        }
+    }
+    Ok(paths)
+}
+
+/// Detect the compression format of the input from its magic bytes, and decompress it accordingly.
+///
+/// Inputs that are not compressed, or whose compression format is not supported, are returned as they are.
+/// The given length of the input is kept only for those, since the decompressed length is unknown.
+pub fn decompress(
+    mut input: impl BufRead + Send + 'static,
+    length: Option<u64>,
+) -> std::io::Result<Input> {
+    let magic = input.fill_buf()?;
+
+    // Parallel compressors like pigz write multiple concatenated gzip members, which are all read.
+    #[cfg(feature = "gzip")]
+    if magic.starts_with(&[0x1F, 0x8B]) {
+        return Ok(Input::new(
+            Box::new(flate2::bufread::MultiGzDecoder::new(input)),
+            None,
+        ));
+    }
+
+    #[cfg(feature = "bzip2")]
+    if magic.starts_with(b"BZh") {
+        return Ok(Input::new(
+            Box::new(bzip2::bufread::BzDecoder::new(input)),
+            None,
+        ));
+    }
+
+    #[cfg(feature = "zstd")]
+    if magic.starts_with(&[0x28, 0xB5, 0x2F, 0xFD]) {
+        return Ok(Input::new(
+            Box::new(zstd::Decoder::with_buffer(input)?),
+            None,
+        ));
+    }
+
+    #[cfg(not(all(feature = "gzip", feature = "bzip2", feature = "zstd")))]
+    if magic.starts_with(&[0x1F, 0x8B])
+        || magic.starts_with(b"BZh")
+        || magic.starts_with(&[0x28, 0xB5, 0x2F, 0xFD])
+    {
+        log::warn!("Input seems to be compressed, but support for its compression format was not compiled in");
+    }
+
+    Ok(Input::new(Box::new(input), length))
+}
+
+/// Create a progress bar for reading the given file.
+///
+/// If the length of the file is known, then the progress bar shows the fraction of bytes read.
+/// Otherwise, e.g. for pipes, it shows a spinner with the number of bytes read per second.
+#[cfg(feature = "progress")]
+fn progress_bar(
+    progress: &indicatif::MultiProgress,
+    path: &Path,
+    file: &File,
+) -> indicatif::ProgressBar {
+    use indicatif::{ProgressBar, ProgressFinish, ProgressStyle};
+
+    let progress_bar = if let Some(length) = file_length(file) {
+        ProgressBar::new(length).with_style(
+            ProgressStyle::with_template(
+                "{msg} [{wide_bar}] {bytes}/{total_bytes} ({bytes_per_sec}, {eta})",
+            )
+            .unwrap(),
+        )
+    } else {
+        ProgressBar::new_spinner().with_style(
+            ProgressStyle::with_template("{msg} {spinner} {bytes} ({bytes_per_sec})").unwrap(),
+        )
+    };
+
+    progress.add(
+        progress_bar
+            .with_message(path.display().to_string())
+            .with_finish(ProgressFinish::AndLeave),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::input::{decompress, read_test_tigs_list, Input, LengthHint};
+    use std::io::Read;
+    use std::path::PathBuf;
+
+    const SEQUENCE: &str = ">a\nACGTTGCA\n";
+
+    fn decompressed(compressed: Vec<u8>) -> String {
+        let mut output = String::new();
+        decompress(std::io::Cursor::new(compressed), None)
+            .unwrap()
+            .read_to_string(&mut output)
+            .unwrap();
+        output
+    }
+
+    #[test]
+    fn test_read_test_tigs_list() {
+        let manifest = "# assemblies\na.fa\n\n  b.fa.gz \n#c.fa\nd e.fa\n";
+        assert_eq!(
+            read_test_tigs_list(manifest.as_bytes()).unwrap(),
+            ["a.fa", "b.fa.gz", "d e.fa"].map(PathBuf::from)
+        );
+    }
+
+    #[test]
+    fn test_chain() {
+        let shards = [">a\nACGT", ">b\nTTGCA\n"];
+        let mut input = Input::chain(
+            shards.map(|shard| Input::new(Box::new(shard.as_bytes()), Some(shard.len() as u64))),
+        );
+        let expected = ">a\nACGT\n>b\nTTGCA\n\n";
+        assert_eq!(input.length_hint(), Some(expected.len() as u64));
+        let mut output = String::new();
+        input.read_to_string(&mut output).unwrap();
+        assert_eq!(output, expected);
+
+        let input = Input::chain([
+            Input::new(Box::new(shards[0].as_bytes()), None),
+            Input::new(Box::new(shards[1].as_bytes()), Some(shards[1].len() as u64)),
+        ]);
+        assert_eq!(input.length_hint(), None);
+    }
+
+    #[test]
+    fn test_uncompressed() {
+        assert_eq!(decompressed(SEQUENCE.as_bytes().to_vec()), SEQUENCE);
+    }
+
+    #[test]
+    fn test_length_hint() {
+        let length = Some(SEQUENCE.len() as u64);
+        let input = decompress(std::io::Cursor::new(SEQUENCE.as_bytes().to_vec()), length);
+        assert_eq!(input.unwrap().length_hint(), length);
+
+        #[cfg(feature = "gzip")]
+        {
+            use std::io::Write;
+
+            let mut encoder = flate2::write::GzEncoder::new(Vec::new(), Default::default());
+            encoder.write_all(SEQUENCE.as_bytes()).unwrap();
+            let compressed = encoder.finish().unwrap();
+            let length = Some(compressed.len() as u64);
+            let input = decompress(std::io::Cursor::new(compressed), length);
+            assert_eq!(input.unwrap().length_hint(), None);
+        }
+    }
+
+    #[cfg(feature = "gzip")]
+    #[test]
+    fn test_gzip() {
+        use std::io::Write;
+
+        let mut encoder = flate2::write::GzEncoder::new(Vec::new(), Default::default());
+        encoder.write_all(SEQUENCE.as_bytes()).unwrap();
+        assert_eq!(decompressed(encoder.finish().unwrap()), SEQUENCE);
+    }
+
+    #[cfg(feature = "gzip")]
+    #[test]
+    fn test_concatenated_gzip() {
+        use crate::kmer::BitPackedKmer;
+        use crate::kmer_iterator::{KmerIterator, ParseOptions};
+        use std::io::Write;
+
+        let second_sequence = ">b\nTTGCAAC\n";
+        let mut compressed = Vec::new();
+        for sequence in [SEQUENCE, second_sequence] {
+            let mut encoder = flate2::write::GzEncoder::new(Vec::new(), Default::default());
+            encoder.write_all(sequence.as_bytes()).unwrap();
+            compressed.extend(encoder.finish().unwrap());
+        }
+        assert_eq!(
+            decompressed(compressed.clone()),
+            format!("{SEQUENCE}{second_sequence}")
+        );
+
+        let input = decompress(std::io::Cursor::new(compressed), None).unwrap();
+        let mut iterator = KmerIterator::<_, BitPackedKmer<3, u8>>::new(
+            input,
+            3,
+            ParseOptions {
+                panic_on_parse_error: true,
+                ..Default::default()
+            },
+        );
+        assert_eq!(iterator.by_ref().count(), 11);
+        assert_eq!(iterator.sequence_count(), 2);
+        assert_eq!(
+            iterator.character_count(),
+            "ACGTTGCA".len() + "TTGCAAC".len()
+        );
+    }
+
+    #[cfg(feature = "bzip2")]
+    #[test]
+    fn test_bzip2() {
+        use std::io::Write;
+
+        let mut encoder = bzip2::write::BzEncoder::new(Vec::new(), Default::default());
+        encoder.write_all(SEQUENCE.as_bytes()).unwrap();
+        assert_eq!(decompressed(encoder.finish().unwrap()), SEQUENCE);
+    }
+
+    #[cfg(feature = "zstd")]
+    #[test]
+    fn test_zstd() {
+        assert_eq!(
+            decompressed(zstd::encode_all(SEQUENCE.as_bytes(), 0).unwrap()),
+            SEQUENCE
+        );
+    }
+}
@@ -1,20 +1,78 @@
 use bitvec::vec::BitVec;
 use std::fmt::{Debug, Display, Formatter};
+use std::io::{Read, Write};
 use std::ops::{BitAnd, BitOr, BitOrAssign, Not, Shl, ShlAssign, Shr, ShrAssign};
 
-#[derive(Debug, Clone, Copy, Eq, PartialEq, PartialOrd, Ord)]
+#[derive(Debug, Clone, Copy, Eq, PartialEq, PartialOrd, Ord, Hash)]
 pub struct BitPackedKmer<const K: usize, Integer> {
     kmer: Integer,
 }
 
-#[derive(Debug, Clone, Eq, PartialEq, PartialOrd, Ord)]
+#[derive(Debug, Clone, Eq, PartialEq, PartialOrd, Ord, Hash)]
 pub struct BitPackedVectorKmer {
     kmer: BitVec,
 }
 
+/// A kmer packed into a fixed number of 64-bit words, for kmers that do not fit into a `u128`.
+///
+/// The words are stored from most to least significant, and the kmer occupies the lowest `2 * K` bits.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, PartialOrd, Ord, Hash)]
+pub struct BitPackedArrayKmer<const K: usize, const WORDS: usize> {
+    kmer: [u64; WORDS],
+}
+
+/// Two kmers of the same size that occur with a fixed gap between them, compared as one unit, e.g. a spaced seed.
+///
+/// The gap is not stored, so a pair behaves like the concatenation of its two kmers.
+/// In particular, pairs are ordered lexicographically by the characters of both kmers,
+/// and the reverse complement of a pair is the pair of the reverse complements in swapped order.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, PartialOrd, Ord, Hash)]
+pub struct PairedKmer<KmerType> {
+    first: KmerType,
+    second: KmerType,
+}
+
+/// A kmer over the alphabet `A`, `C`, `G`, `T`, `N` with three bits per character,
+/// for comparing kmers that contain `N` as a fifth character.
+///
+/// `N` is ordered after `T` and is its own complement.
+#[derive(Debug, Clone, Eq, PartialEq, PartialOrd, Ord, Hash)]
+pub struct ThreeBitKmer {
+    kmer: BitVec,
+}
+
+/// An error that occurs when parsing a kmer from ASCII characters.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum KmerParseError {
+    /// The number of characters does not match the kmer size.
+    WrongLength { expected: usize, actual: usize },
+    /// There are no characters, for kmer types whose size is not fixed.
+    Empty,
+    /// A character is not one of `A`, `C`, `G` or `T`.
+    NotADnaCharacter { character: u8, position: usize },
+}
+
+/// A kmer over the alphabet `A`, `C`, `G`, `T`, or `A`, `C`, `G`, `T`, `N` for [`ThreeBitKmer`].
+///
+/// All implementations order kmers of the same size lexicographically by their characters,
+/// such that sorted kmers are in the same order regardless of the kmer type.
 pub trait Kmer: Ord + Sized + Clone {
+    /// Parse a kmer from ASCII characters, failing if it has the wrong length or contains non-ACGT characters.
+    ///
+    /// Unlike [`FromIterator`], this never panics on invalid input.
+    fn from_ascii(ascii: &[u8]) -> Result<Self, KmerParseError>;
+
+    /// The number of characters of this kmer.
+    fn length(&self) -> usize;
+
     fn reverse_complement(&self) -> Self;
 
+    /// Replaces each character by its complement without reversing the kmer.
+    fn complement(&self) -> Self;
+
+    /// Returns the lexicographically smaller of the kmer and its reverse complement.
+    ///
+    /// Since all implementations order kmers lexicographically, all kmer types choose the same representative.
     fn canonical(&self) -> Self {
         let reverse_complement = self.reverse_complement();
         if &reverse_complement < self {
@@ -24,9 +82,100 @@ pub trait Kmer: Ord + Sized + Clone {
         }
     }
 
+    /// Like [`Kmer::canonical`], but consumes the kmer, such that its memory can be reused.
+    fn into_canonical(self) -> Self {
+        self.canonical()
+    }
+
+    /// Returns true if the two kmers are equal up to reverse complement, i.e. if their canonical kmers are equal.
+    fn same_content(&self, other: &Self) -> bool {
+        self.canonical() == other.canonical()
+    }
+
+    /// Prepends the character and drops the last character.
+    ///
+    /// Panics if the character is not one of `A`, `C`, `G` or `T`,
+    /// so parsers should build kmers with [`Kmer::from_ascii`] instead.
     fn predecessor(&self, character: u8) -> Self;
 
+    /// Appends the character and drops the first character.
+    ///
+    /// Panics if the character is not one of `A`, `C`, `G` or `T`,
+    /// so parsers should build kmers with [`Kmer::from_ascii`] instead.
     fn successor(&self, character: u8) -> Self;
+
+    /// The four predecessors of this kmer, in the order `A`, `C`, `G`, `T` of the prepended character.
+    ///
+    /// For [`ThreeBitKmer`], predecessors starting with `N` are not included.
+    fn predecessors(&self) -> [Self; 4] {
+        [b'A', b'C', b'G', b'T'].map(|character| self.predecessor(character))
+    }
+
+    /// The four successors of this kmer, in the order `A`, `C`, `G`, `T` of the appended character.
+    ///
+    /// For [`ThreeBitKmer`], successors ending with `N` are not included.
+    fn successors(&self) -> [Self; 4] {
+        [b'A', b'C', b'G', b'T'].map(|character| self.successor(character))
+    }
+
+    /// Write the bit-packed representation of this kmer, such that [`Kmer::read_packed`] can read it back.
+    ///
+    /// All kmers of the same type and size are written with the same number of bytes.
+    fn write_packed(&self, output: &mut impl Write) -> std::io::Result<()>;
+
+    /// Read a kmer of size `k` that was written by [`Kmer::write_packed`].
+    fn read_packed(input: &mut impl Read, k: usize) -> std::io::Result<Self>;
+
+    /// The number of bytes that a kmer of size `k` occupies in memory, including its heap allocations.
+    fn memory_size(_k: usize) -> usize {
+        std::mem::size_of::<Self>()
+    }
+
+    /// The number of bytes of the integer that stores a kmer of size `k`, if this kmer type is a single integer.
+    fn integer_size(_k: usize) -> Option<usize> {
+        None
+    }
+
+    /// Create a kmer of size `k` from the little-endian bytes of the integer that stores it,
+    /// in the 2-bit encoding of [`BitPackedKmer::as_integer`].
+    ///
+    /// Returns `None` if this kmer type is not a single integer, or if bits above the lowest `2 * k` bits are set.
+    fn from_le_integer_bytes(_bytes: &[u8], _k: usize) -> Option<Self> {
+        None
+    }
+}
+
+impl<const K: usize, Integer> BitPackedKmer<K, Integer> {
+    /// Fails the build if `K` characters do not fit into `Integer`.
+    ///
+    /// Associated constants are evaluated when a function that uses them is instantiated,
+    /// so each kmer type that is actually constructed is checked.
+    const FITS_INTEGER: () = assert!(
+        2 * K <= 8 * std::mem::size_of::<Integer>(),
+        "the kmer does not fit into the integer type"
+    );
+
+    /// Returns the 2-bit encoding of this kmer as an integer.
+    ///
+    /// The characters are encoded as `A = 0`, `C = 1`, `G = 2` and `T = 3`,
+    /// and the first character occupies the highest two of the lowest `2 * K` bits,
+    /// i.e. the kmer reads from the most to the least significant bits, like e.g. in KMC or Jellyfish.
+    /// All bits above the lowest `2 * K` bits are zero.
+    #[allow(dead_code)]
+    pub fn as_integer(&self) -> Integer
+    where
+        Integer: Copy,
+    {
+        self.kmer
+    }
+
+    /// Create a kmer from its 2-bit encoding as returned by [`Self::as_integer`].
+    ///
+    /// All bits above the lowest `2 * K` bits must be zero.
+    pub fn from_integer(integer: Integer) -> Self {
+        let () = Self::FITS_INTEGER;
+        Self { kmer: integer }
+    }
 }
 
 impl<
@@ -35,7 +184,7 @@ impl<
     > FromIterator<u8> for BitPackedKmer<K, Integer>
 {
     fn from_iter<Iter: IntoIterator<Item = u8>>(iter: Iter) -> Self {
-        assert!(2 * K <= 8 * std::mem::size_of::<Integer>());
+        let () = Self::FITS_INTEGER;
 
         let iter = iter.into_iter();
         let size = iter.size_hint();
@@ -64,21 +213,22 @@ impl FromIterator<u8> for BitPackedVectorKmer {
     fn from_iter<T: IntoIterator<Item = u8>>(iter: T) -> Self {
         let iter = iter.into_iter();
         let kmer = BitVec::with_capacity(iter.size_hint().0 * 2);
-        BitPackedVectorKmer {
-            kmer: iter.fold(kmer, |mut result, character| {
-                let bits = match character {
-                    b'A' => 0,
-                    b'C' => 1,
-                    b'G' => 2,
-                    b'T' => 3,
-                    other => panic!("Not a DNA character: {other}"),
-                };
+        let mut kmer = iter.fold(kmer, |mut result, character| {
+            let bits = match character {
+                b'A' => 0,
+                b'C' => 1,
+                b'G' => 2,
+                b'T' => 3,
+                other => panic!("Not a DNA character: {other}"),
+            };
 
-                result.push(bits & 2 != 0);
-                result.push(bits & 1 != 0);
-                result
-            }),
-        }
+            result.push(bits & 2 != 0);
+            result.push(bits & 1 != 0);
+            result
+        });
+        // The size hint may be loose, and many kmers are kept in memory at once.
+        kmer.shrink_to_fit();
+        BitPackedVectorKmer { kmer }
     }
 }
 
@@ -132,6 +282,103 @@ impl Display for BitPackedVectorKmer {
     }
 }
 
+impl Display for KmerParseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            KmerParseError::WrongLength { expected, actual } => {
+                write!(f, "expected {expected} characters, but got {actual}")
+            }
+            KmerParseError::NotADnaCharacter {
+                character,
+                position,
+            } => write!(
+                f,
+                "not a DNA character at position {position}: {:?}",
+                char::from(*character)
+            ),
+            KmerParseError::Empty => write!(f, "expected at least one character"),
+        }
+    }
+}
+
+impl std::error::Error for KmerParseError {}
+
+/// Convert an ASCII DNA character into its two-bit encoding.
+fn character_bits(character: u8, position: usize) -> Result<u8, KmerParseError> {
+    match character {
+        b'A' => Ok(0),
+        b'C' => Ok(1),
+        b'G' => Ok(2),
+        b'T' => Ok(3),
+        character => Err(KmerParseError::NotADnaCharacter {
+            character,
+            position,
+        }),
+    }
+}
+
+/// An integer that can reverse complement a bit-packed kmer stored in its lowest bits.
+pub trait ReverseComplementBits {
+    /// Returns the reverse complement of the kmer of `k` characters stored in the lowest `2 * k` bits.
+    fn reverse_complement_bits(self, k: usize) -> Self;
+}
+
+/// Reverse complement a bit-packed kmer by moving one character at a time.
+fn reverse_complement_loop<
+    Integer: BitAnd<Integer, Output = Integer>
+        + BitOrAssign<Integer>
+        + Not<Output = Integer>
+        + ShlAssign<i32>
+        + ShrAssign<i32>
+        + From<u8>
+        + Copy,
+>(
+    kmer: Integer,
+    k: usize,
+) -> Integer {
+    let mut source = !kmer;
+    let mut result = 0.into();
+    for _ in 0..k {
+        result <<= 2;
+        result |= source & 3.into();
+        source >>= 2;
+    }
+    result
+}
+
+macro_rules! impl_reverse_complement_loop {
+    ($($integer:ty),*) => {$(
+        impl ReverseComplementBits for $integer {
+            fn reverse_complement_bits(self, k: usize) -> Self {
+                reverse_complement_loop(self, k)
+            }
+        }
+    )*};
+}
+
+impl_reverse_complement_loop!(u8, u16, u32);
+
+/// Reverses the characters of the whole integer with a byte swap followed by swapping nibbles and pairs of bits,
+/// which takes a logarithmic instead of a linear number of operations in the kmer size.
+macro_rules! impl_reverse_complement_swap {
+    ($($integer:ty),*) => {$(
+        impl ReverseComplementBits for $integer {
+            fn reverse_complement_bits(self, k: usize) -> Self {
+                const NIBBLES: $integer = <$integer>::MAX / 0xFF * 0x0F;
+                const PAIRS: $integer = <$integer>::MAX / 0xFF * 0x33;
+
+                let mut kmer = (!self).swap_bytes();
+                kmer = ((kmer >> 4) & NIBBLES) | ((kmer & NIBBLES) << 4);
+                kmer = ((kmer >> 2) & PAIRS) | ((kmer & PAIRS) << 2);
+                // The reversed kmer occupies the highest bits, and the complemented unused bits are shifted out.
+                kmer >> (<$integer>::BITS as usize - 2 * k)
+            }
+        }
+    )*};
+}
+
+impl_reverse_complement_swap!(u64, u128);
+
 impl<
         const K: usize,
         Integer: BitAnd<Integer, Output = Integer>
@@ -140,20 +387,48 @@ impl<
             + ShlAssign<i32>
             + ShrAssign<i32>
             + From<u8>
+            + TryInto<u8>
+            + ReverseComplementBits
             + Copy
             + Ord,
     > Kmer for BitPackedKmer<K, Integer>
 {
+    fn from_ascii(ascii: &[u8]) -> Result<Self, KmerParseError> {
+        let () = Self::FITS_INTEGER;
+
+        if ascii.len() != K {
+            return Err(KmerParseError::WrongLength {
+                expected: K,
+                actual: ascii.len(),
+            });
+        }
+
+        let mut kmer = Integer::from(0);
+        for (position, &character) in ascii.iter().enumerate() {
+            kmer <<= 2;
+            kmer |= character_bits(character, position)?.into();
+        }
+
+        Ok(Self { kmer })
+    }
+
+    fn length(&self) -> usize {
+        K
+    }
+
     fn reverse_complement(&self) -> Self {
-        let mut source = !self.kmer;
-        let mut result = 0.into();
-        for _ in 0..K {
-            result <<= 2;
-            result |= source & 3.into();
-            source >>= 2;
+        BitPackedKmer {
+            kmer: self.kmer.reverse_complement_bits(K),
         }
+    }
 
-        BitPackedKmer { kmer: result }
+    fn complement(&self) -> Self {
+        // The high bits that are not used by the kmer must stay zero.
+        let mut mask = !Integer::from(0);
+        mask >>= i32::try_from(8 * std::mem::size_of::<Integer>() - 2 * K).unwrap();
+        BitPackedKmer {
+            kmer: !self.kmer & mask,
+        }
     }
 
     fn predecessor(&self, character: u8) -> Self {
@@ -186,27 +461,165 @@ impl<
         kmer <<= 2;
         kmer |= character_bits.into();
 
-        // Clear high bits.
-        let mut mask = Integer::from(3);
-        mask <<= i32::try_from(K).unwrap() * 2;
-        mask = !mask;
-        kmer = kmer & mask;
+        // Clear high bits. If the kmer fills the whole integer, they were already shifted out,
+        // and shifting the mask by the width of the integer would overflow.
+        if 2 * K < 8 * std::mem::size_of::<Integer>() {
+            let mut mask = Integer::from(3);
+            mask <<= i32::try_from(K).unwrap() * 2;
+            mask = !mask;
+            kmer = kmer & mask;
+        }
 
         Self { kmer }
     }
+
+    fn write_packed(&self, output: &mut impl Write) -> std::io::Result<()> {
+        let size = std::mem::size_of::<Integer>();
+        let mut bytes = [0; 16];
+        let mut source = self.kmer;
+        for (index, byte) in bytes[..size].iter_mut().rev().enumerate() {
+            // Shifting a `u8` by eight bits would overflow.
+            if index > 0 {
+                source >>= 8;
+            }
+            *byte = (source & 0xff.into())
+                .try_into()
+                .unwrap_or_else(|_| unreachable!());
+        }
+
+        output.write_all(&bytes[..size])
+    }
+
+    fn read_packed(input: &mut impl Read, _k: usize) -> std::io::Result<Self> {
+        let () = Self::FITS_INTEGER;
+        let size = std::mem::size_of::<Integer>();
+        let mut bytes = [0; 16];
+        input.read_exact(&mut bytes[..size])?;
+
+        let mut kmer = Integer::from(0);
+        for (index, &byte) in bytes[..size].iter().enumerate() {
+            if index > 0 {
+                kmer <<= 8;
+            }
+            kmer |= byte.into();
+        }
+
+        Ok(Self { kmer })
+    }
+
+    fn integer_size(_k: usize) -> Option<usize> {
+        Some(std::mem::size_of::<Integer>())
+    }
+
+    fn from_le_integer_bytes(bytes: &[u8], _k: usize) -> Option<Self> {
+        let () = Self::FITS_INTEGER;
+        debug_assert_eq!(bytes.len(), std::mem::size_of::<Integer>());
+
+        let mut integer = Integer::from(0);
+        for (index, &byte) in bytes.iter().rev().enumerate() {
+            if index > 0 {
+                integer <<= 8;
+            }
+            integer |= byte.into();
+        }
+
+        // Shifting by the width of the integer would overflow, but then there are no bits above the kmer.
+        if 2 * K < 8 * bytes.len() {
+            let mut high_bits = integer;
+            high_bits >>= (2 * K) as i32;
+            if high_bits != Integer::from(0) {
+                return None;
+            }
+        }
+
+        Some(Self::from_integer(integer))
+    }
+}
+
+impl BitPackedVectorKmer {
+    fn character(&self, position: usize) -> u8 {
+        (u8::from(self.kmer[2 * position]) << 1) | u8::from(self.kmer[2 * position + 1])
+    }
+
+    fn set_character(&mut self, position: usize, bits: u8) {
+        self.kmer.set(2 * position, bits & 2 != 0);
+        self.kmer.set(2 * position + 1, bits & 1 != 0);
+    }
+
+    /// Returns true if the reverse complement is smaller than this kmer, without constructing it.
+    fn is_reverse_complement_smaller(&self) -> bool {
+        let length = self.length();
+        for position in 0..length {
+            let reverse_character = 3 - self.character(length - 1 - position);
+            match reverse_character.cmp(&self.character(position)) {
+                std::cmp::Ordering::Less => return true,
+                std::cmp::Ordering::Greater => return false,
+                std::cmp::Ordering::Equal => {}
+            }
+        }
+        false
+    }
+
+    fn reverse_complement_in_place(&mut self) {
+        let length = self.length();
+        for position in 0..length.div_ceil(2) {
+            let mirrored_position = length - 1 - position;
+            let character = self.character(position);
+            let mirrored_character = self.character(mirrored_position);
+            self.set_character(position, 3 - mirrored_character);
+            self.set_character(mirrored_position, 3 - character);
+        }
+    }
 }
 
 impl Kmer for BitPackedVectorKmer {
+    fn from_ascii(ascii: &[u8]) -> Result<Self, KmerParseError> {
+        if ascii.is_empty() {
+            return Err(KmerParseError::Empty);
+        }
+
+        let mut kmer = BitVec::with_capacity(ascii.len() * 2);
+        for (position, &character) in ascii.iter().enumerate() {
+            let bits = character_bits(character, position)?;
+            kmer.push(bits & 2 != 0);
+            kmer.push(bits & 1 != 0);
+        }
+
+        Ok(Self { kmer })
+    }
+
+    fn length(&self) -> usize {
+        self.kmer.len() / 2
+    }
+
     fn reverse_complement(&self) -> Self {
         assert_eq!(self.kmer.len() % 2, 0);
+        let mut kmer: BitVec = self
+            .kmer
+            .chunks(2)
+            .rev()
+            .flat_map(|bits| [!bits[0], !bits[1]])
+            .collect();
+        // Collecting from a flat map grows the vector without knowing its final length.
+        kmer.shrink_to_fit();
+        Self { kmer }
+    }
+
+    fn complement(&self) -> Self {
         Self {
-            kmer: self
-                .kmer
-                .chunks(2)
-                .rev()
-                .flat_map(|bits| [!bits[0], !bits[1]])
-                .collect(),
+            kmer: !self.kmer.clone(),
+        }
+    }
+
+    fn canonical(&self) -> Self {
+        self.clone().into_canonical()
+    }
+
+    fn into_canonical(mut self) -> Self {
+        if self.is_reverse_complement_smaller() {
+            self.reverse_complement_in_place();
         }
+        self
     }
 
     fn predecessor(&self, character: u8) -> Self {
@@ -243,12 +656,459 @@ impl Kmer for BitPackedVectorKmer {
 
         Self { kmer }
     }
+
+    fn write_packed(&self, output: &mut impl Write) -> std::io::Result<()> {
+        let bytes: Vec<_> = self
+            .kmer
+            .chunks(8)
+            .map(|bits| {
+                bits.iter()
+                    .fold(0u8, |byte, bit| (byte << 1) | u8::from(*bit))
+                    << (8 - bits.len())
+            })
+            .collect();
+        output.write_all(&bytes)
+    }
+
+    fn read_packed(input: &mut impl Read, k: usize) -> std::io::Result<Self> {
+        let mut bytes = vec![0; (2 * k).div_ceil(8)];
+        input.read_exact(&mut bytes)?;
+
+        Ok(Self {
+            kmer: (0..2 * k)
+                .map(|index| bytes[index / 8] & (0x80 >> (index % 8)) != 0)
+                .collect(),
+        })
+    }
+
+    fn memory_size(k: usize) -> usize {
+        // The bits are stored in a heap-allocated buffer of words.
+        std::mem::size_of::<Self>()
+            + (2 * k).div_ceil(usize::BITS as usize) * std::mem::size_of::<usize>()
+    }
+}
+
+impl<const K: usize, const WORDS: usize> BitPackedArrayKmer<K, WORDS> {
+    const EMPTY: Self = {
+        assert!(2 * K <= 64 * WORDS);
+        Self { kmer: [0; WORDS] }
+    };
+
+    /// Returns the word index and the shift of the character at the given position.
+    fn character_location(position: usize) -> (usize, usize) {
+        let offset = 2 * (K - 1 - position);
+        (WORDS - 1 - offset / 64, offset % 64)
+    }
+
+    fn character(&self, position: usize) -> u64 {
+        let (word, shift) = Self::character_location(position);
+        (self.kmer[word] >> shift) & 3
+    }
+
+    /// Set the character at the given position, assuming that its bits are zero.
+    fn set_character(&mut self, position: usize, bits: u64) {
+        let (word, shift) = Self::character_location(position);
+        self.kmer[word] |= bits << shift;
+    }
+
+    fn shift_left(&mut self) {
+        for word in 0..WORDS {
+            let carry = if word + 1 < WORDS {
+                self.kmer[word + 1] >> 62
+            } else {
+                0
+            };
+            self.kmer[word] = (self.kmer[word] << 2) | carry;
+        }
+
+        // Clear high bits.
+        let unused_bits = 64 * WORDS - 2 * K;
+        for word in &mut self.kmer[..unused_bits / 64] {
+            *word = 0;
+        }
+        if unused_bits % 64 != 0 {
+            self.kmer[unused_bits / 64] &= u64::MAX >> (unused_bits % 64);
+        }
+    }
+
+    fn shift_right(&mut self) {
+        for word in (0..WORDS).rev() {
+            let carry = if word > 0 {
+                self.kmer[word - 1] << 62
+            } else {
+                0
+            };
+            self.kmer[word] = (self.kmer[word] >> 2) | carry;
+        }
+    }
+}
+
+impl<const K: usize, const WORDS: usize> FromIterator<u8> for BitPackedArrayKmer<K, WORDS> {
+    fn from_iter<Iter: IntoIterator<Item = u8>>(iter: Iter) -> Self {
+        let iter = iter.into_iter();
+        let size = iter.size_hint();
+        assert_eq!(Some(size.0), size.1);
+        assert_eq!(K, size.0);
+
+        iter.enumerate()
+            .fold(Self::EMPTY, |mut result, (position, character)| {
+                let bits = match character {
+                    b'A' => 0,
+                    b'C' => 1,
+                    b'G' => 2,
+                    b'T' => 3,
+                    other => panic!("Not a DNA character: {other}"),
+                };
+
+                result.set_character(position, bits);
+                result
+            })
+    }
+}
+
+impl<const K: usize, const WORDS: usize> Display for BitPackedArrayKmer<K, WORDS> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        static CHARACTERS: [char; 4] = ['A', 'C', 'G', 'T'];
+
+        for position in 0..K {
+            let character = CHARACTERS[usize::try_from(self.character(position)).unwrap()];
+            write!(f, "{character}")?;
+        }
+
+        Ok(())
+    }
+}
+
+impl<const K: usize, const WORDS: usize> Kmer for BitPackedArrayKmer<K, WORDS> {
+    fn from_ascii(ascii: &[u8]) -> Result<Self, KmerParseError> {
+        if ascii.len() != K {
+            return Err(KmerParseError::WrongLength {
+                expected: K,
+                actual: ascii.len(),
+            });
+        }
+
+        let mut kmer = Self::EMPTY;
+        for (position, &character) in ascii.iter().enumerate() {
+            kmer.set_character(position, character_bits(character, position)?.into());
+        }
+
+        Ok(kmer)
+    }
+
+    fn length(&self) -> usize {
+        K
+    }
+
+    fn reverse_complement(&self) -> Self {
+        let mut result = Self::EMPTY;
+        for position in 0..K {
+            result.set_character(K - 1 - position, 3 - self.character(position));
+        }
+
+        result
+    }
+
+    fn complement(&self) -> Self {
+        let mut result = Self::EMPTY;
+        for position in 0..K {
+            result.set_character(position, 3 - self.character(position));
+        }
+
+        result
+    }
+
+    fn predecessor(&self, character: u8) -> Self {
+        let bits = match character {
+            b'A' => 0,
+            b'C' => 1,
+            b'G' => 2,
+            b'T' => 3,
+            other => panic!("Not a DNA character: {other}"),
+        };
+
+        let mut kmer = *self;
+        kmer.shift_right();
+        kmer.set_character(0, bits);
+        kmer
+    }
+
+    fn successor(&self, character: u8) -> Self {
+        let bits = match character {
+            b'A' => 0,
+            b'C' => 1,
+            b'G' => 2,
+            b'T' => 3,
+            other => panic!("Not a DNA character: {other}"),
+        };
+
+        let mut kmer = *self;
+        kmer.shift_left();
+        kmer.set_character(K - 1, bits);
+        kmer
+    }
+
+    fn write_packed(&self, output: &mut impl Write) -> std::io::Result<()> {
+        for word in self.kmer {
+            output.write_all(&word.to_be_bytes())?;
+        }
+        Ok(())
+    }
+
+    fn read_packed(input: &mut impl Read, _k: usize) -> std::io::Result<Self> {
+        let mut kmer = Self::EMPTY;
+        for word in &mut kmer.kmer {
+            let mut bytes = [0; 8];
+            input.read_exact(&mut bytes)?;
+            *word = u64::from_be_bytes(bytes);
+        }
+        Ok(kmer)
+    }
+}
+
+impl<KmerType: FromIterator<u8>> FromIterator<u8> for PairedKmer<KmerType> {
+    /// Build a pair from the characters of both kmers without the gap, i.e. the first half are the characters of the first kmer.
+    fn from_iter<Iter: IntoIterator<Item = u8>>(iter: Iter) -> Self {
+        let characters: Vec<_> = iter.into_iter().collect();
+        assert_eq!(characters.len() % 2, 0);
+        let (first, second) = characters.split_at(characters.len() / 2);
+
+        Self {
+            first: first.iter().copied().collect(),
+            second: second.iter().copied().collect(),
+        }
+    }
+}
+
+impl<KmerType: Display> Display for PairedKmer<KmerType> {
+    /// Write both kmers separated by a `-` that stands for the gap.
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}-{}", self.first, self.second)
+    }
+}
+
+impl<KmerType: Kmer> Kmer for PairedKmer<KmerType> {
+    /// Parse a pair from the characters of both kmers without the gap, see [`FromIterator`].
+    fn from_ascii(ascii: &[u8]) -> Result<Self, KmerParseError> {
+        let half = ascii.len() / 2;
+        let parse = |characters, offset| {
+            KmerType::from_ascii(characters).map_err(|error| match error {
+                KmerParseError::WrongLength { expected, .. } => KmerParseError::WrongLength {
+                    expected: 2 * expected,
+                    actual: ascii.len(),
+                },
+                KmerParseError::NotADnaCharacter {
+                    character,
+                    position,
+                } => KmerParseError::NotADnaCharacter {
+                    character,
+                    position: position + offset,
+                },
+                KmerParseError::Empty => KmerParseError::Empty,
+            })
+        };
+        let first = parse(&ascii[..half], 0)?;
+        let second = parse(&ascii[half..], half)?;
+
+        if first.length() != second.length() {
+            return Err(KmerParseError::WrongLength {
+                expected: 2 * first.length(),
+                actual: ascii.len(),
+            });
+        }
+        Ok(Self { first, second })
+    }
+
+    fn length(&self) -> usize {
+        self.first.length() + self.second.length()
+    }
+
+    fn reverse_complement(&self) -> Self {
+        Self {
+            first: self.second.reverse_complement(),
+            second: self.first.reverse_complement(),
+        }
+    }
+
+    fn complement(&self) -> Self {
+        Self {
+            first: self.first.complement(),
+            second: self.second.complement(),
+        }
+    }
+
+    /// Panics, since the character that moves into or out of the gap is unknown.
+    ///
+    /// Never called, since `--gap` cannot be combined with `--allow-cuttlefish2-errors` or `--count-k-plus-1`.
+    fn predecessor(&self, _character: u8) -> Self {
+        unreachable!("--gap cannot be combined with --allow-cuttlefish2-errors or --count-k-plus-1")
+    }
+
+    /// Panics, since the character that moves into or out of the gap is unknown.
+    ///
+    /// Never called, since `--gap` cannot be combined with `--allow-cuttlefish2-errors` or `--count-k-plus-1`.
+    fn successor(&self, _character: u8) -> Self {
+        unreachable!("--gap cannot be combined with --allow-cuttlefish2-errors or --count-k-plus-1")
+    }
+
+    fn write_packed(&self, output: &mut impl Write) -> std::io::Result<()> {
+        self.first.write_packed(output)?;
+        self.second.write_packed(output)
+    }
+
+    /// Read a pair whose kmers have `k / 2` characters each, such that `k` matches [`Kmer::length`].
+    fn read_packed(input: &mut impl Read, k: usize) -> std::io::Result<Self> {
+        Ok(Self {
+            first: KmerType::read_packed(input, k / 2)?,
+            second: KmerType::read_packed(input, k / 2)?,
+        })
+    }
+
+    fn memory_size(k: usize) -> usize {
+        2 * KmerType::memory_size(k / 2)
+    }
+}
+
+/// The characters of a [`ThreeBitKmer`], indexed by their 3-bit codes.
+const THREE_BIT_CHARACTERS: [u8; 5] = *b"ACGTN";
+
+/// Returns the 3-bit code of a character of a [`ThreeBitKmer`].
+fn three_bit_code(character: u8, position: usize) -> Result<u8, KmerParseError> {
+    match character {
+        b'N' => Ok(4),
+        character => character_bits(character, position),
+    }
+}
+
+/// Returns the code of the complement of the character with the given 3-bit code.
+fn complement_three_bit_code(code: u8) -> u8 {
+    match code {
+        4 => 4,
+        code => 3 - code,
+    }
+}
+
+impl ThreeBitKmer {
+    fn from_codes(codes: impl IntoIterator<Item = u8>) -> Self {
+        let mut kmer = BitVec::new();
+        for code in codes {
+            kmer.push(code & 4 != 0);
+            kmer.push(code & 2 != 0);
+            kmer.push(code & 1 != 0);
+        }
+        kmer.shrink_to_fit();
+        Self { kmer }
+    }
+
+    fn code(&self, position: usize) -> u8 {
+        (u8::from(self.kmer[3 * position]) << 2)
+            | (u8::from(self.kmer[3 * position + 1]) << 1)
+            | u8::from(self.kmer[3 * position + 2])
+    }
+
+    fn codes(&self) -> impl DoubleEndedIterator<Item = u8> + '_ {
+        (0..self.length()).map(|position| self.code(position))
+    }
+}
+
+impl FromIterator<u8> for ThreeBitKmer {
+    fn from_iter<T: IntoIterator<Item = u8>>(iter: T) -> Self {
+        Self::from_codes(iter.into_iter().enumerate().map(|(position, character)| {
+            three_bit_code(character, position)
+                .unwrap_or_else(|_| panic!("Not a DNA character: {character}"))
+        }))
+    }
+}
+
+impl Display for ThreeBitKmer {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for code in self.codes() {
+            write!(f, "{}", char::from(THREE_BIT_CHARACTERS[usize::from(code)]))?;
+        }
+        Ok(())
+    }
+}
+
+impl Kmer for ThreeBitKmer {
+    /// Unlike for the other kmer types, `N` is a valid character.
+    fn from_ascii(ascii: &[u8]) -> Result<Self, KmerParseError> {
+        if ascii.is_empty() {
+            return Err(KmerParseError::Empty);
+        }
+
+        let codes = ascii
+            .iter()
+            .enumerate()
+            .map(|(position, &character)| three_bit_code(character, position))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Self::from_codes(codes))
+    }
+
+    fn length(&self) -> usize {
+        self.kmer.len() / 3
+    }
+
+    fn reverse_complement(&self) -> Self {
+        Self::from_codes(self.codes().rev().map(complement_three_bit_code))
+    }
+
+    fn complement(&self) -> Self {
+        Self::from_codes(self.codes().map(complement_three_bit_code))
+    }
+
+    fn predecessor(&self, character: u8) -> Self {
+        let code = three_bit_code(character, 0)
+            .unwrap_or_else(|_| panic!("Not a DNA character: {character}"));
+        Self::from_codes(std::iter::once(code).chain(self.codes().take(self.length() - 1)))
+    }
+
+    fn successor(&self, character: u8) -> Self {
+        let code = three_bit_code(character, 0)
+            .unwrap_or_else(|_| panic!("Not a DNA character: {character}"));
+        Self::from_codes(self.codes().skip(1).chain(std::iter::once(code)))
+    }
+
+    fn write_packed(&self, output: &mut impl Write) -> std::io::Result<()> {
+        let bytes: Vec<_> = self
+            .kmer
+            .chunks(8)
+            .map(|bits| {
+                bits.iter()
+                    .fold(0u8, |byte, bit| (byte << 1) | u8::from(*bit))
+                    << (8 - bits.len())
+            })
+            .collect();
+        output.write_all(&bytes)
+    }
+
+    fn read_packed(input: &mut impl Read, k: usize) -> std::io::Result<Self> {
+        let mut bytes = vec![0; (3 * k).div_ceil(8)];
+        input.read_exact(&mut bytes)?;
+
+        let mut kmer: BitVec = (0..3 * k)
+            .map(|index| bytes[index / 8] & (0x80 >> (index % 8)) != 0)
+            .collect();
+        kmer.shrink_to_fit();
+        Ok(Self { kmer })
+    }
+
+    fn memory_size(k: usize) -> usize {
+        // The bits are stored in a heap-allocated buffer of words.
+        std::mem::size_of::<Self>()
+            + (3 * k).div_ceil(usize::BITS as usize) * std::mem::size_of::<usize>()
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::kmer::{BitPackedVectorKmer, Kmer};
+    use crate::kmer::{
+        reverse_complement_loop, BitPackedArrayKmer, BitPackedVectorKmer, Kmer, KmerParseError,
+        PairedKmer, ReverseComplementBits, ThreeBitKmer,
+    };
+    use crate::test_util::Xorshift;
     use crate::BitPackedKmer;
+    use std::collections::HashSet;
+    use std::fmt::{Debug, Display};
 
     #[test]
     fn test_k31_display() {
@@ -367,4 +1227,619 @@ mod tests {
             BitPackedVectorKmer::from_iter("GGA".as_bytes().iter().copied())
         );
     }
+
+    #[test]
+    fn test_predecessors_successors() {
+        let kmer = |kmer: &str| BitPackedKmer::<3, u8>::from_iter(kmer.bytes());
+        assert_eq!(
+            kmer("GGG").successors(),
+            ["GGA", "GGC", "GGG", "GGT"].map(kmer)
+        );
+        assert_eq!(
+            kmer("GGG").predecessors(),
+            ["AGG", "CGG", "GGG", "TGG"].map(kmer)
+        );
+
+        let vector_kmer = |kmer: &str| BitPackedVectorKmer::from_iter(kmer.bytes());
+        assert_eq!(
+            vector_kmer("GGG").successors(),
+            ["GGA", "GGC", "GGG", "GGT"].map(vector_kmer)
+        );
+        assert_eq!(
+            vector_kmer("GGG").predecessors(),
+            ["AGG", "CGG", "GGG", "TGG"].map(vector_kmer)
+        );
+    }
+
+    #[test]
+    fn test_vector_kmer_capacity() {
+        let sequence = "ACGT".repeat(50);
+        let exact_capacity = 400usize.div_ceil(usize::BITS as usize) * usize::BITS as usize;
+
+        // The filter makes the lower bound of the size hint zero.
+        let kmer = BitPackedVectorKmer::from_iter(sequence.bytes().filter(|_| true));
+        assert_eq!(kmer.kmer.capacity(), exact_capacity);
+        assert_eq!(kmer.reverse_complement().kmer.capacity(), exact_capacity);
+    }
+
+    #[test]
+    fn test_array_kmer() {
+        let sequence =
+            "ACAACAACAACAACAACAACAACAACAACAACAACAACAACAACATTTTTTGGGGGGGGGGCCCCCCCCCCACGTACGTAC";
+        let reverse_complement: String = sequence
+            .bytes()
+            .rev()
+            .map(|character| match character {
+                b'A' => 'T',
+                b'C' => 'G',
+                b'G' => 'C',
+                b'T' => 'A',
+                _ => unreachable!(),
+            })
+            .collect();
+        assert_eq!(sequence.len(), 81);
+
+        let kmer = BitPackedArrayKmer::<81, 3>::from_iter(sequence.bytes());
+        assert_eq!(format!("{kmer}"), sequence);
+        assert_eq!(format!("{}", kmer.reverse_complement()), reverse_complement);
+        assert_eq!(
+            kmer.successor(b'G'),
+            BitPackedArrayKmer::from_iter(format!("{}G", &sequence[1..]).bytes())
+        );
+        assert_eq!(
+            kmer.predecessor(b'T'),
+            BitPackedArrayKmer::from_iter(format!("T{}", &sequence[..80]).bytes())
+        );
+        assert_eq!(
+            BitPackedArrayKmer::<81, 3>::from_ascii(sequence.as_bytes()),
+            Ok(kmer)
+        );
+
+        // The order matches the lexicographic order of the kmers.
+        let mut kmers = ["T", "GA", "GC", "AT", "CCC"].map(|prefix| {
+            BitPackedArrayKmer::<81, 3>::from_iter(
+                format!("{prefix}{}", &sequence[prefix.len()..]).bytes(),
+            )
+        });
+        kmers.sort();
+        assert_eq!(
+            kmers.map(|kmer| kmer.to_string()[..3].to_string()),
+            ["ATA", "CCC", "GAA", "GCA", "TCA"]
+        );
+    }
+
+    #[test]
+    fn test_hash() {
+        let kmers = ["AAA", "TTT", "ACA", "TGT", "AAA", "ACC"];
+
+        let bit_packed_kmers: HashSet<_> = kmers
+            .iter()
+            .map(|kmer| BitPackedKmer::<3, u8>::from_iter(kmer.bytes()))
+            .collect();
+        assert_eq!(bit_packed_kmers.len(), 5);
+        let canonical_bit_packed_kmers: HashSet<_> =
+            bit_packed_kmers.iter().map(Kmer::canonical).collect();
+        assert_eq!(canonical_bit_packed_kmers.len(), 3);
+
+        let bit_packed_vector_kmers: HashSet<_> = kmers
+            .iter()
+            .map(|kmer| BitPackedVectorKmer::from_iter(kmer.bytes()))
+            .collect();
+        assert_eq!(bit_packed_vector_kmers.len(), 5);
+        let canonical_bit_packed_vector_kmers: HashSet<_> = bit_packed_vector_kmers
+            .iter()
+            .map(Kmer::canonical)
+            .collect();
+        assert_eq!(canonical_bit_packed_vector_kmers.len(), 3);
+    }
+
+    #[test]
+    fn test_from_ascii() {
+        assert_eq!(
+            BitPackedKmer::<3, u8>::from_ascii(b"ACG"),
+            Ok(BitPackedKmer::<3, u8>::from_iter("ACG".bytes()))
+        );
+        assert_eq!(
+            BitPackedKmer::<3, u8>::from_ascii(b"ACGT"),
+            Err(KmerParseError::WrongLength {
+                expected: 3,
+                actual: 4
+            })
+        );
+        assert_eq!(
+            BitPackedKmer::<3, u8>::from_ascii(b"ANG"),
+            Err(KmerParseError::NotADnaCharacter {
+                character: b'N',
+                position: 1
+            })
+        );
+
+        assert_eq!(
+            BitPackedVectorKmer::from_ascii(b"ACGT"),
+            Ok(BitPackedVectorKmer::from_iter("ACGT".bytes()))
+        );
+        assert_eq!(
+            BitPackedVectorKmer::from_ascii(b""),
+            Err(KmerParseError::Empty)
+        );
+        assert_eq!(
+            BitPackedVectorKmer::from_ascii(b"ACGa"),
+            Err(KmerParseError::NotADnaCharacter {
+                character: b'a',
+                position: 3
+            })
+        );
+    }
+
+    #[test]
+    fn test_vector_canonical() {
+        for kmer in [
+            "A", "T", "AT", "TA", "ACGT", "TTGCA", "ACGTT", "GGGCCC", "TTTAAAC", "CATG",
+        ] {
+            let vector_kmer = BitPackedVectorKmer::from_iter(kmer.bytes());
+            let reverse_complement = vector_kmer.reverse_complement();
+            let expected = if reverse_complement < vector_kmer {
+                reverse_complement
+            } else {
+                vector_kmer.clone()
+            };
+
+            assert_eq!(vector_kmer.canonical(), expected, "kmer: {kmer}");
+            assert_eq!(vector_kmer.into_canonical(), expected, "kmer: {kmer}");
+        }
+    }
+
+    #[test]
+    fn test_from_le_integer_bytes() {
+        let kmer = BitPackedKmer::<3, u8>::from_ascii(b"ACG").unwrap();
+        assert_eq!(BitPackedKmer::<3, u8>::integer_size(3), Some(1));
+        assert_eq!(
+            BitPackedKmer::<3, u8>::from_le_integer_bytes(&[0b00_01_10], 3),
+            Some(kmer)
+        );
+        assert_eq!(
+            BitPackedKmer::<3, u8>::from_le_integer_bytes(&[0b01_00_01_10], 3),
+            None
+        );
+
+        let kmer = BitPackedKmer::<5, u16>::from_ascii(b"TACGT").unwrap();
+        assert_eq!(
+            BitPackedKmer::<5, u16>::from_le_integer_bytes(&0b11_00_01_10_11u16.to_le_bytes(), 5),
+            Some(kmer)
+        );
+        // The kmer fills the whole integer.
+        let kmer = BitPackedKmer::<4, u8>::from_ascii(b"TTTT").unwrap();
+        assert_eq!(
+            BitPackedKmer::<4, u8>::from_le_integer_bytes(&[0xff], 4),
+            Some(kmer)
+        );
+
+        assert_eq!(BitPackedVectorKmer::integer_size(3), None);
+        assert_eq!(BitPackedVectorKmer::from_le_integer_bytes(&[0], 3), None);
+    }
+
+    #[test]
+    fn test_integer_encoding() {
+        let kmer = BitPackedKmer::<3, u8>::from_iter("ACG".bytes());
+        assert_eq!(kmer.as_integer(), 0b00_01_10);
+        assert_eq!(BitPackedKmer::<3, u8>::from_integer(0b00_01_10), kmer);
+        assert_eq!(
+            BitPackedKmer::<3, u8>::from_integer(0b11_11_00)
+                .reverse_complement()
+                .as_integer(),
+            0b11_00_00
+        );
+
+        for kmer in [
+            "ACGTACGTACGTACGTACGTACGTACGTACG",
+            "TTTTTTTTTTTTTTTTTTTTTTTTTTTTTTT",
+            "AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA",
+            "GATTACAGATTACAGATTACAGATTACAGAT",
+        ] {
+            let bit_packed_kmer = BitPackedKmer::<31, u64>::from_iter(kmer.bytes());
+            let integer = bit_packed_kmer.as_integer();
+            assert_eq!(integer >> 62, 0);
+            assert_eq!(
+                BitPackedKmer::<31, u64>::from_integer(integer),
+                bit_packed_kmer
+            );
+            assert_eq!(
+                BitPackedKmer::<31, u64>::from_integer(integer).to_string(),
+                kmer
+            );
+        }
+    }
+
+    #[test]
+    fn test_lexicographic_order() {
+        let mut strings: Vec<String> = random_kmers(70).collect();
+        strings.sort();
+
+        fn sorted_strings<KmerType: Kmer + FromIterator<u8> + Display>(
+            strings: &[String],
+            length: usize,
+        ) -> Vec<String> {
+            let mut kmers: Vec<_> = strings
+                .iter()
+                .map(|string| KmerType::from_iter(string[..length].bytes()))
+                .collect();
+            kmers.sort();
+            kmers.iter().map(ToString::to_string).collect()
+        }
+
+        let prefixes = |length: usize| -> Vec<String> {
+            let mut prefixes: Vec<_> = strings
+                .iter()
+                .map(|string| string[..length].to_string())
+                .collect();
+            prefixes.sort();
+            prefixes
+        };
+
+        assert_eq!(
+            sorted_strings::<BitPackedKmer<5, u16>>(&strings, 5),
+            prefixes(5)
+        );
+        assert_eq!(
+            sorted_strings::<BitPackedKmer<31, u64>>(&strings, 31),
+            prefixes(31)
+        );
+        assert_eq!(
+            sorted_strings::<BitPackedArrayKmer<70, 3>>(&strings, 70),
+            prefixes(70)
+        );
+        assert_eq!(
+            sorted_strings::<BitPackedVectorKmer>(&strings, 70),
+            prefixes(70)
+        );
+    }
+
+    #[test]
+    fn test_canonical_across_types() {
+        fn canonical_string<KmerType: Kmer + FromIterator<u8> + Display>(kmer: &str) -> String {
+            KmerType::from_iter(kmer.bytes()).canonical().to_string()
+        }
+
+        let complement = |character| match character {
+            'A' => 'T',
+            'C' => 'G',
+            'G' => 'C',
+            'T' => 'A',
+            _ => unreachable!(),
+        };
+
+        for index in 0..4usize.pow(7) {
+            let kmer: String = (0..7)
+                .map(|position| ['A', 'C', 'G', 'T'][(index >> (2 * position)) & 3])
+                .collect();
+            let reverse_complement: String = kmer.chars().rev().map(complement).collect();
+            let expected = kmer.clone().min(reverse_complement);
+
+            assert_eq!(canonical_string::<BitPackedKmer<7, u16>>(&kmer), expected);
+            assert_eq!(
+                canonical_string::<BitPackedArrayKmer<7, 1>>(&kmer),
+                expected
+            );
+            assert_eq!(canonical_string::<BitPackedVectorKmer>(&kmer), expected);
+        }
+    }
+
+    #[test]
+    fn test_same_content() {
+        fn same_content<KmerType: Kmer + FromIterator<u8>>(a: &str, b: &str) -> bool {
+            KmerType::from_iter(a.bytes()).same_content(&KmerType::from_iter(b.bytes()))
+        }
+
+        for (a, b, expected) in [
+            // A palindrome.
+            ("AATT", "AATT", true),
+            ("ACGT", "ACGT", true),
+            // A non-palindrome and its reverse complement.
+            ("CAGT", "ACTG", true),
+            ("CAGT", "CAGT", true),
+            // The complement and the reverse are not the same content.
+            ("CAGT", "GTCA", false),
+            ("CAGT", "TGAC", false),
+            // Unrelated kmers.
+            ("AAAA", "CCCC", false),
+            ("AATT", "ACGT", false),
+        ] {
+            assert_eq!(
+                same_content::<BitPackedKmer<4, u8>>(a, b),
+                expected,
+                "{a} {b}"
+            );
+            assert_eq!(
+                same_content::<BitPackedArrayKmer<4, 1>>(a, b),
+                expected,
+                "{a} {b}"
+            );
+            assert_eq!(
+                same_content::<BitPackedVectorKmer>(a, b),
+                expected,
+                "{a} {b}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_length() {
+        fn length<KmerType: Kmer + FromIterator<u8>>(k: usize) -> usize {
+            let kmer: String = "ACGT".repeat(k).chars().take(k).collect();
+            let from_iter = KmerType::from_iter(kmer.bytes());
+            let from_ascii = KmerType::from_ascii(kmer.as_bytes()).unwrap();
+            assert_eq!(from_iter.length(), from_ascii.length());
+            assert_eq!(from_iter.reverse_complement().length(), from_iter.length());
+            from_iter.length()
+        }
+
+        assert_eq!(length::<BitPackedKmer<1, u8>>(1), 1);
+        assert_eq!(length::<BitPackedKmer<31, u64>>(31), 31);
+        assert_eq!(length::<BitPackedArrayKmer<70, 3>>(70), 70);
+        for k in [1, 31, 70, 200] {
+            assert_eq!(length::<BitPackedVectorKmer>(k), k);
+        }
+    }
+
+    /// Deterministic random kmer strings of length k.
+    fn random_kmers(k: usize) -> impl Iterator<Item = String> {
+        let mut random = Xorshift::new(k as u64);
+        (0..300).map(move |_| {
+            (0..k)
+                .map(|_| char::from(random.next_nucleotide()))
+                .collect()
+        })
+    }
+
+    #[test]
+    fn test_reverse_complement_swap() {
+        let mut random = Xorshift::new(0);
+        let mut random = || random.next_u64();
+
+        for k in 1..=32 {
+            for _ in 0..100 {
+                let kmer = random() >> (64 - 2 * k);
+                assert_eq!(
+                    kmer.reverse_complement_bits(k),
+                    reverse_complement_loop(kmer, k),
+                    "k: {k}, kmer: {kmer:#x}"
+                );
+            }
+        }
+        for k in 1..=64 {
+            for _ in 0..100 {
+                let kmer = ((u128::from(random()) << 64) | u128::from(random())) >> (128 - 2 * k);
+                assert_eq!(
+                    kmer.reverse_complement_bits(k),
+                    reverse_complement_loop(kmer, k),
+                    "k: {k}, kmer: {kmer:#x}"
+                );
+            }
+        }
+    }
+
+    /// Checks the reverse complement and the complement of random kmers against ones computed on their strings.
+    fn check_random_reverse_complements<KmerType: Kmer + FromIterator<u8> + Display + Debug>(
+        k: usize,
+    ) {
+        for kmer in random_kmers(k) {
+            let expected_complement: String = kmer
+                .chars()
+                .map(|character| match character {
+                    'A' => 'T',
+                    'C' => 'G',
+                    'G' => 'C',
+                    'T' => 'A',
+                    _ => unreachable!(),
+                })
+                .collect();
+            let expected: String = expected_complement.chars().rev().collect();
+
+            let bit_packed_kmer = KmerType::from_iter(kmer.bytes());
+            let reverse_complement = bit_packed_kmer.reverse_complement();
+            assert_eq!(reverse_complement.to_string(), expected, "kmer: {kmer}");
+            assert_eq!(
+                reverse_complement.reverse_complement(),
+                bit_packed_kmer,
+                "kmer: {kmer}"
+            );
+
+            let complement = bit_packed_kmer.complement();
+            assert_eq!(complement.to_string(), expected_complement, "kmer: {kmer}");
+            assert_eq!(complement.complement(), bit_packed_kmer, "kmer: {kmer}");
+            assert_eq!(
+                complement,
+                KmerType::from_iter(expected_complement.bytes()),
+                "kmer: {kmer}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_random_reverse_complements() {
+        check_random_reverse_complements::<BitPackedKmer<1, u8>>(1);
+        check_random_reverse_complements::<BitPackedKmer<4, u8>>(4);
+        check_random_reverse_complements::<BitPackedKmer<5, u16>>(5);
+        check_random_reverse_complements::<BitPackedKmer<8, u16>>(8);
+        check_random_reverse_complements::<BitPackedKmer<9, u32>>(9);
+        check_random_reverse_complements::<BitPackedKmer<16, u32>>(16);
+        check_random_reverse_complements::<BitPackedKmer<17, u64>>(17);
+        check_random_reverse_complements::<BitPackedKmer<32, u64>>(32);
+        check_random_reverse_complements::<BitPackedKmer<33, u128>>(33);
+        check_random_reverse_complements::<BitPackedKmer<64, u128>>(64);
+        check_random_reverse_complements::<BitPackedArrayKmer<65, 3>>(65);
+        check_random_reverse_complements::<BitPackedArrayKmer<96, 3>>(96);
+        check_random_reverse_complements::<BitPackedArrayKmer<97, 4>>(97);
+        check_random_reverse_complements::<BitPackedArrayKmer<128, 4>>(128);
+        for k in [1, 2, 7, 8, 31, 32, 33, 64, 65, 128, 129, 200] {
+            check_random_reverse_complements::<BitPackedVectorKmer>(k);
+        }
+    }
+
+    /// Checks the predecessors and successors of random kmers against shifting their strings.
+    fn check_random_neighbours<KmerType: Kmer + FromIterator<u8> + Display + Debug>(k: usize) {
+        for kmer in random_kmers(k) {
+            let bit_packed_kmer = KmerType::from_iter(kmer.bytes());
+            let first_character = kmer.as_bytes()[0];
+            let last_character = kmer.as_bytes()[k - 1];
+
+            for character in [b'A', b'C', b'G', b'T'] {
+                let successor = bit_packed_kmer.successor(character);
+                assert_eq!(
+                    successor.to_string(),
+                    format!("{}{}", &kmer[1..], char::from(character)),
+                    "kmer: {kmer}"
+                );
+                assert_eq!(
+                    successor.predecessor(first_character),
+                    bit_packed_kmer,
+                    "kmer: {kmer}"
+                );
+
+                let predecessor = bit_packed_kmer.predecessor(character);
+                assert_eq!(
+                    predecessor.to_string(),
+                    format!("{}{}", char::from(character), &kmer[..k - 1]),
+                    "kmer: {kmer}"
+                );
+                assert_eq!(
+                    predecessor.successor(last_character),
+                    bit_packed_kmer,
+                    "kmer: {kmer}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_random_neighbours() {
+        check_random_neighbours::<BitPackedKmer<1, u8>>(1);
+        check_random_neighbours::<BitPackedKmer<3, u8>>(3);
+        check_random_neighbours::<BitPackedKmer<4, u8>>(4);
+        check_random_neighbours::<BitPackedKmer<5, u16>>(5);
+        check_random_neighbours::<BitPackedKmer<8, u16>>(8);
+        check_random_neighbours::<BitPackedKmer<9, u32>>(9);
+        check_random_neighbours::<BitPackedKmer<16, u32>>(16);
+        check_random_neighbours::<BitPackedKmer<17, u64>>(17);
+        check_random_neighbours::<BitPackedKmer<31, u64>>(31);
+        check_random_neighbours::<BitPackedKmer<32, u64>>(32);
+        check_random_neighbours::<BitPackedKmer<33, u128>>(33);
+        check_random_neighbours::<BitPackedKmer<64, u128>>(64);
+        check_random_neighbours::<BitPackedArrayKmer<65, 3>>(65);
+        check_random_neighbours::<BitPackedArrayKmer<96, 3>>(96);
+        check_random_neighbours::<BitPackedArrayKmer<97, 4>>(97);
+        check_random_neighbours::<BitPackedArrayKmer<128, 4>>(128);
+        for k in [1, 2, 7, 8, 31, 32, 33, 64, 65, 128, 129, 200] {
+            check_random_neighbours::<BitPackedVectorKmer>(k);
+        }
+    }
+
+    #[test]
+    fn test_memory_size() {
+        assert_eq!(BitPackedKmer::<31, u64>::memory_size(31), 8);
+        assert_eq!(BitPackedKmer::<64, u128>::memory_size(64), 16);
+        assert_eq!(BitPackedArrayKmer::<65, 3>::memory_size(65), 24);
+
+        let vector_kmer_size = std::mem::size_of::<BitPackedVectorKmer>();
+        let word_size = std::mem::size_of::<usize>();
+        assert_eq!(
+            BitPackedVectorKmer::memory_size(3),
+            vector_kmer_size + word_size
+        );
+        assert_eq!(
+            BitPackedVectorKmer::memory_size(129),
+            vector_kmer_size + (258usize).div_ceil(8 * word_size) * word_size
+        );
+        assert_eq!(
+            PairedKmer::<BitPackedVectorKmer>::memory_size(6),
+            2 * BitPackedVectorKmer::memory_size(3)
+        );
+    }
+
+    #[test]
+    fn test_paired_kmer() {
+        type Pair = PairedKmer<BitPackedKmer<3, u8>>;
+        let pair = Pair::from_ascii(b"ACGTTA").unwrap();
+        assert_eq!(pair, Pair::from_iter(b"ACGTTA".iter().copied()));
+        assert_eq!(pair.to_string(), "ACG-TTA");
+        assert_eq!(pair.length(), 6);
+        assert_eq!(pair.reverse_complement().to_string(), "TAA-CGT");
+        assert_eq!(pair.complement().to_string(), "TGC-AAT");
+        assert_eq!(pair.canonical(), pair);
+        assert!(Pair::from_ascii(b"ACGAAA").unwrap() < pair);
+
+        let mut packed = Vec::new();
+        pair.write_packed(&mut packed).unwrap();
+        assert_eq!(Pair::read_packed(&mut packed.as_slice(), 6).unwrap(), pair);
+        let vector_pair = PairedKmer::<BitPackedVectorKmer>::from_ascii(b"ACGTTA").unwrap();
+        packed.clear();
+        vector_pair.write_packed(&mut packed).unwrap();
+        assert_eq!(
+            PairedKmer::<BitPackedVectorKmer>::read_packed(&mut packed.as_slice(), 6).unwrap(),
+            vector_pair
+        );
+
+        assert_eq!(
+            Pair::from_ascii(b"ACGTNA"),
+            Err(KmerParseError::NotADnaCharacter {
+                character: b'N',
+                position: 4
+            })
+        );
+        assert_eq!(
+            Pair::from_ascii(b"ACGTA"),
+            Err(KmerParseError::WrongLength {
+                expected: 6,
+                actual: 5
+            })
+        );
+        assert_eq!(
+            PairedKmer::<BitPackedVectorKmer>::from_ascii(b"ACGTA"),
+            Err(KmerParseError::WrongLength {
+                expected: 4,
+                actual: 5
+            })
+        );
+    }
+
+    #[test]
+    fn test_three_bit_kmer() {
+        let kmer = ThreeBitKmer::from_ascii(b"ACNGT").unwrap();
+        assert_eq!(kmer, ThreeBitKmer::from_iter(b"ACNGT".iter().copied()));
+        assert_eq!(kmer.to_string(), "ACNGT");
+        assert_eq!(kmer.length(), 5);
+        assert_eq!(kmer.reverse_complement().to_string(), "ACNGT");
+        assert_eq!(kmer.complement().to_string(), "TGNCA");
+        assert_eq!(kmer.predecessor(b'N').to_string(), "NACNG");
+        assert_eq!(kmer.successor(b'A').to_string(), "CNGTA");
+        assert_eq!(
+            ThreeBitKmer::from_ascii(b"NNA").unwrap().canonical(),
+            ThreeBitKmer::from_ascii(b"TNN").unwrap()
+        );
+
+        // N is ordered after T.
+        assert!(
+            ThreeBitKmer::from_ascii(b"ACT").unwrap() < ThreeBitKmer::from_ascii(b"ACN").unwrap()
+        );
+        assert!(
+            ThreeBitKmer::from_ascii(b"ANA").unwrap() > ThreeBitKmer::from_ascii(b"ATT").unwrap()
+        );
+
+        let mut packed = Vec::new();
+        kmer.write_packed(&mut packed).unwrap();
+        assert_eq!(packed.len(), 2);
+        assert_eq!(
+            ThreeBitKmer::read_packed(&mut packed.as_slice(), 5).unwrap(),
+            kmer
+        );
+
+        assert_eq!(ThreeBitKmer::from_ascii(b""), Err(KmerParseError::Empty));
+        assert_eq!(
+            ThreeBitKmer::from_ascii(b"ACR"),
+            Err(KmerParseError::NotADnaCharacter {
+                character: b'R',
+                position: 2
+            })
+        );
+    }
 }
@@ -1,7 +1,11 @@
-use log::warn;
-use std::collections::VecDeque;
-use std::io::{BufReader, Read};
-use std::marker::PhantomData;
+use crate::gfa::spell_paths;
+use crate::kmer::Kmer;
+use crate::Config;
+use log::{debug, warn};
+use std::collections::{HashSet, VecDeque};
+use std::fmt::{Display, Formatter};
+use std::io::Read;
+use std::iter::FusedIterator;
 
 #[derive(Debug, Clone, Copy, Eq, PartialEq)]
 enum State {
@@ -10,54 +14,426 @@ enum State {
     GfaSequence,
     FaId,
     FaSequence,
+    KmerLine,
     Eof,
 }
 
+/// The format of an input file, detected from its first sequence.
 #[derive(Debug, Clone, Copy, Eq, PartialEq)]
-enum Format {
+pub enum Format {
+    /// No sequence was read yet.
     None,
     Gfa,
     Fa,
+    /// One kmer and its count per line, separated by a tab.
+    KmerCounts,
+    /// One kmer per line.
+    KmerList,
+    /// Kmers packed into fixed-width little-endian integers, see [`ParseOptions::two_bit_input`].
+    TwoBit,
 }
 
+impl Display for Format {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Format::None => write!(f, "unknown"),
+            Format::Gfa => write!(f, "GFA"),
+            Format::Fa => write!(f, "fasta"),
+            Format::KmerCounts => write!(f, "kmer counts"),
+            Format::KmerList => write!(f, "kmer list"),
+            Format::TwoBit => write!(f, "2-bit packed kmers"),
+        }
+    }
+}
+
+/// How ambiguous IUPAC characters (e.g. `N` or `R`) within sequences are handled.
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq, clap::ValueEnum)]
+pub enum AmbiguousCharacterPolicy {
+    /// Split sequences at ambiguous characters, such that no kmer contains an ambiguous character.
+    #[default]
+    Split,
+    /// Replace each kmer containing ambiguous characters by all kmers it may represent.
+    ///
+    /// Since `N` would expand to all four characters, it still splits the sequence.
+    Expand,
+}
+
+/// The characters of which kmers are built.
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq, clap::ValueEnum)]
+pub enum Alphabet {
+    /// Kmers consist of `A`, `C`, `G` and `T`, and sequences are split at `N`.
+    #[default]
+    Acgt,
+    /// Kmers consist of `A`, `C`, `G`, `T` and `N`, such that kmers containing `N` are compared as well.
+    Acgtn,
+}
+
+/// The format in which the input is parsed.
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq, clap::ValueEnum)]
+pub enum InputFormat {
+    /// Detect the format from the first record.
+    ///
+    /// Fasta records start with `>` and GFA segments with `S`.
+    /// If the first record starts with `A`, `C`, `G` or `T`, then the input is a kmer list,
+    /// or a list of kmer counts if the first line contains a tab.
+    #[default]
+    Auto,
+    /// One kmer per line, where each line is treated as a separate sequence.
+    KmerList,
+    /// One kmer and its count per line, separated by a tab.
+    KmerCounts,
+}
+
+/// Decides which characters are sequence characters, and how each of them is handled.
+///
+/// The default accepts `ACGT` in any case, and splits sequences at all other IUPAC characters.
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq)]
+pub struct NucleotideAlphabet {
+    pub ambiguous_character_policy: AmbiguousCharacterPolicy,
+    /// Treat lowercase (soft-masked) characters as sequence breaks instead of folding them to uppercase.
+    pub respect_softmask: bool,
+    /// Accept `U` and treat it like `T`.
+    pub rna: bool,
+    /// Treat `N` as a fifth base instead of splitting sequences at it.
+    pub n_as_base: bool,
+}
+
+/// How a single character within a sequence is handled.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum CharacterClass {
+    /// One of `ACGT`, or `N` if it is treated as a base, given in uppercase.
+    Base(u8),
+    /// An ambiguous IUPAC character whose kmers are expanded, given in uppercase.
+    Ambiguous(u8),
+    /// A sequence character that no kmer may contain, so the sequence is split at it.
+    Break,
+    /// Not a sequence character.
+    Invalid,
+}
+
+impl NucleotideAlphabet {
+    pub fn classify(&self, character: u8) -> CharacterClass {
+        let character = match character {
+            b'U' if self.rna => b'T',
+            b'u' if self.rna => b't',
+            character => character,
+        };
+        let uppercase = character.to_ascii_uppercase();
+
+        match uppercase {
+            _ if iupac_character_bases(uppercase).is_none() => CharacterClass::Invalid,
+            _ if self.respect_softmask && character.is_ascii_lowercase() => CharacterClass::Break,
+            b'A' | b'C' | b'G' | b'T' => CharacterClass::Base(uppercase),
+            b'N' if self.n_as_base => CharacterClass::Base(uppercase),
+            b'N' => CharacterClass::Break,
+            _ => match self.ambiguous_character_policy {
+                AmbiguousCharacterPolicy::Split => CharacterClass::Break,
+                AmbiguousCharacterPolicy::Expand => CharacterClass::Ambiguous(uppercase),
+            },
+        }
+    }
+}
+
+/// Options that control how the input is parsed into kmers.
+#[derive(Debug, Clone, Default)]
+pub struct ParseOptions {
+    /// Abort instead of printing warnings on parse errors.
+    pub panic_on_parse_error: bool,
+    pub alphabet: NucleotideAlphabet,
+    /// Skip sequences shorter than this.
+    pub min_sequence_length: usize,
+    /// Skip sequences whose ID matches any of these patterns, see [`id_matches_pattern`].
+    pub exclude_id_patterns: Vec<String>,
+    /// Record the length of each sequence, see [`KmerIterator::sequence_lengths`].
+    pub record_sequence_lengths: bool,
+    /// Count sequences whose ID occurred before, see [`KmerIterator::duplicate_id_count`].
+    pub check_duplicate_ids: bool,
+    /// Compare IDs and exclusion patterns in lowercase.
+    pub case_insensitive_ids: bool,
+    /// For GFA inputs, spell the paths and walks instead of reading the segments.
+    pub spell_gfa_paths: bool,
+    /// For kmer count inputs, skip kmers with a lower count.
+    pub min_count: u64,
+    /// Output pairs of kmers separated by this many characters instead of single kmers, see [`crate::kmer::PairedKmer`].
+    pub gap: Option<usize>,
+    pub input_format: InputFormat,
+    /// Read the input as a sequence of kmers, each packed into a little-endian integer
+    /// of the width used by the kmer type, instead of parsing text.
+    ///
+    /// Each kmer is counted as a sequence of k characters.
+    pub two_bit_input: bool,
+    /// Count the G and C characters among the A, C, G and T characters, see [`KmerIterator::gc_content`].
+    pub count_gc_content: bool,
+}
+
+impl ParseOptions {
+    pub fn new(config: &Config) -> Self {
+        Self {
+            panic_on_parse_error: config.panic_on_parse_error,
+            alphabet: NucleotideAlphabet {
+                ambiguous_character_policy: config.ambiguous_characters,
+                respect_softmask: config.respect_softmask,
+                rna: config.rna,
+                n_as_base: config.alphabet == Alphabet::Acgtn,
+            },
+            min_sequence_length: config.min_sequence_length,
+            exclude_id_patterns: config.exclude_id.clone(),
+            check_duplicate_ids: config.check_duplicate_ids,
+            case_insensitive_ids: config.case_insensitive_ids,
+            record_sequence_lengths: config.per_sequence_stats.is_some(),
+            spell_gfa_paths: config.gfa_spell_paths,
+            min_count: config.min_count,
+            gap: config.gap,
+            input_format: config.input_format,
+            two_bit_input: config.two_bit_input,
+            count_gc_content: config.report_gc_content,
+        }
+    }
+}
+
+/// Returns true if the ID contains the pattern, or if the pattern contains `*` or `?`,
+/// if the whole ID matches the pattern as a glob.
+///
+/// In a glob, `*` matches any number of characters and `?` matches exactly one character.
+pub fn id_matches_pattern(id: &[u8], pattern: &[u8]) -> bool {
+    if !pattern.contains(&b'*') && !pattern.contains(&b'?') {
+        return pattern.is_empty() || id.windows(pattern.len()).any(|window| window == pattern);
+    }
+
+    // Backtracking to the last `*` suffices, since a later `*` can match everything an earlier one could.
+    let (mut id_position, mut pattern_position) = (0, 0);
+    let mut last_star = None;
+    while id_position < id.len() {
+        match pattern.get(pattern_position) {
+            Some(b'*') => {
+                last_star = Some((id_position, pattern_position));
+                pattern_position += 1;
+            }
+            Some(&character) if character == b'?' || character == id[id_position] => {
+                id_position += 1;
+                pattern_position += 1;
+            }
+            _ => {
+                let Some((star_id_position, star_pattern_position)) = last_star else {
+                    return false;
+                };
+                last_star = Some((star_id_position + 1, star_pattern_position));
+                id_position = star_id_position + 1;
+                pattern_position = star_pattern_position + 1;
+            }
+        }
+    }
+    pattern[pattern_position..]
+        .iter()
+        .all(|&character| character == b'*')
+}
+
+/// The size statistics of an input that are needed for the report.
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq)]
+pub struct InputCounts {
+    pub character_count: usize,
+    pub sequence_count: usize,
+    /// See [`KmerIterator::kmer_position_count`].
+    pub kmer_position_count: usize,
+    /// The number of A, C, G and T characters, which is only counted if the GC content is counted.
+    pub base_count: usize,
+    /// The number of G and C characters, which is only counted if the GC content is counted.
+    pub gc_count: usize,
+}
+
+impl InputCounts {
+    /// The fraction of G and C characters among the A, C, G and T characters,
+    /// or `None` if no such characters were counted.
+    pub fn gc_content(&self) -> Option<f64> {
+        (self.base_count != 0).then(|| self.gc_count as f64 / self.base_count as f64)
+    }
+}
+
+/// The number of bytes read from the input at once.
+const CHUNK_SIZE: usize = 16 * 1024 * 1024;
+
 pub struct KmerIterator<InputReader: Read, KmerType> {
-    input: BufReader<InputReader>,
+    input: InputReader,
+    /// The total number of bytes of the input, if known, to bound the number of remaining kmers.
+    length_hint: Option<u64>,
+    /// The number of bytes read from the input so far.
+    read_length: u64,
+    /// The bytes read from the input, which are consumed by indexing rather than by reading single bytes.
+    chunk: Vec<u8>,
+    chunk_position: usize,
+    chunk_length: usize,
     k: usize,
     state: State,
     format: Format,
+    /// The characters of the current window, which is one kmer, or a pair of kmers including the gap.
     buffer: VecDeque<u8>,
-    character_buffer: [u8; 1],
+    /// The characters of the current pair of kmers without the gap.
+    pair_characters: Vec<u8>,
     sequence_count: usize,
     character_count: usize,
-    panic_on_parse_error: bool,
-    kmer_type: PhantomData<KmerType>,
+    /// The number of A, C, G and T characters, if the GC content is counted.
+    base_count: usize,
+    /// The number of G and C characters, if the GC content is counted.
+    gc_count: usize,
+    options: ParseOptions,
+    sequence_break: bool,
+    sequence_has_kmer: bool,
+    short_sequence_count: usize,
+    short_sequence_character_count: usize,
+    /// The character count when the last sequence was finished.
+    finished_character_count: usize,
+    /// The length of each sequence, if sequence lengths are recorded.
+    sequence_lengths: Vec<usize>,
+    pending_kmers: Vec<KmerType>,
+    expanded_kmer_count: usize,
+    lookahead: VecDeque<u8>,
+    skipped_sequence_count: usize,
+    /// The header or name of the current sequence, which is only recorded if IDs are excluded or checked for duplicates.
+    id: Vec<u8>,
+    excluded_sequence_count: usize,
+    /// The IDs of all sequences read so far, if duplicate IDs are checked.
+    /// True if the recorded ID was ended by whitespace, such that the rest of the header is not recorded.
+    id_complete: bool,
+    seen_ids: HashSet<Vec<u8>>,
+    duplicate_id_count: usize,
+    low_count_kmer_count: usize,
+    /// The number of GFA segments whose sequence is `*`, which are not counted as sequences.
+    missing_sequence_segment_count: usize,
+    paths_spelled: bool,
+    /// True if the next character is the first character of a line.
+    line_start: bool,
 }
 
 impl<InputReader: Read, KmerType> KmerIterator<InputReader, KmerType> {
-    pub fn new(input: InputReader, k: usize, panic_on_parse_error: bool) -> Self {
+    pub fn new(input: InputReader, k: usize, mut options: ParseOptions) -> Self {
+        if options.case_insensitive_ids {
+            options
+                .exclude_id_patterns
+                .iter_mut()
+                .for_each(|pattern| pattern.make_ascii_lowercase());
+        }
+
         Self {
-            input: BufReader::with_capacity(16 * 1024 * 1024, input),
+            input,
+            length_hint: None,
+            read_length: 0,
+            chunk: vec![0; CHUNK_SIZE],
+            chunk_position: 0,
+            chunk_length: 0,
             k,
             state: State::None,
-            format: Format::None,
+            format: match options.input_format {
+                _ if options.two_bit_input => Format::TwoBit,
+                InputFormat::Auto => Format::None,
+                InputFormat::KmerList => Format::KmerList,
+                InputFormat::KmerCounts => Format::KmerCounts,
+            },
             buffer: Default::default(),
-            character_buffer: Default::default(),
+            pair_characters: Vec::new(),
             sequence_count: 0,
             character_count: 0,
-            panic_on_parse_error,
-            kmer_type: Default::default(),
+            base_count: 0,
+            gc_count: 0,
+            options,
+            sequence_break: false,
+            sequence_has_kmer: false,
+            short_sequence_count: 0,
+            short_sequence_character_count: 0,
+            finished_character_count: 0,
+            sequence_lengths: Vec::new(),
+            pending_kmers: Vec::new(),
+            expanded_kmer_count: 0,
+            lookahead: Default::default(),
+            skipped_sequence_count: 0,
+            id: Vec::new(),
+            id_complete: false,
+            excluded_sequence_count: 0,
+            seen_ids: HashSet::new(),
+            duplicate_id_count: 0,
+            low_count_kmer_count: 0,
+            missing_sequence_segment_count: 0,
+            paths_spelled: false,
+            line_start: true,
+        }
+    }
+
+    /// Set the total number of bytes of the input, such that [`Iterator::size_hint`] can bound the number of kmers.
+    pub fn with_length_hint(mut self, length_hint: Option<u64>) -> Self {
+        self.length_hint = length_hint;
+        self
+    }
+
+    /// Iterate over the kmers together with the index of the sequence they come from.
+    pub fn with_sequence_index(self) -> SequenceIndexedKmers<InputReader, KmerType> {
+        SequenceIndexedKmers {
+            kmer_iterator: self,
         }
     }
 
     fn read_char(&mut self) -> Option<u8> {
-        let read = self.input.read(&mut self.character_buffer).unwrap();
-        if read == 1 {
-            Some(self.character_buffer[0])
-        } else {
-            None
+        if let Some(character) = self.lookahead.pop_front() {
+            return Some(character);
+        }
+
+        self.read_input_char()
+    }
+
+    fn read_input_char(&mut self) -> Option<u8> {
+        if self.chunk_position == self.chunk_length {
+            self.read_chunk();
+            if self.chunk_length == 0 {
+                return None;
+            }
+        }
+
+        let character = self.chunk[self.chunk_position];
+        self.chunk_position += 1;
+        self.line_start = character == b'\n';
+        Some(character)
+    }
+
+    /// Skip the remainder of the current line including the line break, without inspecting its characters.
+    ///
+    /// Returns false if the end of the input was reached instead of a line break.
+    fn skip_line(&mut self) -> bool {
+        while let Some(character) = self.lookahead.pop_front() {
+            if character == b'\n' {
+                self.line_start = true;
+                return true;
+            }
+        }
+
+        loop {
+            if self.chunk_position == self.chunk_length {
+                self.read_chunk();
+                if self.chunk_length == 0 {
+                    return false;
+                }
+            }
+
+            let remaining = &self.chunk[self.chunk_position..self.chunk_length];
+            if let Some(offset) = remaining.iter().position(|&character| character == b'\n') {
+                self.chunk_position += offset + 1;
+                self.line_start = true;
+                return true;
+            }
+            self.chunk_position = self.chunk_length;
         }
     }
 
+    #[cold]
+    fn read_chunk(&mut self) {
+        self.chunk.resize(CHUNK_SIZE, 0);
+        self.chunk_length = self.input.read(&mut self.chunk).unwrap();
+        self.chunk_position = 0;
+        self.read_length += self.chunk_length as u64;
+    }
+
+    /// The format detected from the sequences read so far.
+    pub fn format(&self) -> Format {
+        self.format
+    }
+
     pub fn sequence_count(&self) -> usize {
         self.sequence_count
     }
@@ -65,62 +441,656 @@ impl<InputReader: Read, KmerType> KmerIterator<InputReader, KmerType> {
     pub fn character_count(&self) -> usize {
         self.character_count
     }
+
+    /// The length of each sequence read so far, indexed like the sequences are counted by [`Self::sequence_count`].
+    ///
+    /// This is empty unless sequence lengths are recorded.
+    pub fn sequence_lengths(&self) -> &[usize] {
+        &self.sequence_lengths
+    }
+
+    /// The number of sequences that are shorter than k and hence contain no kmers.
+    pub fn short_sequence_count(&self) -> usize {
+        self.short_sequence_count
+    }
+
+    /// The number of characters from which each kmer is built, which includes the gap of paired kmers.
+    pub fn window_length(&self) -> usize {
+        match self.options.gap {
+            Some(gap) => 2 * self.k + gap,
+            None => self.k,
+        }
+    }
+
+    /// The number of kmer positions in all sequences read so far,
+    /// i.e. the number of kmers including duplicates, but without expanding ambiguous characters.
+    ///
+    /// Sequences are short if they are shorter than the [window length](Self::window_length).
+    pub fn kmer_position_count(&self) -> usize {
+        self.character_count
+            - self.short_sequence_character_count
+            - (self.sequence_count - self.short_sequence_count) * (self.window_length() - 1)
+    }
+
+    pub fn counts(&self) -> InputCounts {
+        InputCounts {
+            character_count: self.character_count(),
+            sequence_count: self.sequence_count(),
+            kmer_position_count: self.kmer_position_count(),
+            base_count: self.base_count,
+            gc_count: self.gc_count,
+        }
+    }
+
+    /// The fraction of G and C characters among the A, C, G and T characters read so far.
+    ///
+    /// This is `None` unless the GC content is counted, or if no such characters were read.
+    /// Ambiguous characters are not counted, and neither are the kmers of 2-bit packed inputs.
+    pub fn gc_content(&self) -> Option<f64> {
+        self.counts().gc_content()
+    }
+
+    /// The number of kmers that were output in addition to one kmer per kmer position,
+    /// due to expanding ambiguous characters.
+    pub fn expanded_kmer_count(&self) -> usize {
+        self.expanded_kmer_count
+    }
+
+    /// The number of sequences that were skipped because they are shorter than the minimum sequence length.
+    pub fn skipped_sequence_count(&self) -> usize {
+        self.skipped_sequence_count
+    }
+
+    /// The number of sequences that were skipped because their ID matches an excluded pattern.
+    pub fn excluded_sequence_count(&self) -> usize {
+        self.excluded_sequence_count
+    }
+
+    /// The number of sequences whose ID equals the ID of an earlier sequence, if duplicate IDs are checked.
+    ///
+    /// Sequences without ID are not counted.
+    pub fn duplicate_id_count(&self) -> usize {
+        self.duplicate_id_count
+    }
+
+    /// Returns true if a line starting with the given character is a line of a kmer list or of kmer counts.
+    ///
+    /// Without a forced input format, these formats are only detected at the start of the file,
+    /// such that e.g. fasta sequence lines after an illegal character are not mistaken for kmers.
+    fn starts_kmer_line(&self, character: u8) -> bool {
+        match self.options.input_format {
+            InputFormat::KmerList | InputFormat::KmerCounts => true,
+            InputFormat::Auto => {
+                matches!(
+                    self.format,
+                    Format::None | Format::KmerList | Format::KmerCounts
+                ) && matches!(character.to_ascii_uppercase(), b'A' | b'C' | b'G' | b'T')
+            }
+        }
+    }
+
+    /// The number of lines of a kmer count input that were skipped because their count is below the minimum count.
+    pub fn low_count_kmer_count(&self) -> usize {
+        self.low_count_kmer_count
+    }
+
+    /// The number of GFA segments that were skipped because their sequence is `*`, i.e. stored elsewhere.
+    ///
+    /// These segments are not counted as sequences.
+    pub fn missing_sequence_segment_count(&self) -> usize {
+        self.missing_sequence_segment_count
+    }
+
+    /// Returns true if the ID of the current sequence should be recorded.
+    fn records_id(&self) -> bool {
+        !self.options.exclude_id_patterns.is_empty() || self.options.check_duplicate_ids
+    }
+
+    /// Returns true if the ID of the current sequence matches an excluded pattern, and counts it as excluded.
+    ///
+    /// If duplicate IDs are checked, then the ID is also counted if it occurred before, even if it is excluded.
+    fn exclude_id(&mut self) -> bool {
+        let id = &self.id;
+        if self.options.check_duplicate_ids && !id.is_empty() && !self.seen_ids.insert(id.to_vec())
+        {
+            debug!(
+                "Found duplicate sequence ID {:?}",
+                String::from_utf8_lossy(id)
+            );
+            self.duplicate_id_count += 1;
+        }
+
+        let excluded = self
+            .options
+            .exclude_id_patterns
+            .iter()
+            .any(|pattern| id_matches_pattern(id, pattern.as_bytes()));
+        self.id.clear();
+        self.id_complete = false;
+        self.excluded_sequence_count += usize::from(excluded);
+        excluded
+    }
+
+    /// Record a character of the current header as part of the ID.
+    ///
+    /// The ID is the first word of the header, which is the whole name for GFA segments.
+    /// If IDs are case-insensitive, then it is recorded in lowercase.
+    fn push_id_character(&mut self, character: u8) {
+        if character.is_ascii_whitespace() {
+            self.id_complete = true;
+        } else if !self.id_complete {
+            self.id.push(if self.options.case_insensitive_ids {
+                character.to_ascii_lowercase()
+            } else {
+                character
+            });
+        }
+    }
+
+    /// Skip the sequence lines of a fasta record, and set the state to what follows them.
+    fn skip_fa_sequence(&mut self) {
+        self.state = loop {
+            match self.read_char() {
+                Some(b'>') => break State::FaId,
+                Some(b'\n') => {}
+                Some(_) => {
+                    if !self.skip_line() {
+                        break State::Eof;
+                    }
+                }
+                None => break State::Eof,
+            }
+        };
+    }
+
+    /// Read ahead the kmer list or kmer count line that starts at the current position, and skip it
+    /// if it contains a count that is below the minimum count or cannot be parsed.
+    ///
+    /// The format is detected from the first line, which is a kmer count line if it contains a tab.
+    /// If the line is not skipped, then its kmer field followed by the line break is replayed by [`Self::read_char`].
+    fn skip_kmer_line(&mut self) -> bool {
+        let mut line: Vec<u8> = self.lookahead.drain(..).collect();
+        let terminator = loop {
+            match self.read_input_char() {
+                Some(b'\n') => break Some(b'\n'),
+                Some(character) => line.push(character),
+                None => break None,
+            }
+        };
+
+        if self.format == Format::None {
+            self.format = if line.contains(&b'\t') {
+                Format::KmerCounts
+            } else {
+                Format::KmerList
+            };
+        }
+
+        let mut fields = line.split(|&character| character == b'\t');
+        let kmer = fields.next().unwrap();
+        if self.format == Format::KmerList {
+            let length = kmer.strip_suffix(b"\r").unwrap_or(kmer).len();
+            if self.options.panic_on_parse_error && length != self.window_length() {
+                match self.options.gap {
+                    Some(_) => panic!(
+                        "Found kmer list line of length {length} instead of 2k + gap = {}",
+                        self.window_length()
+                    ),
+                    None => panic!(
+                        "Found kmer list line of length {length} instead of k = {}",
+                        self.k
+                    ),
+                }
+            }
+            self.lookahead.extend(kmer);
+            self.lookahead.extend(terminator);
+            return false;
+        }
+
+        let count = fields.next().and_then(|count| {
+            std::str::from_utf8(count.strip_suffix(b"\r").unwrap_or(count))
+                .ok()?
+                .parse::<u64>()
+                .ok()
+        });
+
+        let skip = match count {
+            Some(count) => {
+                let skip = count < self.options.min_count;
+                self.low_count_kmer_count += usize::from(skip);
+                skip
+            }
+            None => {
+                let message = format!(
+                    "Found kmer count line without a valid count: {:?}",
+                    String::from_utf8_lossy(&line)
+                );
+                if self.options.panic_on_parse_error {
+                    panic!("{message}");
+                } else {
+                    warn!("{message}, ignoring it");
+                }
+                true
+            }
+        };
+
+        if skip {
+            self.state = if terminator.is_some() {
+                State::None
+            } else {
+                State::Eof
+            };
+        } else {
+            self.lookahead.extend(kmer);
+            self.lookahead.extend(terminator);
+        }
+        skip
+    }
+
+    /// Read ahead until the end of the sequence that starts at the current position,
+    /// and skip it if it is shorter than the minimum sequence length.
+    ///
+    /// If the sequence is skipped, then the state is set to what follows the sequence, and true is returned.
+    /// Otherwise, the read characters are replayed by [`Self::read_char`] and the state is left unchanged.
+    /// A minimum sequence length of at most the window length never skips anything,
+    /// since shorter sequences contain no kmers anyways.
+    fn skip_short_sequence(&mut self, sequence_state: State) -> bool {
+        if self.options.min_sequence_length <= self.window_length() {
+            return false;
+        }
+        // Characters that were already read to check for a missing GFA sequence come first.
+        let mut peeked = std::mem::take(&mut self.lookahead);
+
+        let mut length = 0;
+        let terminator = loop {
+            let Some(character) = peeked.pop_front().or_else(|| self.read_input_char()) else {
+                break None;
+            };
+
+            if self.options.alphabet.classify(character) != CharacterClass::Invalid {
+                length += 1;
+            } else if !(character == b'\r'
+                || (sequence_state == State::FaSequence && character == b'\n'))
+            {
+                break Some(character);
+            }
+            self.lookahead.push_back(character);
+        };
+
+        if length >= self.options.min_sequence_length {
+            self.lookahead.extend(terminator);
+            self.lookahead.extend(peeked);
+            return false;
+        }
+
+        self.lookahead = peeked;
+        self.skipped_sequence_count += 1;
+        self.state = match terminator {
+            None => State::Eof,
+            Some(b'>') if sequence_state == State::FaSequence => State::FaId,
+            Some(_) => State::None,
+        };
+        true
+    }
+
+    /// Skip the current GFA segment if its sequence is `*`, which means that it is stored elsewhere.
+    ///
+    /// Returns false if the segment has a sequence, in which case the characters read to check this are kept.
+    fn skip_missing_gfa_sequence(&mut self) -> bool {
+        let first = self.read_char();
+        if first != Some(b'*') {
+            if let Some(first) = first {
+                self.lookahead.push_front(first);
+            }
+            return false;
+        }
+
+        match self.read_char() {
+            // The rest of the line is skipped in the `None` state.
+            terminator @ (Some(b'\t' | b'\n' | b'\r') | None) => {
+                self.missing_sequence_segment_count += 1;
+                self.state = if terminator.is_none() {
+                    State::Eof
+                } else {
+                    State::None
+                };
+                true
+            }
+            Some(second) => {
+                self.lookahead.push_front(second);
+                self.lookahead.push_front(b'*');
+                false
+            }
+        }
+    }
+
+    /// Append a character to the current sequence, handling it according to the alphabet.
+    ///
+    /// Returns false if the character is not a sequence character.
+    fn push_sequence_character(&mut self, character: u8) -> bool {
+        let character = match self.options.alphabet.classify(character) {
+            CharacterClass::Base(character) => {
+                if self.options.count_gc_content && character != b'N' {
+                    self.base_count += 1;
+                    self.gc_count += usize::from(matches!(character, b'C' | b'G'));
+                }
+                character
+            }
+            CharacterClass::Ambiguous(character) => character,
+            CharacterClass::Break => {
+                self.break_sequence();
+                return true;
+            }
+            CharacterClass::Invalid => return false,
+        };
+
+        if self.sequence_break {
+            // The characters after an ambiguous character form a new sequence.
+            self.sequence_count += 1;
+            self.sequence_break = false;
+            self.sequence_has_kmer = false;
+        }
+        self.buffer.push_back(character);
+        true
+    }
+
+    fn break_sequence(&mut self) {
+        if !self.buffer.is_empty() {
+            self.finish_sequence();
+            self.sequence_break = true;
+        }
+    }
+
+    /// Account for the characters remaining in the buffer at the end of a sequence.
+    fn finish_sequence(&mut self) {
+        // After a sequence break, the sequence was already finished.
+        if !self.sequence_break && !self.sequence_has_kmer {
+            self.short_sequence_count += 1;
+            self.short_sequence_character_count += self.buffer.len();
+        }
+
+        self.character_count += self.buffer.len();
+        self.buffer.clear();
+
+        if self.options.record_sequence_lengths && !self.sequence_break {
+            self.sequence_lengths
+                .push(self.character_count - self.finished_character_count);
+        }
+        self.finished_character_count = self.character_count;
+    }
+}
+
+impl<InputReader: Read, KmerType: FromIterator<u8> + Kmer> KmerIterator<InputReader, KmerType> {
+    /// Read the next kmer of a 2-bit packed input, skipping kmers with bits set above the kmer.
+    fn next_two_bit_kmer(&mut self) -> Option<KmerType> {
+        let size = KmerType::integer_size(self.k)
+            .unwrap_or_else(|| panic!("2-bit packed input is not supported for k = {}", self.k));
+        let mut bytes = [0; 16];
+
+        loop {
+            let mut length = 0;
+            while length < size {
+                let Some(byte) = self.read_input_char() else {
+                    break;
+                };
+                bytes[length] = byte;
+                length += 1;
+            }
+
+            if length < size {
+                self.state = State::Eof;
+                if length > 0 {
+                    let message = format!("Found {length} trailing bytes after the last 2-bit packed kmer of {size} bytes");
+                    if self.options.panic_on_parse_error {
+                        panic!("{message}");
+                    } else {
+                        warn!("{message}, ignoring them");
+                    }
+                }
+                return None;
+            }
+
+            let Some(kmer) = KmerType::from_le_integer_bytes(&bytes[..size], self.k) else {
+                let message = format!(
+                    "Found 2-bit packed kmer {:?} with bits set above the lowest 2k = {} bits",
+                    &bytes[..size],
+                    2 * self.k
+                );
+                if self.options.panic_on_parse_error {
+                    panic!("{message}");
+                } else {
+                    warn!("{message}, ignoring it");
+                }
+                continue;
+            };
+
+            self.sequence_count += 1;
+            self.character_count += self.k;
+            if self.options.record_sequence_lengths {
+                self.sequence_lengths.push(self.k);
+            }
+            self.finished_character_count = self.character_count;
+            return Some(kmer);
+        }
+    }
+
+    /// Build the kmer in the buffer and advance the buffer by one character.
+    ///
+    /// If a gap is given, then the kmer is built from the first and last k characters of the buffer.
+    /// Returns `None` if the buffer contains a character that is neither a base nor an IUPAC character,
+    /// which is handled like a parse error.
+    fn pop_kmer(&mut self) -> Option<KmerType> {
+        self.character_count += 1;
+        self.sequence_has_kmer = true;
+
+        let characters: &[u8] = match self.options.gap {
+            Some(gap) => {
+                let window = self.buffer.make_contiguous();
+                self.pair_characters.clear();
+                self.pair_characters.extend_from_slice(&window[..self.k]);
+                self.pair_characters
+                    .extend_from_slice(&window[self.k + gap..]);
+                &self.pair_characters
+            }
+            None => self.buffer.make_contiguous(),
+        };
+
+        let kmer = if let Ok(kmer) = KmerType::from_ascii(characters) {
+            Some(kmer)
+        } else {
+            let mut expansions = vec![Vec::with_capacity(characters.len())];
+            for &character in characters {
+                let Some(bases) = iupac_character_bases(character) else {
+                    let message = format!("Not a DNA character: {:?}", char::from(character));
+                    if self.options.panic_on_parse_error {
+                        panic!("{message}");
+                    } else {
+                        warn!("{message}, skipping the kmer");
+                    }
+                    self.buffer.pop_front();
+                    return None;
+                };
+                expansions = expansions
+                    .into_iter()
+                    .flat_map(|prefix| {
+                        bases.iter().map(move |&base| {
+                            let mut expansion = prefix.clone();
+                            expansion.push(base);
+                            expansion
+                        })
+                    })
+                    .collect();
+            }
+
+            self.expanded_kmer_count += expansions.len() - 1;
+            self.pending_kmers.extend(
+                expansions
+                    .into_iter()
+                    .map(|expansion| expansion.into_iter().collect()),
+            );
+            self.pending_kmers.pop()
+        };
+
+        self.buffer.pop_front();
+        kmer
+    }
+}
+
+/// Returns the bases represented by an IUPAC character,
+/// or `None` if the character is not an IUPAC character.
+fn iupac_character_bases(character: u8) -> Option<&'static [u8]> {
+    Some(match character {
+        b'A' => b"A",
+        b'C' => b"C",
+        b'G' => b"G",
+        b'T' => b"T",
+        b'R' => b"AG",
+        b'Y' => b"CT",
+        b'S' => b"CG",
+        b'W' => b"AT",
+        b'K' => b"GT",
+        b'M' => b"AC",
+        b'B' => b"CGT",
+        b'D' => b"AGT",
+        b'H' => b"ACT",
+        b'V' => b"ACG",
+        b'N' => b"ACGT",
+        _ => return None,
+    })
 }
 
-impl<InputReader: Read, KmerType: FromIterator<u8>> Iterator
+impl<InputReader: Read, KmerType: FromIterator<u8> + Kmer> Iterator
     for KmerIterator<InputReader, KmerType>
 {
     type Item = KmerType;
 
     fn next(&mut self) -> Option<KmerType> {
+        if let Some(kmer) = self.pending_kmers.pop() {
+            return Some(kmer);
+        }
+        if self.format == Format::TwoBit {
+            return self.next_two_bit_kmer();
+        }
+
+        if self.options.spell_gfa_paths && !self.paths_spelled {
+            // Paths may refer to segments that appear later in the file, so the whole file is read at once.
+            let mut input = Vec::new();
+            self.input.read_to_end(&mut input).unwrap();
+            self.chunk = spell_paths(&input, self.k, self.options.panic_on_parse_error);
+            self.chunk_position = 0;
+            self.chunk_length = self.chunk.len();
+            self.paths_spelled = true;
+        }
+
         while self.state != State::Eof {
             match self.state {
-                State::None => loop {
-                    let character = self.read_char();
-                    if character == Some(b'S') {
-                        if self.format == Format::Fa {
-                            if self.panic_on_parse_error {
-                                panic!("Found GFA within fasta");
-                            } else {
-                                warn!("Found GFA within fasta");
-                            }
-                        } else {
-                            self.format = Format::Gfa;
+                State::None => {
+                    // Records start only at the beginning of a line, so all other lines
+                    // (e.g. GFA `H`, `L`, `P` and `W` lines) are skipped as a whole.
+                    if !self.line_start && !self.skip_line() {
+                        self.state = State::Eof;
+                        continue;
+                    }
+
+                    match self.read_char() {
+                        Some(character)
+                            if character != b'\n' && self.starts_kmer_line(character) =>
+                        {
+                            self.lookahead.push_front(character);
+                            self.state = State::KmerLine;
                         }
+                        Some(b'S') => self.state = State::GfaS,
+                        Some(b'>') => {
+                            if self.format == Format::None {
+                                self.format = Format::Fa;
+                            } else if self.format != Format::Fa {
+                                if self.options.panic_on_parse_error {
+                                    panic!("Found fasta within {}", self.format);
+                                } else {
+                                    warn!("Found fasta within {}", self.format);
+                                }
+                            }
 
-                        self.state = State::GfaS;
-                        break;
-                    } else if character == Some(b'>') {
-                        if self.format == Format::Gfa {
-                            if self.panic_on_parse_error {
-                                panic!("Found fasta within GFA");
-                            } else {
-                                warn!("Found fasta within GFA");
+                            self.state = State::FaId;
+                        }
+                        Some(b'\n') => {}
+                        Some(_) => {
+                            if !self.skip_line() {
+                                self.state = State::Eof;
                             }
-                        } else {
-                            self.format = Format::Fa;
                         }
-
-                        self.state = State::FaId;
-                        break;
-                    } else if character.is_none() {
-                        self.state = State::Eof;
-                        break;
+                        None => self.state = State::Eof,
                     }
-                },
+                }
+                State::KmerLine => {
+                    if !self.skip_kmer_line() {
+                        // Like a GFA segment sequence, the kmer ends at the end of the line.
+                        self.sequence_count += 1;
+                        self.sequence_break = false;
+                        self.sequence_has_kmer = false;
+                        self.state = State::GfaSequence;
+                    }
+                }
                 State::GfaS => {
+                    // The line is split into tab-separated fields,
+                    // where field 0 must be exactly `S`, field 1 is the name and field 2 is the sequence.
                     let character = self.read_char();
-                    if character == Some(b'\t') {
-                        loop {
-                            let character = self.read_char();
-                            if character == Some(b'\t') {
-                                self.sequence_count += 1;
-                                self.state = State::GfaSequence;
+                    if character != Some(b'\t') {
+                        // Not a segment line, so skip it.
+                        self.state = if character.is_none() {
+                            State::Eof
+                        } else {
+                            State::None
+                        };
+                        continue;
+                    }
+
+                    if self.format == Format::None {
+                        self.format = Format::Gfa;
+                    } else if self.format != Format::Gfa {
+                        if self.options.panic_on_parse_error {
+                            panic!("Found GFA within {}", self.format);
+                        } else {
+                            warn!("Found GFA within {}", self.format);
+                        }
+                    }
+
+                    let record_id = self.records_id();
+                    loop {
+                        match self.read_char() {
+                            Some(b'\t') => {
+                                if record_id && self.exclude_id() {
+                                    // The rest of the line is skipped in the `None` state.
+                                    self.state = State::None;
+                                } else if !self.skip_missing_gfa_sequence()
+                                    && !self.skip_short_sequence(State::GfaSequence)
+                                {
+                                    self.sequence_count += 1;
+                                    self.sequence_break = false;
+                                    self.sequence_has_kmer = false;
+                                    self.state = State::GfaSequence;
+                                }
                                 break;
-                            } else if character.is_none() {
-                                self.state = State::Eof;
+                            }
+                            // The file may end without a newline.
+                            terminator @ (Some(b'\n') | None) => {
+                                if self.options.panic_on_parse_error {
+                                    panic!("Found GFA S line without sequence");
+                                } else {
+                                    warn!("Found GFA S line without sequence");
+                                }
+                                self.state = if terminator.is_none() {
+                                    State::Eof
+                                } else {
+                                    State::None
+                                };
                                 break;
                             }
+                            Some(character) => {
+                                if record_id {
+                                    self.push_id_character(character);
+                                }
+                            }
                         }
                     }
                 }
@@ -128,12 +1098,24 @@ impl<InputReader: Read, KmerType: FromIterator<u8>> Iterator
                     while self.state == State::GfaSequence {
                         let character = self.read_char();
                         if let Some(character) = character {
-                            let character = character.to_ascii_uppercase();
                             match character {
-                                b'A' | b'C' | b'G' | b'T' => {
-                                    self.buffer.push_back(character);
-                                }
+                                _ if self.push_sequence_character(character) => {}
+                                // Carriage returns are ignored, such that CRLF line endings behave like LF line endings.
+                                b'\r' => {}
+                                // The sequence ends at the optional tags or at the end of the line,
+                                // and the rest of the line is skipped in the `None` state.
+                                b'\t' | b'\n' => self.state = State::None,
                                 _ => {
+                                    let message = format!(
+                                        "Found illegal character {:?} in {} sequence",
+                                        char::from(character),
+                                        self.format
+                                    );
+                                    if self.options.panic_on_parse_error {
+                                        panic!("{message}");
+                                    } else {
+                                        warn!("{message}, ignoring the rest of the line");
+                                    }
                                     self.state = State::None;
                                 }
                             }
@@ -141,39 +1123,42 @@ impl<InputReader: Read, KmerType: FromIterator<u8>> Iterator
                             self.state = State::Eof;
                         }
 
-                        assert!(self.buffer.len() <= self.k);
-                        if self.buffer.len() == self.k {
-                            let kmer = self.buffer.iter().copied().collect();
-                            self.character_count += 1;
-                            self.buffer.pop_front();
-                            return Some(kmer);
+                        debug_assert!(self.buffer.len() <= self.window_length());
+                        if self.buffer.len() == self.window_length() {
+                            if let Some(kmer) = self.pop_kmer() {
+                                return Some(kmer);
+                            }
                         }
                     }
 
-                    self.character_count += self.buffer.len();
-                    self.buffer.clear();
+                    self.finish_sequence();
                 }
                 State::FaId => loop {
-                    let character = self.read_char();
-                    if character == Some(b'\n') {
-                        self.sequence_count += 1;
-                        self.state = State::FaSequence;
-                        break;
-                    } else if character.is_none() {
-                        self.state = State::Eof;
+                    // A header at the end of the file without a newline starts an empty record.
+                    let Some(character) = self.read_char().filter(|&character| character != b'\n')
+                    else {
+                        if self.records_id() && self.exclude_id() {
+                            self.skip_fa_sequence();
+                        } else if !self.skip_short_sequence(State::FaSequence) {
+                            self.sequence_count += 1;
+                            self.sequence_break = false;
+                            self.sequence_has_kmer = false;
+                            self.state = State::FaSequence;
+                        }
                         break;
+                    };
+                    if self.records_id() {
+                        self.push_id_character(character);
                     }
                 },
                 State::FaSequence => {
                     while self.state == State::FaSequence {
                         let character = self.read_char();
                         if let Some(character) = character {
-                            let character = character.to_ascii_uppercase();
                             match character {
-                                b'A' | b'C' | b'G' | b'T' => {
-                                    self.buffer.push_back(character);
-                                }
-                                b'\n' => { /* ignore newlines */ }
+                                _ if self.push_sequence_character(character) => {}
+                                // Carriage returns are ignored, such that CRLF line endings behave like LF line endings.
+                                b'\n' | b'\r' => { /* ignore newlines */ }
                                 b'>' => {
                                     self.state = State::FaId;
                                 }
@@ -185,44 +1170,152 @@ impl<InputReader: Read, KmerType: FromIterator<u8>> Iterator
                             self.state = State::Eof;
                         }
 
-                        assert!(self.buffer.len() <= self.k);
-                        if self.buffer.len() == self.k {
-                            let kmer = self.buffer.iter().copied().collect();
-                            self.character_count += 1;
-                            self.buffer.pop_front();
-                            return Some(kmer);
+                        debug_assert!(self.buffer.len() <= self.window_length());
+                        if self.buffer.len() == self.window_length() {
+                            if let Some(kmer) = self.pop_kmer() {
+                                return Some(kmer);
+                            }
                         }
                     }
 
-                    self.character_count += self.buffer.len();
-                    self.buffer.clear();
+                    self.finish_sequence();
                 }
                 State::Eof => unreachable!("Loop is not entered when self.state == State::Eof"),
             }
         }
 
-        assert_eq!(self.state, State::Eof);
-        if self.format == Format::None {
-            if self.panic_on_parse_error {
-                panic!("Found no kmers");
-            } else {
-                warn!("Found no kmers");
-            }
-        }
+        debug_assert_eq!(self.state, State::Eof);
         None
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use crate::{initialise_logging, BitPackedKmer, KmerIterator};
-    use log::LevelFilter;
+    /// Each kmer except for expanded ones ends with a different character,
+    /// so the number of remaining kmers is at most the number of remaining bytes.
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let pending_kmer_count = self.pending_kmers.len();
+        if self.state == State::Eof {
+            return (pending_kmer_count, Some(pending_kmer_count));
+        }
+        if self.options.alphabet.ambiguous_character_policy == AmbiguousCharacterPolicy::Expand {
+            return (pending_kmer_count, None);
+        }
+
+        let unread_length = if self.paths_spelled {
+            // The spelled paths replaced the input and are all in the chunk.
+            Some(0)
+        } else if self.options.spell_gfa_paths {
+            None
+        } else {
+            self.length_hint
+                .map(|length| length.saturating_sub(self.read_length))
+        };
+        let upper_bound = unread_length.and_then(|unread_length| {
+            usize::try_from(unread_length).ok()?.checked_add(
+                self.chunk_length - self.chunk_position + self.lookahead.len() + pending_kmer_count,
+            )
+        });
+        (pending_kmer_count, upper_bound)
+    }
+}
+
+impl<InputReader: Read, KmerType: FromIterator<u8> + Kmer> FusedIterator
+    for KmerIterator<InputReader, KmerType>
+{
+}
+
+/// An iterator over pairs of a kmer and the 0-based index of the sequence it comes from.
+///
+/// Sequences are indexed like they are counted by [`KmerIterator::sequence_count`],
+/// so sequences that are split at ambiguous characters get one index per part,
+/// and skipped or excluded sequences get no index.
+pub struct SequenceIndexedKmers<InputReader: Read, KmerType> {
+    kmer_iterator: KmerIterator<InputReader, KmerType>,
+}
+
+impl<InputReader: Read, KmerType> SequenceIndexedKmers<InputReader, KmerType> {
+    /// The underlying kmer iterator, e.g. to access its counts.
+    pub fn kmer_iterator(&self) -> &KmerIterator<InputReader, KmerType> {
+        &self.kmer_iterator
+    }
+}
+
+impl<InputReader: Read, KmerType: FromIterator<u8> + Kmer> Iterator
+    for SequenceIndexedKmers<InputReader, KmerType>
+{
+    type Item = (KmerType, usize);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let kmer = self.kmer_iterator.next()?;
+        // The sequence of a kmer is counted before the kmer is yielded.
+        Some((kmer, self.kmer_iterator.sequence_count() - 1))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.kmer_iterator.size_hint()
+    }
+}
+
+impl<InputReader: Read, KmerType: FromIterator<u8> + Kmer> FusedIterator
+    for SequenceIndexedKmers<InputReader, KmerType>
+{
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::kmer::PairedKmer;
+    use crate::kmer_iterator::{
+        id_matches_pattern, AmbiguousCharacterPolicy, CharacterClass, Format, InputFormat,
+        NucleotideAlphabet, ParseOptions,
+    };
+    use crate::test_util::Xorshift;
+    use crate::{initialise_logging, BitPackedKmer, KmerIterator};
+    use log::LevelFilter;
+    use std::io::Read;
+
+    /// A reader that returns at most one byte per call, to exercise refilling the chunk.
+    struct SingleByteReader<'a>(&'a [u8]);
+
+    impl Read for SingleByteReader<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            let Some((&first, rest)) = self.0.split_first() else {
+                return Ok(0);
+            };
+            if buf.is_empty() {
+                return Ok(0);
+            }
+            buf[0] = first;
+            self.0 = rest;
+            Ok(1)
+        }
+    }
+
+    /// A deterministic random fasta file of roughly the given size.
+    fn synthetic_fasta(size: usize) -> Vec<u8> {
+        let mut random = Xorshift::new(0);
+        let mut fasta = Vec::with_capacity(size + 100);
+        let mut index = 0;
+        while fasta.len() < size {
+            fasta.extend_from_slice(format!(">{index}\n").as_bytes());
+            for _ in 0..10_000 {
+                fasta.push(random.next_nucleotide());
+            }
+            fasta.push(b'\n');
+            index += 1;
+        }
+        fasta
+    }
 
     #[test]
     fn test_simple_fa() {
         initialise_logging(LevelFilter::Debug);
         let tigs = ">b\nAAAC\n>\nCAGT\n>a\nCCC";
-        let mut iterator = KmerIterator::<_, BitPackedKmer<3, u8>>::new(tigs.as_bytes(), 3, true);
+        let mut iterator = KmerIterator::<_, BitPackedKmer<3, u8>>::new(
+            tigs.as_bytes(),
+            3,
+            ParseOptions {
+                panic_on_parse_error: true,
+                ..Default::default()
+            },
+        );
         let kmers: Vec<_> = iterator.by_ref().collect();
         assert_eq!(
             kmers,
@@ -237,4 +1330,1012 @@ mod tests {
         assert_eq!(iterator.sequence_count(), 3);
         assert_eq!(iterator.character_count(), 11);
     }
+
+    #[test]
+    fn test_ambiguous_characters() {
+        initialise_logging(LevelFilter::Debug);
+        let tigs = ">\nAACNNCCGT\nRTT\n>\nNAAAC";
+
+        let mut iterator = KmerIterator::<_, BitPackedKmer<3, u8>>::new(
+            tigs.as_bytes(),
+            3,
+            ParseOptions {
+                panic_on_parse_error: true,
+                ..Default::default()
+            },
+        );
+        let kmers: Vec<_> = iterator.by_ref().collect();
+        assert_eq!(
+            kmers,
+            ["AAC", "CCG", "CGT", "AAA", "AAC"]
+                .map(|kmer| BitPackedKmer::from_iter(kmer.bytes()))
+                .to_vec()
+        );
+        assert_eq!(iterator.sequence_count(), 4);
+        assert_eq!(iterator.character_count(), 13);
+        assert_eq!(iterator.expanded_kmer_count(), 0);
+
+        let mut iterator = KmerIterator::<_, BitPackedKmer<3, u8>>::new(
+            tigs.as_bytes(),
+            3,
+            ParseOptions {
+                panic_on_parse_error: true,
+                alphabet: NucleotideAlphabet {
+                    ambiguous_character_policy: AmbiguousCharacterPolicy::Expand,
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+        );
+        let mut kmers: Vec<_> = iterator.by_ref().collect();
+        kmers.sort_unstable();
+        let mut expected = [
+            "AAC", "CCG", "CGT", "GTA", "GTG", "TAT", "TGT", "ATT", "GTT", "AAA", "AAC",
+        ]
+        .map(|kmer| BitPackedKmer::from_iter(kmer.bytes()))
+        .to_vec();
+        expected.sort_unstable();
+        assert_eq!(kmers, expected);
+        assert_eq!(iterator.sequence_count(), 3);
+        assert_eq!(iterator.character_count(), 14);
+        assert_eq!(iterator.expanded_kmer_count(), 3);
+    }
+
+    #[test]
+    fn test_pop_kmer_invalid_character() {
+        initialise_logging(LevelFilter::Debug);
+        // The parser never buffers invalid characters, so they are inserted directly.
+        let mut iterator =
+            KmerIterator::<_, BitPackedKmer<3, u8>>::new("".as_bytes(), 3, ParseOptions::default());
+        iterator.buffer.extend(b"AXG");
+        assert_eq!(iterator.pop_kmer(), None);
+        assert_eq!(iterator.buffer, b"XG");
+        iterator.buffer.push_back(b'T');
+        assert_eq!(iterator.pop_kmer(), None);
+        assert_eq!(iterator.buffer, b"GT");
+        iterator.buffer.push_back(b'A');
+        assert_eq!(iterator.pop_kmer(), Some(BitPackedKmer::from_iter(*b"GTA")));
+    }
+
+    #[test]
+    fn test_short_sequences() {
+        initialise_logging(LevelFilter::Debug);
+        let tigs = ">\nAAACC\n>\nAC\n>\n\n>\nCCNA\n";
+        let mut iterator = KmerIterator::<_, BitPackedKmer<4, u8>>::new(
+            tigs.as_bytes(),
+            4,
+            ParseOptions {
+                panic_on_parse_error: true,
+                ..Default::default()
+            },
+        );
+        let kmers: Vec<_> = iterator.by_ref().collect();
+        assert_eq!(
+            kmers,
+            ["AAAC", "AACC"]
+                .map(|kmer| BitPackedKmer::from_iter(kmer.bytes()))
+                .to_vec()
+        );
+        assert_eq!(iterator.sequence_count(), 5);
+        assert_eq!(iterator.short_sequence_count(), 4);
+        assert_eq!(iterator.character_count(), 10);
+        assert_eq!(iterator.kmer_position_count(), 2);
+    }
+
+    #[test]
+    fn test_crlf_line_endings() {
+        initialise_logging(LevelFilter::Debug);
+        for (lf_tigs, options) in [
+            (">a\nAAAC\nCAG\nT\n>b\nGGGG\n", ParseOptions::default()),
+            (
+                ">a\nAAAC\nCAG\nT\n>b\nGG\n>c\nGGGG\n",
+                ParseOptions {
+                    min_sequence_length: 4,
+                    ..Default::default()
+                },
+            ),
+            (
+                "H\tVN:Z:1.0\nS\t1\tAAACCAG\tLN:i:7\nS\t2\tGGGG\n",
+                ParseOptions::default(),
+            ),
+        ] {
+            let crlf_tigs = lf_tigs.replace('\n', "\r\n");
+            let options = ParseOptions {
+                panic_on_parse_error: true,
+                ..options
+            };
+
+            let mut lf_iterator = KmerIterator::<_, BitPackedKmer<3, u8>>::new(
+                lf_tigs.as_bytes(),
+                3,
+                options.clone(),
+            );
+            let mut crlf_iterator =
+                KmerIterator::<_, BitPackedKmer<3, u8>>::new(crlf_tigs.as_bytes(), 3, options);
+            let lf_kmers: Vec<_> = lf_iterator.by_ref().collect();
+            let crlf_kmers: Vec<_> = crlf_iterator.by_ref().collect();
+
+            assert!(lf_kmers.len() >= 7, "{lf_tigs:?}");
+            assert_eq!(lf_kmers, crlf_kmers, "{lf_tigs:?}");
+            assert_eq!(lf_iterator.counts(), crlf_iterator.counts(), "{lf_tigs:?}");
+            assert_eq!(
+                lf_iterator.skipped_sequence_count(),
+                crlf_iterator.skipped_sequence_count()
+            );
+        }
+    }
+
+    #[test]
+    fn test_min_sequence_length() {
+        initialise_logging(LevelFilter::Debug);
+        let tigs = ">\nAAACC\n>\nACG\nT\n>\nCCG\n>\nGGGG";
+        let mut iterator = KmerIterator::<_, BitPackedKmer<3, u8>>::new(
+            tigs.as_bytes(),
+            3,
+            ParseOptions {
+                panic_on_parse_error: true,
+                min_sequence_length: 4,
+                ..Default::default()
+            },
+        );
+        let kmers: Vec<_> = iterator.by_ref().collect();
+        assert_eq!(
+            kmers,
+            ["AAA", "AAC", "ACC", "ACG", "CGT", "GGG", "GGG"]
+                .map(|kmer| BitPackedKmer::from_iter(kmer.bytes()))
+                .to_vec()
+        );
+        assert_eq!(iterator.sequence_count(), 3);
+        assert_eq!(iterator.skipped_sequence_count(), 1);
+        assert_eq!(iterator.character_count(), 13);
+        assert_eq!(iterator.kmer_position_count(), 7);
+
+        let tigs = "S\t1\tAAACC\nS\t2\tCCG\tLN:i:3\nS\t3\tACGT\n";
+        let mut iterator = KmerIterator::<_, BitPackedKmer<3, u8>>::new(
+            tigs.as_bytes(),
+            3,
+            ParseOptions {
+                panic_on_parse_error: true,
+                min_sequence_length: 4,
+                ..Default::default()
+            },
+        );
+        let kmers: Vec<_> = iterator.by_ref().collect();
+        assert_eq!(
+            kmers,
+            ["AAA", "AAC", "ACC", "ACG", "CGT"]
+                .map(|kmer| BitPackedKmer::from_iter(kmer.bytes()))
+                .to_vec()
+        );
+        assert_eq!(iterator.sequence_count(), 2);
+        assert_eq!(iterator.skipped_sequence_count(), 1);
+        assert_eq!(iterator.character_count(), 9);
+    }
+
+    #[test]
+    fn test_exclude_id() {
+        initialise_logging(LevelFilter::Debug);
+        for tigs in [
+            ">contig1\nAAAC\n>plasmid_1 circular\nCCG\nTT\n>\nACG\n>contig2\nGGGG",
+            ">contig1\r\nAAAC\r\n>plasmid_1\r\nCCG\r\nTT\r\n>\r\nACG\r\n>contig2\r\nGGGG\r\n",
+            "S\tcontig1\tAAAC\nS\tplasmid_1\tCCGTT\tLN:i:5\nS\t\tACG\nS\tcontig2\tGGGG\n",
+        ] {
+            let mut iterator = KmerIterator::<_, BitPackedKmer<3, u8>>::new(
+                tigs.as_bytes(),
+                3,
+                ParseOptions {
+                    panic_on_parse_error: true,
+                    exclude_id_patterns: vec!["plasmid".to_string(), "contig?".to_string()],
+                    ..Default::default()
+                },
+            );
+            let kmers: Vec<_> = iterator.by_ref().collect();
+            assert_eq!(
+                kmers,
+                [BitPackedKmer::from_iter(b"ACG".iter().copied())],
+                "tigs: {tigs:?}"
+            );
+            assert_eq!(iterator.sequence_count(), 1);
+            assert_eq!(iterator.excluded_sequence_count(), 3);
+            assert_eq!(iterator.character_count(), 3);
+        }
+    }
+
+    #[test]
+    fn test_gap() {
+        initialise_logging(LevelFilter::Debug);
+        let mut iterator = KmerIterator::<_, PairedKmer<BitPackedKmer<2, u8>>>::new(
+            ">a\nACGTTAC\n>b\nACGT\n>c\nNNACNTT\n".as_bytes(),
+            2,
+            ParseOptions {
+                panic_on_parse_error: true,
+                gap: Some(1),
+                ..Default::default()
+            },
+        );
+        let kmers: Vec<_> = iterator.by_ref().map(|kmer| kmer.to_string()).collect();
+        assert_eq!(kmers, ["AC-TT", "CG-TA", "GT-AC"]);
+        assert_eq!(iterator.window_length(), 5);
+        assert_eq!(iterator.short_sequence_count(), 3);
+        assert_eq!(iterator.kmer_position_count(), 3);
+    }
+
+    #[test]
+    fn test_sequence_lengths() {
+        initialise_logging(LevelFilter::Debug);
+        for tigs in [
+            ">a\nAAACT\nGG\n>b\nAC\n>\n>c\nACGNNTTTTN\n",
+            "S\ta\tAAACTGG\nS\tb\tAC\nS\t\t\nS\tc\tACGNNTTTTN\n",
+        ] {
+            let mut iterator = KmerIterator::<_, BitPackedKmer<3, u8>>::new(
+                tigs.as_bytes(),
+                3,
+                ParseOptions {
+                    panic_on_parse_error: true,
+                    record_sequence_lengths: true,
+                    ..Default::default()
+                },
+            );
+            assert_eq!(iterator.by_ref().count(), 8, "tigs: {tigs:?}");
+            assert_eq!(
+                iterator.sequence_lengths(),
+                [7, 2, 0, 3, 4],
+                "tigs: {tigs:?}"
+            );
+            assert_eq!(iterator.sequence_count(), 5, "tigs: {tigs:?}");
+        }
+    }
+
+    #[test]
+    fn test_gc_content() {
+        initialise_logging(LevelFilter::Debug);
+        for (tigs, count_gc_content, expected_gc_content) in [
+            (">a\nAACT\nGG\n>b\ncaNNGT\n", true, Some(0.5)),
+            ("S\ta\tAACTGG\nS\tb\tcaNNGT\n", true, Some(0.5)),
+            (">a\nAAACTGG\n", false, None),
+            (">a\nNN\n", true, None),
+        ] {
+            let mut iterator = KmerIterator::<_, BitPackedKmer<3, u8>>::new(
+                tigs.as_bytes(),
+                3,
+                ParseOptions {
+                    panic_on_parse_error: true,
+                    count_gc_content,
+                    ..Default::default()
+                },
+            );
+            iterator.by_ref().for_each(drop);
+            assert_eq!(iterator.gc_content(), expected_gc_content, "tigs: {tigs:?}");
+        }
+    }
+
+    #[test]
+    fn test_missing_gfa_sequence() {
+        initialise_logging(LevelFilter::Debug);
+        let tigs = "S\t1\tAAAC\nS\t2\t*\tLN:i:5\nS\t3\t*\r\nS\t4\tCCG\nS\t5\tGGTT\nS\t6\t*";
+        for (min_sequence_length, expected_kmers, expected_sequence_count) in [
+            (0, &["AAA", "AAC", "CCG", "GGT", "GTT"][..], 3),
+            (4, &["AAA", "AAC", "GGT", "GTT"][..], 2),
+        ] {
+            let mut iterator = KmerIterator::<_, BitPackedKmer<3, u8>>::new(
+                tigs.as_bytes(),
+                3,
+                ParseOptions {
+                    panic_on_parse_error: true,
+                    min_sequence_length,
+                    ..Default::default()
+                },
+            );
+            let kmers: Vec<_> = iterator.by_ref().map(|kmer| kmer.to_string()).collect();
+            assert_eq!(kmers, expected_kmers, "{min_sequence_length}");
+            assert_eq!(
+                iterator.sequence_count(),
+                expected_sequence_count,
+                "{min_sequence_length}"
+            );
+            assert_eq!(
+                iterator.missing_sequence_segment_count(),
+                3,
+                "{min_sequence_length}"
+            );
+            assert_eq!(iterator.short_sequence_count(), 0, "{min_sequence_length}");
+        }
+    }
+
+    #[test]
+    fn test_check_duplicate_ids() {
+        initialise_logging(LevelFilter::Debug);
+        for tigs in [
+            ">a\nAAAC\n>b first\nCCG\n>\nACG\n>b second\nGGGG\n>\nTTT\n>a",
+            "S\ta\tAAAC\nS\tb\tCCG\nS\t\tACG\nS\tb\tGGGG\nS\t\tTTT\nS\ta\t*\n",
+        ] {
+            let mut iterator = KmerIterator::<_, BitPackedKmer<3, u8>>::new(
+                tigs.as_bytes(),
+                3,
+                ParseOptions {
+                    panic_on_parse_error: true,
+                    check_duplicate_ids: true,
+                    exclude_id_patterns: vec!["a".to_string()],
+                    ..Default::default()
+                },
+            );
+            assert_eq!(iterator.by_ref().count(), 5, "tigs: {tigs:?}");
+            assert_eq!(iterator.duplicate_id_count(), 2, "tigs: {tigs:?}");
+            assert_eq!(iterator.excluded_sequence_count(), 2, "tigs: {tigs:?}");
+        }
+    }
+
+    #[test]
+    fn test_id_is_first_word_of_header() {
+        initialise_logging(LevelFilter::Debug);
+        for (
+            case_insensitive_ids,
+            expected_count,
+            expected_duplicate_id_count,
+            expected_excluded_count,
+        ) in [(false, 5, 1, 1), (true, 4, 2, 2)]
+        {
+            let mut iterator = KmerIterator::<_, BitPackedKmer<3, u8>>::new(
+                ">seq1 description text\nAAAC\n>seq1\tother description\nCCG\n>SEQ1\nACG\n>Seq2 seq1\nGGGG\n>seq1_plasmid \nTTT\n".as_bytes(),
+                3,
+                ParseOptions {
+                    panic_on_parse_error: true,
+                    check_duplicate_ids: true,
+                    case_insensitive_ids,
+                    exclude_id_patterns: vec!["*2".to_string(), "PLASMID".to_string()],
+                    ..Default::default()
+                },
+            );
+            assert_eq!(
+                iterator.by_ref().count(),
+                expected_count,
+                "case_insensitive_ids: {case_insensitive_ids}"
+            );
+            assert_eq!(
+                iterator.duplicate_id_count(),
+                expected_duplicate_id_count,
+                "case_insensitive_ids: {case_insensitive_ids}"
+            );
+            assert_eq!(
+                iterator.excluded_sequence_count(),
+                expected_excluded_count,
+                "case_insensitive_ids: {case_insensitive_ids}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_id_matches_pattern() {
+        for (id, pattern, expected) in [
+            ("plasmid_1", "plasmid", true),
+            ("my_plasmid", "plasmid", true),
+            ("plasm", "plasmid", false),
+            ("anything", "", true),
+            ("contig12", "contig?", false),
+            ("contig1", "contig?", true),
+            ("contig12", "contig*", true),
+            ("contig", "contig*", true),
+            ("my_contig", "contig*", false),
+            ("a_b_c", "*_c", true),
+            ("a_b_c_d", "*_c", false),
+            ("aab", "*a*b", true),
+            ("abab", "a*ab", true),
+            ("abac", "a*ab", false),
+            ("", "*", true),
+            ("", "?", false),
+        ] {
+            assert_eq!(
+                id_matches_pattern(id.as_bytes(), pattern.as_bytes()),
+                expected,
+                "id: {id:?}, pattern: {pattern:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_spell_gfa_paths() {
+        initialise_logging(LevelFilter::Debug);
+        let tigs = "S\t1\tAACG\nS\t2\tCGTT\nP\tp\t1+,2-\t*\n";
+        let mut iterator = KmerIterator::<_, BitPackedKmer<3, u8>>::new(
+            tigs.as_bytes(),
+            3,
+            ParseOptions {
+                panic_on_parse_error: true,
+                spell_gfa_paths: true,
+                ..Default::default()
+            },
+        );
+        let kmers: Vec<_> = iterator.by_ref().collect();
+        assert_eq!(
+            kmers,
+            ["AAC", "ACG", "CGC", "GCG"]
+                .map(|kmer| BitPackedKmer::from_iter(kmer.bytes()))
+                .to_vec()
+        );
+        assert_eq!(iterator.sequence_count(), 1);
+        assert_eq!(iterator.character_count(), 6);
+    }
+
+    #[test]
+    fn test_gfa_fields() {
+        initialise_logging(LevelFilter::Debug);
+        let tigs = "H\tVN:Z:1.0\nS\tname\tACGT\tLN:i:4\nL\tname\t+\tS2\t+\t0M\nSX\tfoo\tGGGG\nS\tS2\tCCCA\n";
+        let mut iterator = KmerIterator::<_, BitPackedKmer<3, u8>>::new(
+            tigs.as_bytes(),
+            3,
+            ParseOptions {
+                panic_on_parse_error: true,
+                ..Default::default()
+            },
+        );
+        let kmers: Vec<_> = iterator.by_ref().collect();
+        assert_eq!(
+            kmers,
+            ["ACG", "CGT", "CCC", "CCA"]
+                .map(|kmer| BitPackedKmer::from_iter(kmer.bytes()))
+                .to_vec()
+        );
+        assert_eq!(iterator.sequence_count(), 2);
+        assert_eq!(iterator.character_count(), 8);
+    }
+
+    #[test]
+    fn test_gfa_sequence_end() {
+        initialise_logging(LevelFilter::Debug);
+        // Optional tags may contain sequence characters, and segments may have no sequence.
+        let tigs = "S\t1\tACGT\tSQ:Z:GGGG\nS\t2\t*\tLN:i:5\nS\t3\tCCCA\nS\t4\tTTT";
+        let mut iterator = KmerIterator::<_, BitPackedKmer<3, u8>>::new(
+            tigs.as_bytes(),
+            3,
+            ParseOptions {
+                panic_on_parse_error: true,
+                ..Default::default()
+            },
+        );
+        let kmers: Vec<_> = iterator.by_ref().collect();
+        assert_eq!(
+            kmers,
+            ["ACG", "CGT", "CCC", "CCA", "TTT"]
+                .map(|kmer| BitPackedKmer::from_iter(kmer.bytes()))
+                .to_vec()
+        );
+        assert_eq!(iterator.sequence_count(), 3);
+        assert_eq!(iterator.missing_sequence_segment_count(), 1);
+        assert_eq!(iterator.character_count(), 11);
+
+        // An illegal character ends the segment, but not the next record.
+        let tigs = "S\t1\tACGTX\tGGG\nS\t2\tCCCA\n";
+        let mut iterator = KmerIterator::<_, BitPackedKmer<3, u8>>::new(
+            tigs.as_bytes(),
+            3,
+            ParseOptions::default(),
+        );
+        let kmers: Vec<_> = iterator.by_ref().collect();
+        assert_eq!(
+            kmers,
+            ["ACG", "CGT", "CCC", "CCA"]
+                .map(|kmer| BitPackedKmer::from_iter(kmer.bytes()))
+                .to_vec()
+        );
+    }
+
+    #[test]
+    fn test_gfa_interleaved_lines() {
+        initialise_logging(LevelFilter::Debug);
+        let tigs = "H\tVN:Z:1.0\n# comment >ACGT\nS\tAAAA\tACGT\nL\tAAAA\t+\tGGGG\t+\t0M\n\nC\tAAAA\t+\tTTTT\t-\t1\t2M\nS\tGGGG\tCCCA\nP\tS\tAAAA+,GGGG+\t*\nW\tsample\t0\tS\t0\t8\t>AAAA>GGGG\nS\tTTTT\tGGA";
+        let mut iterator = KmerIterator::<_, BitPackedKmer<3, u8>>::new(
+            tigs.as_bytes(),
+            3,
+            ParseOptions {
+                panic_on_parse_error: true,
+                ..Default::default()
+            },
+        );
+        let kmers: Vec<_> = iterator.by_ref().collect();
+        assert_eq!(
+            kmers,
+            ["ACG", "CGT", "CCC", "CCA", "GGA"]
+                .map(|kmer| BitPackedKmer::from_iter(kmer.bytes()))
+                .to_vec()
+        );
+        assert_eq!(iterator.sequence_count(), 3);
+        assert_eq!(iterator.character_count(), 11);
+    }
+
+    #[test]
+    fn test_sequence_index() {
+        initialise_logging(LevelFilter::Debug);
+        let tigs = ">a\nAAAC\n>b\nCA\n>c\nCAGT\nGNCCC\n>d\nGGG";
+        let mut iterator = KmerIterator::<_, BitPackedKmer<3, u8>>::new(
+            tigs.as_bytes(),
+            3,
+            ParseOptions {
+                panic_on_parse_error: true,
+                ..Default::default()
+            },
+        )
+        .with_sequence_index();
+        let kmers: Vec<_> = iterator
+            .by_ref()
+            .map(|(kmer, sequence_index)| (kmer.to_string(), sequence_index))
+            .collect();
+        assert_eq!(
+            kmers,
+            [
+                ("AAA", 0),
+                ("AAC", 0),
+                ("CAG", 2),
+                ("AGT", 2),
+                ("GTG", 2),
+                ("CCC", 3),
+                ("GGG", 4)
+            ]
+            .map(|(kmer, sequence_index)| (kmer.to_string(), sequence_index))
+        );
+        assert_eq!(iterator.kmer_iterator().sequence_count(), 5);
+        assert_eq!(iterator.next(), None);
+    }
+
+    #[test]
+    fn test_missing_final_newline() {
+        initialise_logging(LevelFilter::Debug);
+        for (tigs, expected_kmers, sequence_count, character_count) in [
+            (
+                ">a\nAAAC\n>b\nCAGT",
+                &["AAA", "AAC", "CAG", "AGT"][..],
+                2,
+                8,
+            ),
+            (
+                ">a\nAAAC\nCAG\n>b\nCAG",
+                &["AAA", "AAC", "ACC", "CCA", "CAG", "CAG"],
+                2,
+                10,
+            ),
+            (">a\nAAAC\n>b\nCA", &["AAA", "AAC"], 2, 6),
+            (">a\nAAAC\n>b", &["AAA", "AAC"], 2, 4),
+            (">a\nAAAC\n>", &["AAA", "AAC"], 2, 4),
+            (">a\nAAAC\n", &["AAA", "AAC"], 1, 4),
+            (">a\nAAA", &["AAA"], 1, 3),
+            (
+                "S\t1\tAAAC\nS\t2\tCAGT",
+                &["AAA", "AAC", "CAG", "AGT"],
+                2,
+                8,
+            ),
+            ("S\t1\tAAAC\nS\t2\tCA", &["AAA", "AAC"], 2, 6),
+            (
+                "S\t1\tAAAC\nS\t2\tCAGT\tLN:i:4",
+                &["AAA", "AAC", "CAG", "AGT"],
+                2,
+                8,
+            ),
+            ("S\t1\tAAAC\nS\t2\t*", &["AAA", "AAC"], 1, 4),
+            ("S\t1\tAAAC\nL\t1\t+\t2\t+\t0M", &["AAA", "AAC"], 1, 4),
+            ("S\t1\tAAA", &["AAA"], 1, 3),
+        ] {
+            let expected_kmers: Vec<_> = expected_kmers
+                .iter()
+                .map(|kmer| BitPackedKmer::<3, u8>::from_iter(kmer.bytes()))
+                .collect();
+
+            // The result must not depend on whether the file ends with a newline.
+            for tigs in [tigs.to_string(), format!("{tigs}\n"), format!("{tigs}\r\n")] {
+                let mut iterator = KmerIterator::<_, BitPackedKmer<3, u8>>::new(
+                    tigs.as_bytes(),
+                    3,
+                    ParseOptions {
+                        panic_on_parse_error: true,
+                        ..Default::default()
+                    },
+                );
+                let kmers: Vec<_> = iterator.by_ref().collect();
+                assert_eq!(kmers, expected_kmers, "{tigs:?}");
+                assert_eq!(iterator.sequence_count(), sequence_count, "{tigs:?}");
+                assert_eq!(iterator.character_count(), character_count, "{tigs:?}");
+                assert_eq!(
+                    iterator.kmer_position_count(),
+                    expected_kmers.len(),
+                    "{tigs:?}"
+                );
+                assert_eq!(iterator.next(), None);
+            }
+        }
+
+        // A segment line without a sequence field is an error, also at the end of the file.
+        for tigs in ["S\t1\tAAAC\nS\t2", "S\t1\tAAAC\nS\t2\n"] {
+            let mut iterator = KmerIterator::<_, BitPackedKmer<3, u8>>::new(
+                tigs.as_bytes(),
+                3,
+                ParseOptions::default(),
+            );
+            assert_eq!(iterator.by_ref().count(), 2, "{tigs:?}");
+            assert_eq!(iterator.sequence_count(), 1, "{tigs:?}");
+            assert_eq!(iterator.character_count(), 4, "{tigs:?}");
+        }
+    }
+
+    #[test]
+    fn test_size_hint() {
+        initialise_logging(LevelFilter::Debug);
+        for (tigs, options) in [
+            (">a\nAAAC\nCAG\nT\n>b\nGGGG\n", ParseOptions::default()),
+            (
+                "H\tVN:Z:1.0\nS\t1\tAAACCAG\tLN:i:7\nS\t2\tGGGG\n",
+                ParseOptions::default(),
+            ),
+            (
+                "S\t1\tAAAC\nS\t2\tCAGT\nP\tp\t1+,2+\t*\n",
+                ParseOptions {
+                    spell_gfa_paths: true,
+                    ..Default::default()
+                },
+            ),
+        ] {
+            let options = ParseOptions {
+                panic_on_parse_error: true,
+                ..options
+            };
+            let kmer_count =
+                KmerIterator::<_, BitPackedKmer<3, u8>>::new(tigs.as_bytes(), 3, options.clone())
+                    .count();
+
+            let mut iterator =
+                KmerIterator::<_, BitPackedKmer<3, u8>>::new(tigs.as_bytes(), 3, options.clone())
+                    .with_length_hint(Some(tigs.len() as u64));
+            if !options.spell_gfa_paths {
+                assert_eq!(iterator.size_hint(), (0, Some(tigs.len())));
+            }
+            for remaining_kmer_count in (0..=kmer_count).rev() {
+                let (lower_bound, upper_bound) = iterator.size_hint();
+                assert!(lower_bound <= remaining_kmer_count, "tigs: {tigs:?}");
+                assert!(
+                    upper_bound.is_none_or(|upper_bound| upper_bound >= remaining_kmer_count),
+                    "tigs: {tigs:?}"
+                );
+                assert_eq!(iterator.next().is_some(), remaining_kmer_count > 0);
+            }
+
+            // The iterator is fused.
+            assert_eq!(iterator.size_hint(), (0, Some(0)));
+            assert!(iterator.next().is_none());
+
+            let iterator =
+                KmerIterator::<_, BitPackedKmer<3, u8>>::new(tigs.as_bytes(), 3, options);
+            assert_eq!(iterator.size_hint(), (0, None));
+        }
+
+        let iterator = KmerIterator::<_, BitPackedKmer<3, u8>>::new(
+            ">\nACNT\n".as_bytes(),
+            3,
+            ParseOptions {
+                panic_on_parse_error: true,
+                alphabet: NucleotideAlphabet {
+                    ambiguous_character_policy: AmbiguousCharacterPolicy::Expand,
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+        )
+        .with_length_hint(Some(7));
+        assert_eq!(iterator.size_hint(), (0, None));
+    }
+
+    #[test]
+    fn test_format() {
+        initialise_logging(LevelFilter::Debug);
+        for (tigs, expected_format) in [
+            ("", Format::None),
+            ("H\tVN:Z:1.0\n", Format::None),
+            (">a\nAAAC\n", Format::Fa),
+            ("H\tVN:Z:1.0\nS\t1\tAAAC\n", Format::Gfa),
+            ("AAAC\t1\n", Format::KmerCounts),
+            ("\nAAA\nCCC\t1\n", Format::KmerList),
+        ] {
+            let mut iterator = KmerIterator::<_, BitPackedKmer<3, u8>>::new(
+                tigs.as_bytes(),
+                3,
+                ParseOptions {
+                    panic_on_parse_error: true,
+                    ..Default::default()
+                },
+            );
+            assert_eq!(iterator.format(), Format::None);
+            assert!(iterator.by_ref().all(|_| true));
+            assert_eq!(iterator.format(), expected_format, "tigs: {tigs:?}");
+        }
+    }
+
+    #[test]
+    fn test_kmer_counts() {
+        initialise_logging(LevelFilter::Debug);
+        let tigs = "ACG\t3\nCGT\t1\n\nttt\t5\r\nAAAC\t2\tfoo\nGGG\tx\nCCC\t2";
+        let mut iterator = KmerIterator::<_, BitPackedKmer<3, u8>>::new(
+            tigs.as_bytes(),
+            3,
+            ParseOptions {
+                min_count: 2,
+                ..Default::default()
+            },
+        );
+        let kmers: Vec<_> = iterator.by_ref().collect();
+        assert_eq!(
+            kmers,
+            ["ACG", "TTT", "AAA", "AAC", "CCC"]
+                .map(|kmer| BitPackedKmer::from_iter(kmer.bytes()))
+                .to_vec()
+        );
+        assert_eq!(iterator.format(), Format::KmerCounts);
+        assert_eq!(iterator.sequence_count(), 4);
+        assert_eq!(iterator.character_count(), 13);
+        assert_eq!(iterator.low_count_kmer_count(), 1);
+
+        // Sequence lines of other formats are not mistaken for kmer count lines.
+        let tigs = ">a\nACGX\nACGT\t5\n";
+        let mut iterator = KmerIterator::<_, BitPackedKmer<3, u8>>::new(
+            tigs.as_bytes(),
+            3,
+            ParseOptions {
+                panic_on_parse_error: true,
+                ..Default::default()
+            },
+        );
+        assert_eq!(iterator.by_ref().count(), 1);
+        assert_eq!(iterator.format(), Format::Fa);
+    }
+
+    #[test]
+    fn test_kmer_list() {
+        initialise_logging(LevelFilter::Debug);
+        let tigs = "ACG\nCGTA\n\nttt\r\nGG\nSACG\nNAC\n";
+        for input_format in [InputFormat::Auto, InputFormat::KmerList] {
+            let mut iterator = KmerIterator::<_, BitPackedKmer<3, u8>>::new(
+                tigs.as_bytes(),
+                3,
+                ParseOptions {
+                    input_format,
+                    ..Default::default()
+                },
+            );
+            let kmers: Vec<_> = iterator.by_ref().collect();
+
+            let mut expected_kmers = vec!["ACG", "CGT", "GTA", "TTT"];
+            // Only a forced kmer list treats lines starting with other characters as kmers.
+            if input_format == InputFormat::KmerList {
+                expected_kmers.push("ACG");
+            }
+            assert_eq!(
+                kmers,
+                expected_kmers
+                    .into_iter()
+                    .map(|kmer| BitPackedKmer::from_iter(kmer.bytes()))
+                    .collect::<Vec<_>>(),
+                "{input_format:?}"
+            );
+            assert_eq!(iterator.format(), Format::KmerList);
+        }
+
+        let tigs = "ACG\nCGT\r\nTTT";
+        let mut iterator = KmerIterator::<_, BitPackedKmer<3, u8>>::new(
+            tigs.as_bytes(),
+            3,
+            ParseOptions {
+                panic_on_parse_error: true,
+                ..Default::default()
+            },
+        );
+        assert_eq!(iterator.by_ref().count(), 3);
+        assert_eq!(iterator.sequence_count(), 3);
+        assert_eq!(iterator.character_count(), 9);
+        assert_eq!(iterator.kmer_position_count(), 3);
+    }
+
+    #[test]
+    #[should_panic(expected = "Found kmer list line of length 4 instead of k = 3")]
+    fn test_kmer_list_strict_length() {
+        let tigs = "ACG\nCGTA\n";
+        let iterator = KmerIterator::<_, BitPackedKmer<3, u8>>::new(
+            tigs.as_bytes(),
+            3,
+            ParseOptions {
+                panic_on_parse_error: true,
+                ..Default::default()
+            },
+        );
+        iterator.for_each(drop);
+    }
+
+    #[test]
+    fn test_two_bit_input() {
+        initialise_logging(LevelFilter::Debug);
+        // AACGT, an integer with bits set above the kmer, and AAAAA as 16-bit little-endian integers,
+        // followed by a trailing byte.
+        let input = [0b00_01_10_11, 0, 0xff, 0xff, 0, 0, 0];
+        let mut iterator = KmerIterator::<_, BitPackedKmer<5, u16>>::new(
+            input.as_slice(),
+            5,
+            ParseOptions {
+                two_bit_input: true,
+                ..Default::default()
+            },
+        );
+        let kmers: Vec<_> = iterator.by_ref().collect();
+        assert_eq!(
+            kmers,
+            ["AACGT", "AAAAA"].map(|kmer| BitPackedKmer::from_iter(kmer.bytes()))
+        );
+        assert_eq!(iterator.format(), Format::TwoBit);
+        assert_eq!(iterator.sequence_count(), 2);
+        assert_eq!(iterator.character_count(), 10);
+        assert_eq!(iterator.kmer_position_count(), 2);
+        assert_eq!(iterator.next(), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "Found 1 trailing bytes after the last 2-bit packed kmer of 2 bytes")]
+    fn test_two_bit_input_trailing_bytes() {
+        let input = [0, 0, 0];
+        let iterator = KmerIterator::<_, BitPackedKmer<5, u16>>::new(
+            input.as_slice(),
+            5,
+            ParseOptions {
+                panic_on_parse_error: true,
+                two_bit_input: true,
+                ..Default::default()
+            },
+        );
+        iterator.for_each(drop);
+    }
+
+    #[test]
+    fn test_softmask() {
+        initialise_logging(LevelFilter::Debug);
+        let tigs = ">\nAACGacgtTTGA\n";
+
+        let mut iterator = KmerIterator::<_, BitPackedKmer<3, u8>>::new(
+            tigs.as_bytes(),
+            3,
+            ParseOptions {
+                panic_on_parse_error: true,
+                ..Default::default()
+            },
+        );
+        let kmers: Vec<_> = iterator.by_ref().collect();
+        assert_eq!(
+            kmers,
+            ["AAC", "ACG", "CGA", "GAC", "ACG", "CGT", "GTT", "TTT", "TTG", "TGA"]
+                .map(|kmer| BitPackedKmer::from_iter(kmer.bytes()))
+                .to_vec()
+        );
+        assert_eq!(iterator.sequence_count(), 1);
+
+        let mut iterator = KmerIterator::<_, BitPackedKmer<3, u8>>::new(
+            tigs.as_bytes(),
+            3,
+            ParseOptions {
+                panic_on_parse_error: true,
+                alphabet: NucleotideAlphabet {
+                    respect_softmask: true,
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+        );
+        let kmers: Vec<_> = iterator.by_ref().collect();
+        assert_eq!(
+            kmers,
+            ["AAC", "ACG", "TTG", "TGA"]
+                .map(|kmer| BitPackedKmer::from_iter(kmer.bytes()))
+                .to_vec()
+        );
+        assert_eq!(iterator.sequence_count(), 2);
+        assert_eq!(iterator.character_count(), 8);
+    }
+
+    #[test]
+    fn test_alphabet_classify() {
+        let alphabet = NucleotideAlphabet::default();
+        assert_eq!(alphabet.classify(b'c'), CharacterClass::Base(b'C'));
+        assert_eq!(alphabet.classify(b'N'), CharacterClass::Break);
+        assert_eq!(alphabet.classify(b'R'), CharacterClass::Break);
+        assert_eq!(alphabet.classify(b'U'), CharacterClass::Invalid);
+        assert_eq!(alphabet.classify(b'\n'), CharacterClass::Invalid);
+
+        let alphabet = NucleotideAlphabet {
+            ambiguous_character_policy: AmbiguousCharacterPolicy::Expand,
+            respect_softmask: true,
+            rna: true,
+            n_as_base: false,
+        };
+        assert_eq!(alphabet.classify(b'U'), CharacterClass::Base(b'T'));
+        assert_eq!(alphabet.classify(b'u'), CharacterClass::Break);
+        assert_eq!(alphabet.classify(b'r'), CharacterClass::Break);
+        assert_eq!(alphabet.classify(b'R'), CharacterClass::Ambiguous(b'R'));
+        assert_eq!(alphabet.classify(b'N'), CharacterClass::Break);
+
+        let alphabet = NucleotideAlphabet {
+            n_as_base: true,
+            ..Default::default()
+        };
+        assert_eq!(alphabet.classify(b'n'), CharacterClass::Base(b'N'));
+        assert_eq!(alphabet.classify(b'R'), CharacterClass::Break);
+    }
+
+    #[test]
+    fn test_rna() {
+        initialise_logging(LevelFilter::Debug);
+        let tigs = ">\nACGUuT\n";
+        let mut iterator = KmerIterator::<_, BitPackedKmer<3, u8>>::new(
+            tigs.as_bytes(),
+            3,
+            ParseOptions {
+                panic_on_parse_error: true,
+                alphabet: NucleotideAlphabet {
+                    rna: true,
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+        );
+        let kmers: Vec<_> = iterator.by_ref().collect();
+        assert_eq!(
+            kmers,
+            ["ACG", "CGT", "GTT", "TTT"]
+                .map(|kmer| BitPackedKmer::from_iter(kmer.bytes()))
+                .to_vec()
+        );
+        assert_eq!(iterator.sequence_count(), 1);
+        assert_eq!(iterator.character_count(), 6);
+
+        let mut iterator = KmerIterator::<_, BitPackedKmer<3, u8>>::new(
+            tigs.as_bytes(),
+            3,
+            ParseOptions {
+                panic_on_parse_error: true,
+                ..Default::default()
+            },
+        );
+        let kmers: Vec<_> = iterator.by_ref().collect();
+        assert_eq!(
+            kmers,
+            ["ACG"]
+                .map(|kmer| BitPackedKmer::from_iter(kmer.bytes()))
+                .to_vec()
+        );
+    }
+
+    #[test]
+    fn test_chunk_refill() {
+        initialise_logging(LevelFilter::Debug);
+        let tigs = synthetic_fasta(50_000);
+        let mut iterator = KmerIterator::<_, BitPackedKmer<5, u16>>::new(
+            tigs.as_slice(),
+            5,
+            ParseOptions {
+                panic_on_parse_error: true,
+                ..Default::default()
+            },
+        );
+        let kmers: Vec<_> = iterator.by_ref().collect();
+
+        let mut single_byte_iterator = KmerIterator::<_, BitPackedKmer<5, u16>>::new(
+            SingleByteReader(&tigs),
+            5,
+            ParseOptions {
+                panic_on_parse_error: true,
+                ..Default::default()
+            },
+        );
+        let single_byte_kmers: Vec<_> = single_byte_iterator.by_ref().collect();
+
+        assert_eq!(kmers, single_byte_kmers);
+        assert_eq!(
+            iterator.sequence_count(),
+            single_byte_iterator.sequence_count()
+        );
+        assert_eq!(
+            iterator.character_count(),
+            single_byte_iterator.character_count()
+        );
+    }
 }
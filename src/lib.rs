@@ -0,0 +1,4473 @@
+//! Verify that an SPSS contains the same kmer content as a set of unitigs.
+//!
+//! The binary only calls [`cli_main`].
+//! Library users can compare inputs that are already open with [`compare_kmer_sets`],
+//! configured with a [`ConfigBuilder`],
+//! or check chunks of kmers against a [`ground_truth::GroundTruthIndex`] that is built once.
+
+use crate::dump::{read_kmer_dump, write_kmer_dump, write_kmer_fasta};
+use crate::fingerprint::KmerSetFingerprint;
+use crate::gfa::k_from_header;
+use crate::ground_truth::GroundTruthIndex;
+use crate::input::{read_test_tigs_list, Input, InputOpener};
+use crate::kmer::{
+    BitPackedArrayKmer, BitPackedKmer, BitPackedVectorKmer, Kmer, PairedKmer, ThreeBitKmer,
+};
+use crate::kmer_iterator::{
+    Alphabet, AmbiguousCharacterPolicy, Format, InputCounts, InputFormat, KmerIterator,
+    ParseOptions,
+};
+use crate::merge::{
+    merge_kmers, merge_multiplicities, MergeCounts, MismatchAnalysis, MismatchEvent, MismatchKind,
+    SortedUniqueKmers, SortingWindow,
+};
+use crate::report::{
+    MemoryEstimateReport, ReportFormat, ScanReport, Strands, VerificationReport, VerificationStatus,
+};
+use crate::sample::KmerSampler;
+use crate::spill::SpilledKmers;
+use clap::Parser;
+use log::{debug, error, info, warn, LevelFilter};
+use simplelog::{ColorChoice, CombinedLogger, TermLogger, TerminalMode};
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::fmt::Display;
+use std::fs::{File, OpenOptions};
+use std::hash::Hash;
+use std::io::{BufRead, BufReader, BufWriter, Read, Write};
+use std::ops::RangeInclusive;
+use std::path::PathBuf;
+use std::process::ExitCode;
+use std::sync::Mutex;
+use std::time::Instant;
+
+mod dump;
+mod fingerprint;
+mod gfa;
+pub mod ground_truth;
+mod input;
+pub mod kmer;
+pub mod kmer_iterator;
+mod merge;
+mod report;
+mod sample;
+mod spill;
+#[cfg(test)]
+mod test_util;
+
+pub use crate::input::LengthHint;
+
+static LOGGING_INITIALISED: Mutex<bool> = Mutex::new(false);
+
+pub fn initialise_logging(log_level: LevelFilter) {
+    initialise_logging_with_mode(log_level, TerminalMode::Mixed);
+}
+
+/// Like [`initialise_logging`], but logs to the given stream(s).
+fn initialise_logging_with_mode(log_level: LevelFilter, terminal_mode: TerminalMode) {
+    let mut logging_initialised = LOGGING_INITIALISED.lock().unwrap();
+
+    if !*logging_initialised {
+        CombinedLogger::init(vec![TermLogger::new(
+            log_level,
+            Default::default(),
+            terminal_mode,
+            ColorChoice::Auto,
+        )])
+        .unwrap();
+
+        *logging_initialised = true;
+    }
+}
+
+/// Log the progress of a processing phase at info level, unless `--quiet` is given.
+macro_rules! phase_info {
+    ($config:expr, $($argument:tt)+) => {
+        if !$config.quiet {
+            info!($($argument)+);
+        }
+    };
+}
+
+/// Verify that an SPSS contains the same kmer content as a set of unitigs.
+#[derive(Parser, Debug, Clone)]
+pub struct Config {
+    /// The desired log level.
+    #[clap(short, long, default_value = "Info")]
+    log_level: LevelFilter,
+
+    /// Do not log the progress of the individual phases like reading and sorting.
+    ///
+    /// The statistics, the final result, warnings and errors are still printed.
+    /// Independent of `--log-level`.
+    #[clap(short, long)]
+    quiet: bool,
+
+    /// Print only `MATCH` or `MISMATCH` to stdout, and log everything else to stderr.
+    ///
+    /// The statistics are not printed, unless `--stats-output` is given.
+    /// Nothing is printed if the comparison fails for another reason, e.g. incompatible arguments.
+    /// Cannot be combined with `--scan-only`, `--estimate-memory` or `--list-kmers`.
+    #[clap(long)]
+    terse: bool,
+
+    /// The kmer size.
+    ///
+    /// Can be omitted if `--k-from-header` is given.
+    #[clap(short, default_value_t = 0, hide_default_value = true)]
+    k: usize,
+
+    /// Read the kmer size from the header of the unitigs.
+    ///
+    /// The first line of the unitigs must be a GFA `H` line or a fasta header with a `KL:i:<k>` or `k:i:<k>` tag.
+    /// If `-k` is given as well, then it must match the kmer size in the header.
+    /// Cannot be combined with `--ground-truth-dump`.
+    #[clap(long)]
+    k_from_header: bool,
+
+    /// Skip the actual verification, and only compute statistics.
+    #[clap(long)]
+    do_not_verify: bool,
+
+    /// Only read the inputs and print their character, sequence and kmer counts.
+    ///
+    /// The kmer count includes duplicates, and gives a cheap estimate of the memory required for the verification.
+    #[clap(long)]
+    scan_only: bool,
+
+    /// Only read the inputs like `--scan-only`, and print an estimate of the peak memory needed to verify them.
+    ///
+    /// The estimate assumes that all kmers of the first input file and of the largest test tig file are in memory at once,
+    /// including duplicates, and accounts for the memory that the `--dedup-strategy` reserves and uses for sorting.
+    /// If the kmers do not fit, `--spill-threshold` bounds the memory instead.
+    /// For kmer sizes up to 128, the estimate with the slower vector kmer type is printed as well.
+    #[clap(long)]
+    estimate_memory: bool,
+
+    /// Only print the sorted unique kmers of the unitigs to stdout, one per line, without comparing.
+    ///
+    /// The kmers are sorted lexicographically, independently of k.
+    /// The kmers are canonical, unless `--strand-specific` is given.
+    /// No test tig files may be given.
+    /// Info messages are logged to stdout as well, so use `--log-level warn` to get only the kmers.
+    #[clap(long)]
+    list_kmers: bool,
+
+    /// Do not print warnings during parsing, but instead abort if there is any warning.
+    #[clap(long)]
+    panic_on_parse_error: bool,
+
+    /// Panic if an internal consistency check fails, instead of failing with an error.
+    ///
+    /// This is meant for development, where the backtrace of the panic helps to find the cause.
+    #[clap(long)]
+    debug_checks: bool,
+
+    /// Only warn about input files without sequences, instead of failing.
+    #[clap(long)]
+    allow_empty_input: bool,
+
+    /// Assume that both inputs yield their canonical kmers in sorted order.
+    ///
+    /// Then the kmers are compared while reading, without keeping them in memory.
+    /// Fails if a kmer is out of order.
+    /// Requires exactly one test tig file, and cannot be combined with `--allow-cuttlefish2-errors`.
+    #[clap(long)]
+    assume_sorted: bool,
+
+    /// Like `--assume-sorted`, but tolerate kmers that are slightly out of order, using a window of this many kmers.
+    ///
+    /// Both inputs are read in lockstep, and at most this many kmers of each input are kept in memory.
+    /// A kmer may be preceded by less than this many larger kmers, counting duplicates.
+    /// If it is preceded by more, then the comparison fails as if the input was not sorted,
+    /// even if the input is only reordered locally. In this case, a larger window may succeed.
+    /// Implies `--assume-sorted`.
+    #[clap(long)]
+    sorted_window: Option<usize>,
+
+    /// Do not treat k-mers in the test tigs as missing if they are missing due to cuttlefish2's error.
+    ///
+    /// This allows k-mers to be missing if they are not part of any k+1-mer.
+    /// See [this github issue][1] for details.
+    ///
+    /// Cannot be combined with `--alphabet acgtn`.
+    ///
+    /// [1]: https://github.com/COMBINE-lab/cuttlefish/issues/36
+    #[clap(long)]
+    allow_cuttlefish2_errors: bool,
+
+    /// Succeed if the test tigs contain all unitig kmers, even if they contain additional kmers.
+    ///
+    /// The additional kmers are still counted and reported.
+    #[clap(long)]
+    allow_superset: bool,
+
+    /// Succeed if all test tig kmers are unitig kmers, even if the test tigs miss some unitig kmers.
+    ///
+    /// The missing kmers are still counted and reported.
+    /// Since missing unitig kmers are allowed anyways, `--allow-cuttlefish2-errors` does not change the outcome,
+    /// but only how many of the missing kmers are reported as excused.
+    /// Cannot be combined with `--allow-superset`.
+    #[clap(long)]
+    allow_subset: bool,
+
+    /// Succeed only if the sets of distinct kmers are exactly equal, without any leniency.
+    ///
+    /// Unlike the default, this ignores the kmer counts including duplicates,
+    /// such that duplicate kmers neither cause nor excuse a failure.
+    /// Cannot be combined with `--allow-superset`, `--allow-subset`, `--allow-cuttlefish2-errors` or `--do-not-verify`.
+    #[clap(long)]
+    strict_equality: bool,
+
+    /// Compare the kmer multisets instead of the kmer sets, i.e. require each kmer to occur equally often in both inputs.
+    ///
+    /// The kmers with different multiplicities are counted in the report, and cause a failure like missing kmers
+    /// if they occur less often in the test tigs, and like superfluous kmers if they occur more often.
+    /// Like `--strict-equality`, this ignores the kmer counts that are otherwise compared.
+    /// Requires `--dedup-strategy sort`, and cannot be combined with `--allow-superset`, `--allow-subset`,
+    /// `--allow-cuttlefish2-errors`, `--assume-sorted`, `--do-not-verify`, `--spill-threshold` or `--ground-truth-dump`.
+    #[clap(long)]
+    multiset: bool,
+
+    /// Compare forward kmers as they are, instead of identifying each kmer with its reverse complement.
+    #[clap(long)]
+    strand_specific: bool,
+
+    /// Compare the inputs twice, once with canonical kmers and once with forward kmers as with `--strand-specific`,
+    /// and print a report labelled with its `strands` for each comparison.
+    ///
+    /// This helps to detect tools that output kmers on the wrong strand.
+    /// The inputs are read twice, so they must be regular files.
+    /// The exit code is determined by the canonical comparison only,
+    /// since the forward comparison is expected to fail whenever a tig is output as its reverse complement.
+    /// Cannot be combined with `--strand-specific`, `--scan-only`, `--list-kmers`, `--mismatch-output`,
+    /// `--stats-output`, `--dump-kmers` or `--ground-truth-dump`.
+    #[clap(long)]
+    both_strands_report: bool,
+
+    /// Compare the inputs once for each kmer size in the inclusive range `MIN..MAX`,
+    /// and print whether they match for each kmer size.
+    ///
+    /// This helps to find the smallest kmer size at which the kmer sets of two tools diverge.
+    /// The inputs are read once for each kmer size, so they must be regular files.
+    /// The exit code is determined by the smallest kmer size at which the comparison fails.
+    /// Cannot be combined with `-k`, `--k-from-header`, `--both-strands-report`, `--scan-only`, `--list-kmers`,
+    /// `--mismatch-output`, `--stats-output`, `--dump-kmers` or `--ground-truth-dump`.
+    #[clap(long, value_parser = parse_k_range)]
+    k_range: Option<RangeInclusive<usize>>,
+
+    /// How ambiguous IUPAC characters such as `N` are handled.
+    #[clap(long, value_enum, default_value_t = AmbiguousCharacterPolicy::Split)]
+    ambiguous_characters: AmbiguousCharacterPolicy,
+
+    /// The characters of which kmers are built.
+    ///
+    /// With `acgtn`, `N` is kept as a fifth character instead of splitting sequences,
+    /// such that kmers containing `N` are compared as well, e.g. to verify masked references.
+    /// The kmers are stored with three bits per character.
+    /// Cannot be combined with `--ambiguous-characters expand`, `--gap`, `--two-bit-input`,
+    /// `--allow-cuttlefish2-errors` or `--count-k-plus-1`.
+    #[clap(long, value_enum, default_value_t = Alphabet::Acgt)]
+    alphabet: Alphabet,
+
+    /// Skip all sequences shorter than this, such that they contribute neither kmers nor characters nor strings.
+    ///
+    /// Values of at most k have no effect, since shorter sequences contain no kmers.
+    #[clap(long, default_value_t = 0)]
+    min_sequence_length: usize,
+
+    /// Skip all sequences whose ID matches this pattern, such that they contribute neither kmers nor characters nor strings.
+    ///
+    /// The ID of a fasta record is the first word of its header, and the ID of a GFA segment is its name.
+    /// If the pattern contains `*` or `?`, then it is a glob that must match the whole ID,
+    /// and otherwise it must be a substring of the ID.
+    /// This option can be given multiple times to exclude sequences matching any of the patterns.
+    #[clap(long, value_name = "PATTERN")]
+    exclude_id: Vec<String>,
+
+    /// Warn if a sequence ID occurs more than once in an input file, which often indicates wrongly concatenated files.
+    ///
+    /// IDs are determined like for `--exclude-id`, and sequences without ID are ignored.
+    /// This keeps all distinct IDs of an input file in memory.
+    #[clap(long)]
+    check_duplicate_ids: bool,
+
+    /// Compare sequence IDs case-insensitively for `--exclude-id` and `--check-duplicate-ids`.
+    #[clap(long)]
+    case_insensitive_ids: bool,
+
+    /// Skip kmers whose count is below this in inputs that list one kmer and its count per line, separated by a tab.
+    ///
+    /// Such inputs are detected if their first line starts with `A`, `C`, `G` or `T` and contains a tab,
+    /// and are written e.g. by KMC. This allows to verify against abundance-filtered kmer sets.
+    #[clap(long, default_value_t = 0)]
+    min_count: u64,
+
+    /// Compare pairs of kmers that are separated by this many characters instead of single kmers, e.g. for spaced seeds.
+    ///
+    /// Each window of 2k + gap characters yields the pair of its first and last k characters,
+    /// and pairs are compared as one unit, where the reverse complement of a pair is the pair of
+    /// the reverse complements in swapped order.
+    /// Pairs are printed as both kmers separated by `-`.
+    /// Cannot be combined with `--count-k-plus-1`, `--allow-cuttlefish2-errors`, `--dump-kmers` or `--ground-truth-dump`.
+    #[clap(long)]
+    gap: Option<usize>,
+
+    /// The format of the input files.
+    ///
+    /// In a kmer list, lines of a length other than k are an error with `--panic-on-parse-error`.
+    #[clap(long, value_enum, default_value_t = InputFormat::Auto)]
+    input_format: InputFormat,
+
+    /// Read both inputs as raw 2-bit packed kmers instead of parsing text.
+    ///
+    /// Each input is a sequence of little-endian integers, each holding one kmer in its lowest 2k bits,
+    /// with `A = 0`, `C = 1`, `G = 2` and `T = 3` and the first character in the most significant bits.
+    /// The integers have the smallest width of 1, 2, 4, 8 or 16 bytes that fits 2k bits, so k must be at most 64.
+    /// An input whose length is not a multiple of the integer width is rejected.
+    /// If the length is not known in advance, e.g. for compressed inputs, trailing bytes that do not form a whole integer
+    /// are a parse error.
+    /// Cannot be combined with `--k-from-header`, `--k-range`, `--gap`, `--count-k-plus-1`, `--gfa-spell-paths`
+    /// or `--input-format`.
+    #[clap(long)]
+    two_bit_input: bool,
+
+    /// For GFA inputs, use the sequences spelled by the paths and walks instead of the segments.
+    ///
+    /// Consecutive segments are assumed to overlap by k - 1 characters.
+    /// The whole file is loaded into memory, since paths may refer to segments that appear after them.
+    #[clap(long)]
+    gfa_spell_paths: bool,
+
+    /// Treat lowercase (soft-masked) characters as sequence breaks, like `N`.
+    ///
+    /// By default, lowercase characters are treated like uppercase characters.
+    #[clap(long)]
+    respect_softmask: bool,
+
+    /// Accept RNA sequences, treating `U` like `T`.
+    ///
+    /// This allows to verify RNA test tigs against DNA unitigs and vice versa.
+    #[clap(long)]
+    rna: bool,
+
+    /// How duplicate kmers are removed from the inputs.
+    #[clap(long, value_enum, default_value_t = DedupStrategy::Sort)]
+    dedup_strategy: DedupStrategy,
+
+    /// Abort if more than this many kmers are collected from a single input file.
+    ///
+    /// With the `sort` dedup strategy, duplicates are counted as well, since they are kept in memory until sorting.
+    /// This guards against running out of memory, e.g. if a tiny k was given by accident.
+    /// By default, there is no limit.
+    #[clap(long)]
+    max_kmers: Option<usize>,
+
+    /// Spill sorted runs of kmers to temporary files whenever this many kmers of an input file are buffered in memory.
+    ///
+    /// The runs are merged from disk during the comparison, such that inputs with more kmers than fit into memory
+    /// can be verified. The files are created in the directory given by the `TMPDIR` environment variable
+    /// and removed on exit. By default, all kmers are kept in memory.
+    #[clap(long)]
+    spill_threshold: Option<usize>,
+
+    /// Verify only about this fraction of the kmers, which must be in the interval `(0, 1]`.
+    ///
+    /// The kmers are chosen by a hash of the canonical kmer, such that each kmer is chosen either in all input files
+    /// or in none of them. Hence, the comparison is exact for the chosen kmers, which makes this a quick check
+    /// for large inputs. The kmer set statistics refer to the chosen kmers only, but the size statistics do not.
+    #[clap(long, default_value_t = 1.0)]
+    sample: f64,
+
+    /// The seed of the hash that chooses the kmers for `--sample`.
+    #[clap(long, default_value_t = 0)]
+    seed: u64,
+
+    /// Read the first input file and the first test tig file concurrently on two threads.
+    ///
+    /// The kmers of both files are collected and sorted in parallel, and then compared on the main thread.
+    /// Further test tig files are read one after another as usual.
+    #[clap(long)]
+    parallel_read: bool,
+
+    /// The number of threads used for sorting kmers.
+    ///
+    /// If zero, all available cores are used.
+    /// If one, kmers are sorted on the main thread.
+    #[cfg(feature = "rayon")]
+    #[clap(long, default_value_t = 0)]
+    threads: usize,
+
+    /// Show a progress bar for reading each input file.
+    #[cfg(feature = "progress")]
+    #[clap(long)]
+    progress: bool,
+
+    /// Memory-map the input files instead of reading them.
+    ///
+    /// This avoids the system call overhead of reading, which may be faster for large files
+    /// that are already in the page cache.
+    /// The input files must not be modified while they are read.
+    #[cfg(feature = "mmap")]
+    #[clap(long)]
+    mmap: bool,
+
+    /// The format in which the statistics are printed.
+    #[clap(long, value_enum, default_value_t = ReportFormat::Plain)]
+    format: ReportFormat,
+
+    /// Write the statistics to this file instead of stdout.
+    ///
+    /// The file is overwritten, and contains the statistics of all test tigs one after another.
+    /// Cannot be combined with `--both-strands-report`.
+    #[clap(long)]
+    stats_output: Option<PathBuf>,
+
+    /// Write the length and the number of kmer positions of each sequence of each input to this file, e.g. to find truncated records.
+    ///
+    /// The file is overwritten, and contains a line `# <input>` for each input,
+    /// followed by one line `sequence_index<TAB>length<TAB>kmers` per sequence of the input.
+    /// Sequences are indexed from zero in the order they are read,
+    /// where sequences split at ambiguous characters count as multiple sequences,
+    /// and skipped or excluded sequences are not listed.
+    /// Cannot be combined with `--both-strands-report` or `--k-range`.
+    #[clap(long)]
+    per_sequence_stats: Option<PathBuf>,
+
+    /// Write all mismatching kmers to this file.
+    ///
+    /// Each line contains the side that contains the kmer (`unitigs` or `test_tigs`),
+    /// followed by a tab and the kmer.
+    /// The kmer is canonical, unless `--strand-specific` is given.
+    #[clap(long)]
+    mismatch_output: Option<PathBuf>,
+
+    /// Log at most this many mismatching kmers of each side at debug level, followed by the number of omitted ones.
+    ///
+    /// This does not limit `--mismatch-output`.
+    #[clap(long, default_value_t = 100)]
+    mismatch_log_limit: usize,
+
+    /// Log a histogram of the length of the longest prefix that each mismatching kmer shares with a kmer of the other input.
+    ///
+    /// This shows whether the mismatching kmers cluster in sequence space, e.g. due to systematic strand errors.
+    #[clap(long)]
+    mismatch_analysis: bool,
+
+    /// If the kmers of a test tig file do not match, check if the complement of its kmers (without reversing) matches.
+    ///
+    /// A match strongly indicates a strand handling bug in the tool that produced the test tigs.
+    /// Cannot be combined with `--assume-sorted`, `--do-not-verify`, `--spill-threshold` or `--sample`.
+    #[clap(long)]
+    complement_only: bool,
+
+    /// Report how many distinct kmers occur exactly once, twice, three times, etc. in the test tigs.
+    ///
+    /// Requires the `sort` dedup strategy, and cannot be combined with `--assume-sorted` or `--do-not-verify`.
+    #[clap(long)]
+    multiplicity_histogram: bool,
+
+    /// Report the number of distinct (k+1)-mers whose prefix and suffix kmers are both unitig kmers.
+    ///
+    /// This is the number of edges of the de Bruijn graph of the unitig kmers,
+    /// where a (k+1)-mer and its reverse complement count as one unless `--strand-specific` is given.
+    /// Cannot be combined with `--assume-sorted`, `--do-not-verify` or `--alphabet acgtn`.
+    #[clap(long)]
+    count_k_plus_1: bool,
+
+    /// Report the GC content of the unitigs and the test tigs, i.e. the fraction of G and C characters
+    /// among their A, C, G and T characters.
+    ///
+    /// A large difference between the two hints at comparing unrelated inputs.
+    /// Ambiguous characters are not counted.
+    /// Cannot be combined with `--two-bit-input` or `--ground-truth-dump`.
+    #[clap(long)]
+    report_gc_content: bool,
+
+    /// Stop reading a test tig file at the first kmer that is not present in the unitigs,
+    /// and report only the kmers read until then.
+    ///
+    /// This fails early on grossly wrong test tigs.
+    /// The counts of missing and superfluous kmers and the Jaccard index are not reported for rejected test tigs,
+    /// since they require merging all kmers.
+    /// The kmers are looked up exactly in the sorted unitig kmers, so no kmer is rejected by mistake.
+    /// Cannot be combined with `--assume-sorted`, `--do-not-verify` or `--allow-superset`.
+    #[clap(long)]
+    fail_fast: bool,
+
+    /// Write the sorted unique unitig kmers to this file, such that later runs can read them with `--ground-truth-dump`.
+    ///
+    /// Cannot be combined with `--assume-sorted`, `--do-not-verify` or `--scan-only`.
+    #[clap(long)]
+    dump_kmers: Option<PathBuf>,
+
+    /// Read the unitig kmers from a file written by `--dump-kmers` instead of reading the unitigs.
+    ///
+    /// This skips parsing, canonicalising and sorting the unitig kmers.
+    /// The unitigs argument is still required, but the file is not read.
+    /// The dump is rejected if it was written with a different k or `--strand-specific` setting.
+    /// Cannot be combined with `--assume-sorted`, `--do-not-verify` or `--scan-only`.
+    #[clap(long)]
+    ground_truth_dump: Option<PathBuf>,
+
+    /// Write the sorted unique unitig kmers to this file as FASTA, with one record per kmer.
+    ///
+    /// The records are named `kmer_0`, `kmer_1`, etc.
+    /// The kmers are canonical, unless `--strand-specific` is given.
+    /// With `--list-kmers`, the kmers of the single input are written.
+    /// Cannot be combined with `--assume-sorted`, `--do-not-verify` or `--scan-only`.
+    #[clap(long)]
+    export_fasta: Option<PathBuf>,
+
+    /// A file listing further test tig files, one path per line.
+    ///
+    /// The listed files are compared against the ground truth after the test tigs given as arguments.
+    /// Blank lines and lines starting with `#` are ignored.
+    /// Relative paths are resolved against the working directory.
+    #[clap(long)]
+    test_tigs_list: Option<PathBuf>,
+
+    /// A further file containing a part of the ground truth, which is read after the unitigs as if they were one file.
+    ///
+    /// This option can be given multiple times for ground truths that are sharded across several files,
+    /// and the size statistics of the ground truth include all shards.
+    /// A line break is inserted after each file, such that a file without a trailing newline does not continue into the next.
+    /// Cannot be combined with `--ground-truth-dump`.
+    #[clap(long, value_name = "PATH")]
+    unitigs_shard: Vec<PathBuf>,
+
+    /// A file containing the ground truth kmer set as unitigs.
+    #[clap(index = 1)]
+    unitigs: PathBuf,
+
+    /// Files containing test kmer sets as any set of strings.
+    ///
+    /// Each file is compared against the ground truth separately.
+    #[clap(
+        index = 2,
+        required_unless_present_any = ["list_kmers", "test_tigs_list"],
+        num_args = 1..
+    )]
+    test_tigs: Vec<PathBuf>,
+}
+
+impl Config {
+    /// The number of characters of each compared kmer, which is 2k for pairs of kmers.
+    fn kmer_length(&self) -> usize {
+        if self.gap.is_some() {
+            2 * self.k
+        } else {
+            self.k
+        }
+    }
+}
+
+/// Builds a [`Config`] for calling [`compare_kmer_sets`] directly, with the same defaults as the command line.
+///
+/// The input paths are left empty, since the inputs are passed to [`compare_kmer_sets`] as readers.
+pub struct ConfigBuilder {
+    config: Config,
+}
+
+/// Define a setter of [`ConfigBuilder`] for each of the given fields of [`Config`].
+macro_rules! config_setters {
+    ($($field:ident: $field_type:ty),* $(,)?) => {
+        $(
+            pub fn $field(mut self, $field: $field_type) -> Self {
+                self.config.$field = $field;
+                self
+            }
+        )*
+    };
+}
+
+impl ConfigBuilder {
+    pub fn new(k: usize) -> Self {
+        Self {
+            config: Config {
+                log_level: LevelFilter::Info,
+                quiet: false,
+                terse: false,
+                k,
+                k_from_header: false,
+                do_not_verify: false,
+                scan_only: false,
+                estimate_memory: false,
+                list_kmers: false,
+                panic_on_parse_error: false,
+                debug_checks: false,
+                allow_empty_input: false,
+                assume_sorted: false,
+                sorted_window: None,
+                allow_cuttlefish2_errors: false,
+                allow_superset: false,
+                strict_equality: false,
+                multiset: false,
+                allow_subset: false,
+                strand_specific: false,
+                both_strands_report: false,
+                k_range: None,
+                ambiguous_characters: AmbiguousCharacterPolicy::Split,
+                alphabet: Alphabet::Acgt,
+                min_sequence_length: 0,
+                exclude_id: Vec::new(),
+                check_duplicate_ids: false,
+                case_insensitive_ids: false,
+                min_count: 0,
+                gap: None,
+                input_format: InputFormat::Auto,
+                two_bit_input: false,
+                gfa_spell_paths: false,
+                respect_softmask: false,
+                rna: false,
+                dedup_strategy: DedupStrategy::Sort,
+                max_kmers: None,
+                spill_threshold: None,
+                sample: 1.0,
+                seed: 0,
+                parallel_read: false,
+                #[cfg(feature = "rayon")]
+                threads: 0,
+                #[cfg(feature = "progress")]
+                progress: false,
+                #[cfg(feature = "mmap")]
+                mmap: false,
+                format: ReportFormat::Plain,
+                stats_output: None,
+                per_sequence_stats: None,
+                mismatch_output: None,
+                mismatch_log_limit: 100,
+                mismatch_analysis: false,
+                complement_only: false,
+                multiplicity_histogram: false,
+                count_k_plus_1: false,
+                report_gc_content: false,
+                fail_fast: false,
+                dump_kmers: None,
+                ground_truth_dump: None,
+                export_fasta: None,
+                test_tigs_list: None,
+                unitigs_shard: Vec::new(),
+                unitigs: PathBuf::new(),
+                test_tigs: Vec::new(),
+            },
+        }
+    }
+
+    // Each setter sets the option of the same name, see the documentation of the command line options.
+    config_setters!(
+        log_level: LevelFilter,
+        do_not_verify: bool,
+        estimate_memory: bool,
+        list_kmers: bool,
+        panic_on_parse_error: bool,
+        debug_checks: bool,
+        allow_empty_input: bool,
+        assume_sorted: bool,
+        sorted_window: Option<usize>,
+        allow_cuttlefish2_errors: bool,
+        allow_superset: bool,
+        allow_subset: bool,
+        strict_equality: bool,
+        multiset: bool,
+        strand_specific: bool,
+        min_count: u64,
+        gap: Option<usize>,
+        dedup_strategy: DedupStrategy,
+        max_kmers: Option<usize>,
+        spill_threshold: Option<usize>,
+        sample: f64,
+        seed: u64,
+        parallel_read: bool,
+        stats_output: Option<PathBuf>,
+        complement_only: bool,
+        multiplicity_histogram: bool,
+        count_k_plus_1: bool,
+        fail_fast: bool,
+        dump_kmers: Option<PathBuf>,
+        ground_truth_dump: Option<PathBuf>,
+        export_fasta: Option<PathBuf>,
+    );
+
+    pub fn build(self) -> Config {
+        self.config
+    }
+}
+
+/// How duplicate kmers are removed from the inputs.
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq, clap::ValueEnum)]
+pub enum DedupStrategy {
+    /// Collect all kmers, sort them and then remove duplicates.
+    ///
+    /// Peak memory is proportional to the number of kmers including duplicates.
+    #[default]
+    Sort,
+    /// Insert kmers into a hash set while reading, and sort only the unique kmers.
+    ///
+    /// Peak memory is proportional to the number of unique kmers,
+    /// which is beneficial for inputs with many duplicates.
+    Hashset,
+    /// Insert kmers into a B-tree set while reading, which is slower than `hashset` and does not lower peak memory.
+    ///
+    /// The set keeps the kmers sorted without a separate sort,
+    /// but its nodes are not much smaller per kmer than a hash table, and each insertion takes logarithmic time.
+    /// On a random sequence of 3 Mbp with k = 31, `btreeset` took 12.2 s and 107 MB,
+    /// `hashset` took 8.1 s and 109 MB, and `sort` took 4.9 s and 59 MB.
+    Btreeset,
+}
+
+/// The reasons for a failed run.
+///
+/// Each variant results in a distinct process exit code, such that scripts can tell them apart.
+/// Panics result in the exit code 101.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Error {
+    /// The test tigs miss kmers that are present in the unitigs. Exit code 2.
+    MissingKmers,
+    /// The test tigs contain kmers that are not present in the unitigs. Exit code 3.
+    SuperfluousKmers,
+    /// The test tigs both miss kmers and contain kmers that are not present in the unitigs. Exit code 4.
+    MissingAndSuperfluousKmers,
+    /// An input given with `--assume-sorted` is not sorted. Exit code 1.
+    UnsortedInput,
+    /// The given arguments cannot be combined. Exit code 1.
+    IncompatibleArguments,
+    /// The file given with `--ground-truth-dump` is not a valid dump for the given arguments. Exit code 1.
+    InvalidKmerDump,
+    /// An input file cannot be parsed as configured, e.g. a `--two-bit-input` file with trailing bytes. Exit code 1.
+    InvalidInput,
+    /// The kmer size is not supported. Exit code 5.
+    IllegalKmerSize {
+        #[allow(dead_code)]
+        kmer_size: usize,
+    },
+    /// An input file contains no sequences. Exit code 6.
+    EmptyInput,
+    /// An input file contains more kmers than allowed by `--max-kmers`. Exit code 7.
+    TooManyKmers,
+    /// An internal consistency check failed, which indicates a bug. Exit code 8.
+    ///
+    /// With `--debug-checks`, the program panics instead.
+    InternalInvariant,
+    /// Writing or reading temporary files failed, e.g. the spilled kmers of `--spill-threshold`. Exit code 9.
+    Io,
+}
+
+impl Error {
+    fn exit_code(&self) -> ExitCode {
+        ExitCode::from(match self {
+            Error::MissingKmers => 2,
+            Error::SuperfluousKmers => 3,
+            Error::MissingAndSuperfluousKmers => 4,
+            Error::UnsortedInput
+            | Error::IncompatibleArguments
+            | Error::InvalidKmerDump
+            | Error::InvalidInput => 1,
+            Error::IllegalKmerSize { .. } => 5,
+            Error::EmptyInput => 6,
+            Error::TooManyKmers => 7,
+            Error::InternalInvariant => 8,
+            Error::Io => 9,
+        })
+    }
+
+    /// Combine the errors of comparing two different test tig files.
+    fn combine(self, other: Self) -> Self {
+        match (self, other) {
+            (Error::MissingKmers, Error::SuperfluousKmers)
+            | (Error::SuperfluousKmers, Error::MissingKmers)
+            | (Error::MissingAndSuperfluousKmers, Error::MissingKmers | Error::SuperfluousKmers)
+            | (Error::MissingKmers | Error::SuperfluousKmers, Error::MissingAndSuperfluousKmers) => {
+                Error::MissingAndSuperfluousKmers
+            }
+            (_, other) => other,
+        }
+    }
+}
+
+/// Compare the kmer content of each of the test tig files against the unitigs.
+///
+/// The unitigs are read only once, and a report is printed for each test tig file.
+/// Returns an error if any of the test tig files does not match the unitigs.
+pub fn compare_kmer_sets<
+    KmerType: FromIterator<u8> + Ord + Hash + Clone + Display + Kmer + Send,
+    TestTigs: Read + LengthHint,
+>(
+    unitigs: impl Read + LengthHint + Send,
+    test_tigs: impl IntoIterator<Item = (String, TestTigs)>,
+    config: Config,
+) -> Result<(), Error> {
+    compare_kmer_sets_with_visitor::<KmerType, _>(unitigs, test_tigs, config, |_| {})
+}
+
+/// Like [`compare_kmer_sets`], but additionally pass each mismatching kmer to `on_mismatch`.
+///
+/// The mismatches are passed in the same order as they are written to `--mismatch-output`,
+/// such that they can be collected without parsing the log or the output file.
+/// Mismatches are only found if the kmers are compared, i.e. not with `--do-not-verify` or `--scan-only`.
+fn compare_kmer_sets_with_visitor<
+    KmerType: FromIterator<u8> + Ord + Hash + Clone + Display + Kmer + Send,
+    TestTigs: Read + LengthHint,
+>(
+    unitigs: impl Read + LengthHint + Send,
+    test_tigs: impl IntoIterator<Item = (String, TestTigs)>,
+    config: Config,
+    mut on_mismatch: impl FnMut(MismatchEvent<KmerType>),
+) -> Result<(), Error> {
+    if config.sorted_window == Some(0) {
+        error!("--sorted-window must be positive");
+        return Err(Error::IncompatibleArguments);
+    }
+    // A sorted window implies --assume-sorted, such that it is subject to the same restrictions.
+    let config = Config {
+        assume_sorted: config.assume_sorted || config.sorted_window.is_some(),
+        ..config
+    };
+
+    if config.two_bit_input && KmerType::integer_size(config.k).is_none() {
+        error!(
+            "--two-bit-input requires k <= 64, but k = {} was given",
+            config.k
+        );
+        return Err(Error::IncompatibleArguments);
+    }
+
+    let mut kmer_iter_unitigs = kmer_iterator::<_, KmerType>(unitigs, "first input file", &config)?;
+
+    if (config.dump_kmers.is_some()
+        || config.ground_truth_dump.is_some()
+        || config.export_fasta.is_some())
+        && (config.assume_sorted
+            || config.do_not_verify
+            || config.scan_only
+            || config.estimate_memory)
+    {
+        error!("--dump-kmers, --ground-truth-dump and --export-fasta cannot be combined with --assume-sorted, --do-not-verify, --scan-only or --estimate-memory");
+        return Err(Error::IncompatibleArguments);
+    }
+
+    if !(config.sample > 0.0 && config.sample <= 1.0) {
+        error!("--sample must be in the interval (0, 1]");
+        return Err(Error::IncompatibleArguments);
+    }
+
+    if let Some(path) = &config.stats_output {
+        // Each report is appended to the file, so it is emptied first.
+        File::create(path)
+            .unwrap_or_else(|_| panic!("--stats-output points to a writable file: {path:?}"));
+    }
+    if let Some(path) = &config.per_sequence_stats {
+        // The statistics of each input are appended to the file, so it is emptied first.
+        File::create(path)
+            .unwrap_or_else(|_| panic!("--per-sequence-stats points to a writable file: {path:?}"));
+    }
+
+    if config.estimate_memory {
+        if config.scan_only || config.list_kmers || config.do_not_verify || config.assume_sorted {
+            error!("--estimate-memory cannot be combined with --scan-only, --list-kmers, --do-not-verify or --assume-sorted");
+            return Err(Error::IncompatibleArguments);
+        }
+
+        return estimate_memory(kmer_iter_unitigs, test_tigs, &config);
+    }
+
+    if config.scan_only {
+        scan_input(
+            kmer_iter_unitigs,
+            config.unitigs.display().to_string(),
+            &config,
+        );
+        for (test_tigs_name, test_tigs) in test_tigs {
+            let kmer_iter_test_tigs = kmer_iterator::<_, KmerType>(
+                test_tigs,
+                &format!("test tigs {test_tigs_name}"),
+                &config,
+            )?;
+            scan_input(kmer_iter_test_tigs, test_tigs_name, &config);
+        }
+        return Ok(());
+    }
+
+    if config.list_kmers {
+        if config.assume_sorted || config.do_not_verify {
+            error!("--list-kmers cannot be combined with --assume-sorted or --do-not-verify");
+            return Err(Error::IncompatibleArguments);
+        }
+        if test_tigs.into_iter().next().is_some() {
+            error!("--list-kmers requires exactly one input file");
+            return Err(Error::IncompatibleArguments);
+        }
+
+        return list_kmers(
+            kmer_iter_unitigs,
+            BufWriter::new(std::io::stdout().lock()),
+            &config,
+        );
+    }
+
+    let mut mismatch_output =
+        config.mismatch_output.as_ref().map(|path| {
+            BufWriter::new(File::create(path).unwrap_or_else(|_| {
+                panic!("--mismatch-output points to a writable file: {path:?}")
+            }))
+        });
+
+    if config.multiplicity_histogram {
+        if config.dedup_strategy != DedupStrategy::Sort {
+            error!("--multiplicity-histogram requires --dedup-strategy sort");
+            return Err(Error::IncompatibleArguments);
+        }
+        if config.assume_sorted || config.do_not_verify {
+            error!("--multiplicity-histogram cannot be combined with --assume-sorted or --do-not-verify");
+            return Err(Error::IncompatibleArguments);
+        }
+    }
+
+    if config.gap.is_some()
+        && (config.count_k_plus_1
+            || config.allow_cuttlefish2_errors
+            || config.dump_kmers.is_some()
+            || config.ground_truth_dump.is_some())
+    {
+        error!("--gap cannot be combined with --count-k-plus-1, --allow-cuttlefish2-errors, --dump-kmers or --ground-truth-dump");
+        return Err(Error::IncompatibleArguments);
+    }
+
+    if config.count_k_plus_1 && (config.assume_sorted || config.do_not_verify) {
+        error!("--count-k-plus-1 cannot be combined with --assume-sorted or --do-not-verify");
+        return Err(Error::IncompatibleArguments);
+    }
+
+    if config.strict_equality
+        && (config.allow_superset
+            || config.allow_subset
+            || config.allow_cuttlefish2_errors
+            || config.do_not_verify)
+    {
+        error!("--strict-equality cannot be combined with --allow-superset, --allow-subset, --allow-cuttlefish2-errors or --do-not-verify");
+        return Err(Error::IncompatibleArguments);
+    }
+
+    if config.multiset {
+        if config.dedup_strategy != DedupStrategy::Sort {
+            error!("--multiset requires --dedup-strategy sort");
+            return Err(Error::IncompatibleArguments);
+        }
+        if config.allow_superset
+            || config.allow_subset
+            || config.allow_cuttlefish2_errors
+            || config.assume_sorted
+            || config.do_not_verify
+            || config.spill_threshold.is_some()
+            || config.ground_truth_dump.is_some()
+        {
+            error!("--multiset cannot be combined with --allow-superset, --allow-subset, --allow-cuttlefish2-errors, --assume-sorted, --do-not-verify, --spill-threshold or --ground-truth-dump");
+            return Err(Error::IncompatibleArguments);
+        }
+    }
+
+    if config.allow_superset && config.allow_subset {
+        error!("--allow-superset cannot be combined with --allow-subset");
+        return Err(Error::IncompatibleArguments);
+    }
+
+    if config.fail_fast && (config.assume_sorted || config.do_not_verify || config.allow_superset) {
+        error!("--fail-fast cannot be combined with --assume-sorted, --do-not-verify or --allow-superset");
+        return Err(Error::IncompatibleArguments);
+    }
+
+    if config.parallel_read
+        && (config.assume_sorted
+            || config.do_not_verify
+            || config.fail_fast
+            || config.spill_threshold.is_some())
+    {
+        error!("--parallel-read cannot be combined with --assume-sorted, --do-not-verify, --fail-fast or --spill-threshold");
+        return Err(Error::IncompatibleArguments);
+    }
+
+    if config.complement_only
+        && (config.assume_sorted
+            || config.do_not_verify
+            || config.spill_threshold.is_some()
+            || config.sample != 1.0)
+    {
+        error!("--complement-only cannot be combined with --assume-sorted, --do-not-verify, --spill-threshold or --sample");
+        return Err(Error::IncompatibleArguments);
+    }
+
+    if let Some(spill_threshold) = config.spill_threshold {
+        if spill_threshold == 0 {
+            error!("--spill-threshold must be positive");
+            return Err(Error::IncompatibleArguments);
+        }
+        if config.assume_sorted
+            || config.do_not_verify
+            || config.dedup_strategy != DedupStrategy::Sort
+            || config.multiplicity_histogram
+            || config.count_k_plus_1
+            || config.fail_fast
+            || config.allow_cuttlefish2_errors
+            || config.dump_kmers.is_some()
+            || config.ground_truth_dump.is_some()
+            || config.export_fasta.is_some()
+        {
+            error!("--spill-threshold requires --dedup-strategy sort and cannot be combined with --assume-sorted, --do-not-verify, --multiplicity-histogram, --count-k-plus-1, --fail-fast, --allow-cuttlefish2-errors, --dump-kmers, --ground-truth-dump or --export-fasta");
+            return Err(Error::IncompatibleArguments);
+        }
+
+        let result = compare_spilled_kmer_sets(
+            kmer_iter_unitigs,
+            test_tigs,
+            mismatch_output.as_mut(),
+            &mut on_mismatch,
+            &config,
+        );
+
+        if let Some(mismatch_output) = mismatch_output.as_mut() {
+            mismatch_output.flush().unwrap();
+        }
+        return result;
+    }
+
+    if config.assume_sorted && !config.do_not_verify {
+        let mut test_tigs = test_tigs.into_iter();
+        let (Some((test_tigs_name, test_tigs)), None) = (test_tigs.next(), test_tigs.next()) else {
+            error!("--assume-sorted requires exactly one test tig file");
+            return Err(Error::IncompatibleArguments);
+        };
+        if config.allow_cuttlefish2_errors {
+            error!("--assume-sorted cannot be combined with --allow-cuttlefish2-errors");
+            return Err(Error::IncompatibleArguments);
+        }
+
+        let kmer_iter_test_tigs = kmer_iterator::<_, KmerType>(
+            test_tigs,
+            &format!("test tigs {test_tigs_name}"),
+            &config,
+        )?;
+        let result = compare_sorted_kmer_streams(
+            kmer_iter_unitigs,
+            test_tigs_name,
+            kmer_iter_test_tigs,
+            mismatch_output.as_mut(),
+            &mut on_mismatch,
+            &config,
+        );
+
+        if let Some(mismatch_output) = mismatch_output.as_mut() {
+            mismatch_output.flush().unwrap();
+        }
+        return result;
+    }
+
+    let mut test_tigs = test_tigs.into_iter();
+    let (unitig_kmers, first_test_tigs) = if config.parallel_read {
+        let first_test_tigs = test_tigs
+            .next()
+            .map(|(test_tigs_name, test_tigs)| {
+                let kmer_iter_test_tigs = kmer_iterator::<_, KmerType>(
+                    test_tigs,
+                    &format!("test tigs {test_tigs_name}"),
+                    &config,
+                )?;
+                Ok((test_tigs_name, kmer_iter_test_tigs))
+            })
+            .transpose()?;
+
+        std::thread::scope(|scope| {
+            let unitig_thread =
+                scope.spawn(|| collect_unitig_kmers(&mut kmer_iter_unitigs, &config));
+            let first_test_tigs =
+                first_test_tigs.map(|(test_tigs_name, mut kmer_iter_test_tigs)| {
+                    let test_tig_kmers = collect_test_tig_kmers(
+                        &mut kmer_iter_test_tigs,
+                        &test_tigs_name,
+                        None,
+                        &config,
+                    );
+                    (test_tigs_name, kmer_iter_test_tigs, Some(test_tig_kmers))
+                });
+            let unitig_kmers = unitig_thread
+                .join()
+                .unwrap_or_else(|panic| std::panic::resume_unwind(panic));
+            (unitig_kmers, first_test_tigs)
+        })
+    } else {
+        (collect_unitig_kmers(&mut kmer_iter_unitigs, &config), None)
+    };
+    let unitig_kmers = unitig_kmers?;
+    warn_about_short_sequences(&kmer_iter_unitigs, "first input file", &config);
+    check_not_empty(unitig_kmers.counts, "first input file", &config)?;
+
+    let mut result: Result<(), Error> = Ok(());
+    for test_tigs in first_test_tigs
+        .into_iter()
+        .map(|(test_tigs_name, kmer_iter_test_tigs, test_tig_kmers)| {
+            Ok((test_tigs_name, kmer_iter_test_tigs, test_tig_kmers))
+        })
+        .chain(test_tigs.map(|(test_tigs_name, test_tigs)| {
+            let kmer_iter_test_tigs = kmer_iterator::<_, KmerType>(
+                test_tigs,
+                &format!("test tigs {test_tigs_name}"),
+                &config,
+            )?;
+            Ok((test_tigs_name, kmer_iter_test_tigs, None))
+        }))
+    {
+        if let Err(error) =
+            test_tigs.and_then(|(test_tigs_name, kmer_iter_test_tigs, test_tig_kmers)| {
+                compare_test_tigs(
+                    &unitig_kmers,
+                    test_tigs_name,
+                    kmer_iter_test_tigs,
+                    test_tig_kmers,
+                    mismatch_output.as_mut(),
+                    &mut on_mismatch,
+                    &config,
+                )
+            })
+        {
+            result = Err(match result {
+                Ok(()) => error,
+                Err(previous_error) => previous_error.combine(error),
+            });
+        }
+    }
+
+    if let Some(mismatch_output) = mismatch_output.as_mut() {
+        mismatch_output.flush().unwrap();
+    }
+
+    result
+}
+
+/// Collect the kmers of the unitigs, or only read the unitigs if `--do-not-verify` is given.
+fn collect_unitig_kmers<KmerType: FromIterator<u8> + Ord + Hash + Clone + Display + Kmer + Send>(
+    kmer_iter_unitigs: &mut KmerIterator<impl Read, KmerType>,
+    config: &Config,
+) -> Result<UnitigKmers<KmerType>, Error> {
+    Ok(if !config.do_not_verify {
+        let mut unitig_multiplicities = Vec::new();
+        let (kmers_unitigs, unitig_counts) = if let Some(path) = &config.ground_truth_dump {
+            phase_info!(config, "Reading ground truth dump {path:?}");
+            let start = Instant::now();
+            let dump = BufReader::with_capacity(
+                16 * 1024 * 1024,
+                File::open(path)
+                    .unwrap_or_else(|_| panic!("--ground-truth-dump points to a file: {path:?}")),
+            );
+            let kmers_and_counts =
+                read_kmer_dump(dump, config.k, config.strand_specific).map_err(|dump_error| {
+                    error!("Cannot read --ground-truth-dump {path:?}: {dump_error}");
+                    Error::InvalidKmerDump
+                })?;
+            log_duration("Reading ground truth dump", start, config);
+            kmers_and_counts
+        } else {
+            let kmers_unitigs = match collect_unique_kmers(
+                kmer_iter_unitigs,
+                "first input file",
+                None,
+                config.multiset.then_some(&mut unitig_multiplicities),
+                None,
+                config,
+            ) {
+                Ok(kmers_unitigs) => kmers_unitigs,
+                Err(CollectError::TooManyKmers) => return Err(Error::TooManyKmers),
+                Err(CollectError::InternalInvariant) => return Err(Error::InternalInvariant),
+                Err(CollectError::RejectedKmer(_)) => {
+                    unreachable!("Kmers are only rejected by a prefilter")
+                }
+            };
+            (kmers_unitigs, kmer_iter_unitigs.counts())
+        };
+
+        if let Some(path) = &config.dump_kmers {
+            phase_info!(config, "Writing kmer dump {path:?}");
+            let start = Instant::now();
+            let output =
+                BufWriter::new(File::create(path).unwrap_or_else(|_| {
+                    panic!("--dump-kmers points to a writable file: {path:?}")
+                }));
+            write_kmer_dump(
+                output,
+                &kmers_unitigs,
+                unitig_counts,
+                config.k,
+                config.strand_specific,
+            )
+            .unwrap_or_else(|error| panic!("Writing {path:?} failed: {error}"));
+            log_duration("Writing kmer dump", start, config);
+        }
+        export_fasta(&kmers_unitigs, config);
+        let index = GroundTruthIndex::new(kmers_unitigs, config.strand_specific);
+
+        let unitig_kmers_without_superstrings = if config.allow_cuttlefish2_errors {
+            phase_info!(config, "Collecting kmers without superstrings");
+            let start = Instant::now();
+            let unitig_kmers_without_superstrings = index
+                .kmers()
+                .iter()
+                .filter(|&kmer| !index.has_superstring(kmer))
+                .cloned()
+                .collect();
+            log_duration("Collecting kmers without superstrings", start, config);
+            unitig_kmers_without_superstrings
+        } else {
+            Vec::new()
+        };
+        debug_assert!(unitig_kmers_without_superstrings.is_sorted());
+        for kmer in &unitig_kmers_without_superstrings {
+            debug!("Unitig kmer without superstrings: {kmer}");
+        }
+
+        UnitigKmers {
+            statistics: Some(KmerSetStatistics {
+                k_plus_1_mer_count: config.count_k_plus_1.then(|| {
+                    phase_info!(config, "Counting distinct (k+1)-mers of unitigs");
+                    let start = Instant::now();
+                    let count = count_k_plus_1_mers(index.kmers(), config.strand_specific);
+                    log_duration("Counting distinct (k+1)-mers of unitigs", start, config);
+                    count
+                }),
+                ..KmerSetStatistics::new(index.kmers(), config.kmer_length())
+            }),
+            index,
+            multiplicities: unitig_multiplicities,
+            kmers_without_superstrings: unitig_kmers_without_superstrings,
+            counts: unitig_counts,
+            format: kmer_iter_unitigs.format(),
+        }
+    } else {
+        phase_info!(config, "Reading first input file");
+        assert!(kmer_iter_unitigs.by_ref().all(|_| true));
+        UnitigKmers {
+            index: GroundTruthIndex::new(Vec::new(), config.strand_specific),
+            multiplicities: Vec::new(),
+            kmers_without_superstrings: Vec::new(),
+            counts: kmer_iter_unitigs.counts(),
+            format: kmer_iter_unitigs.format(),
+            statistics: None,
+        }
+    })
+}
+
+/// The kmers collected from the unitigs, against which each test tig file is compared.
+struct UnitigKmers<KmerType> {
+    /// The unique unitig kmers in sorted order, indexed for membership queries.
+    index: GroundTruthIndex<KmerType>,
+    /// The multiplicity of each unitig kmer, if `--multiset` is given.
+    multiplicities: Vec<usize>,
+    /// The unitig kmers without superstrings, which are excused if cuttlefish2 errors are allowed.
+    kmers_without_superstrings: Vec<KmerType>,
+    /// The size statistics of the unitigs.
+    counts: InputCounts,
+    /// The detected format of the unitigs, which is unknown if they were read from a kmer dump.
+    format: Format,
+    /// The statistics of the unitig kmer set, if the kmers were collected.
+    statistics: Option<KmerSetStatistics>,
+}
+
+/// Statistics of a collected set of unique kmers.
+#[derive(Debug, Clone, Copy)]
+struct KmerSetStatistics {
+    /// The number of distinct kmers.
+    kmer_count: usize,
+    /// The number of kmers that are their own reverse complement.
+    palindrome_count: usize,
+    fingerprint: KmerSetFingerprint,
+    /// The number of distinct (k+1)-mers formed by the kmers, if `--count-k-plus-1` is given for the unitigs.
+    k_plus_1_mer_count: Option<usize>,
+}
+
+impl KmerSetStatistics {
+    fn new<KmerType: Kmer>(kmers: &[KmerType], k: usize) -> Self {
+        Self {
+            kmer_count: kmers.len(),
+            palindrome_count: count_palindromes(kmers, k),
+            fingerprint: KmerSetFingerprint::new(kmers),
+            k_plus_1_mer_count: None,
+        }
+    }
+}
+
+/// Create an iterator over the kmers of the input, parsed as given in the config.
+///
+/// With `--two-bit-input`, returns an error if the length of the input is known and not a multiple of the integer width.
+fn kmer_iterator<InputReader: Read + LengthHint, KmerType: Kmer>(
+    input: InputReader,
+    input_name: &str,
+    config: &Config,
+) -> Result<KmerIterator<InputReader, KmerType>, Error> {
+    let length_hint = input.length_hint();
+    if config.two_bit_input {
+        if let (Some(length), Some(size)) = (length_hint, KmerType::integer_size(config.k)) {
+            if length % size as u64 != 0 {
+                error!("The {input_name} has {length} bytes, which is not a multiple of the {size} bytes of a 2-bit packed kmer");
+                return Err(Error::InvalidInput);
+            }
+        }
+    }
+
+    Ok(KmerIterator::new(input, config.k, ParseOptions::new(config)).with_length_hint(length_hint))
+}
+
+/// The unique kmers of a test tig file in sorted order, and their multiplicity histogram if `--multiplicity-histogram` is given.
+///
+/// Besides the unique kmers, these are the multiplicity histogram and the multiplicity of each kmer,
+/// which are empty unless requested.
+type TestTigKmers<KmerType> = (Vec<KmerType>, BTreeMap<usize, usize>, Vec<usize>);
+
+/// Collect the kmers of a test tig file, rejecting kmers that are not in the prefilter.
+///
+/// Returns `None` if a kmer was rejected, in which case the test tigs were only read up to that kmer.
+fn collect_test_tig_kmers<
+    KmerType: FromIterator<u8> + Ord + Hash + Clone + Display + Kmer + Send,
+>(
+    kmer_iter_test_tigs: &mut KmerIterator<impl Read, KmerType>,
+    test_tigs_name: &str,
+    prefilter: Option<&GroundTruthIndex<KmerType>>,
+    config: &Config,
+) -> Result<Option<TestTigKmers<KmerType>>, Error> {
+    let mut multiplicity_histogram = BTreeMap::new();
+    let mut multiplicities = Vec::new();
+    match collect_unique_kmers(
+        kmer_iter_test_tigs,
+        &format!("test tigs {test_tigs_name}"),
+        config
+            .multiplicity_histogram
+            .then_some(&mut multiplicity_histogram),
+        config.multiset.then_some(&mut multiplicities),
+        prefilter,
+        config,
+    ) {
+        Ok(kmers_test_tigs) => Ok(Some((
+            kmers_test_tigs,
+            multiplicity_histogram,
+            multiplicities,
+        ))),
+        Err(CollectError::RejectedKmer(kmer)) => {
+            error!("Test tigs {test_tigs_name} contain kmer {kmer}, which is not present in unitigs, so they are not read any further");
+            Ok(None)
+        }
+        Err(CollectError::TooManyKmers) => Err(Error::TooManyKmers),
+        Err(CollectError::InternalInvariant) => Err(Error::InternalInvariant),
+    }
+}
+
+/// Compare the kmer content of a single test tig file against the already collected unitig kmers.
+///
+/// If the test tig kmers were already collected, they are given as `test_tig_kmers`.
+fn compare_test_tigs<KmerType: FromIterator<u8> + Ord + Hash + Clone + Display + Kmer + Send>(
+    unitig_kmers: &UnitigKmers<KmerType>,
+    test_tigs_name: String,
+    mut kmer_iter_test_tigs: KmerIterator<impl Read, KmerType>,
+    test_tig_kmers: Option<Result<Option<TestTigKmers<KmerType>>, Error>>,
+    mut mismatch_output: Option<&mut impl Write>,
+    on_mismatch: &mut impl FnMut(MismatchEvent<KmerType>),
+    config: &Config,
+) -> Result<(), Error> {
+    let mut multiplicity_histogram = BTreeMap::new();
+    let mut test_tig_statistics = None;
+    let merge_counts = if config.do_not_verify {
+        phase_info!(config, "Reading test tigs {test_tigs_name}");
+        assert!(kmer_iter_test_tigs.by_ref().all(|_| true));
+        Some(MergeCounts::default())
+    } else if let Some((
+        kmers_test_tigs,
+        test_tig_multiplicity_histogram,
+        test_tig_multiplicities,
+    )) = match test_tig_kmers {
+        Some(test_tig_kmers) => test_tig_kmers?,
+        None => collect_test_tig_kmers(
+            &mut kmer_iter_test_tigs,
+            &test_tigs_name,
+            config.fail_fast.then_some(&unitig_kmers.index),
+            config,
+        )?,
+    } {
+        multiplicity_histogram = test_tig_multiplicity_histogram;
+
+        test_tig_statistics = Some(KmerSetStatistics::new(
+            &kmers_test_tigs,
+            config.kmer_length(),
+        ));
+
+        if let Some(mismatch_output) = mismatch_output.as_mut() {
+            writeln!(mismatch_output, "# {test_tigs_name}").unwrap();
+        }
+
+        let mut merge_counts = merge_unique_kmers(
+            unitig_kmers.index.kmers(),
+            &unitig_kmers.kmers_without_superstrings,
+            &kmers_test_tigs,
+            |event| visit_mismatch(event, mismatch_output.as_deref_mut(), on_mismatch),
+            &test_tigs_name,
+            config,
+        );
+
+        if config.multiset {
+            phase_info!(config, "Comparing kmer multiplicities");
+            merge_counts.multiplicity_mismatches = Some(merge_multiplicities(
+                unitig_kmers.index.kmers(),
+                &unitig_kmers.multiplicities,
+                &kmers_test_tigs,
+                &test_tig_multiplicities,
+                config.mismatch_log_limit,
+            ));
+        }
+
+        if config.complement_only
+            && (merge_counts.shared_kmer_count != unitig_kmers.index.kmers().len()
+                || merge_counts.shared_kmer_count != kmers_test_tigs.len())
+        {
+            phase_info!(
+                config,
+                "Comparing unitigs against the complement of test tigs {test_tigs_name}"
+            );
+            if complement_matches(unitig_kmers.index.kmers(), &kmers_test_tigs, config) {
+                warn!("The complement of test tigs {test_tigs_name} matches the unitigs, which indicates that the test tigs were complemented without being reversed");
+            } else {
+                info!("The complement of test tigs {test_tigs_name} does not match the unitigs either");
+            }
+        }
+        Some(merge_counts)
+    } else {
+        // The test tigs were rejected at their first superfluous kmer, so they were not merged with the unitigs.
+        None
+    };
+
+    note_format_difference(unitig_kmers.format, &kmer_iter_test_tigs, &test_tigs_name);
+    report_comparison(
+        unitig_kmers.counts,
+        &kmer_iter_test_tigs,
+        test_tigs_name,
+        merge_counts,
+        unitig_kmers.statistics.zip(test_tig_statistics),
+        multiplicity_histogram,
+        config,
+    )
+}
+
+/// Merge the sorted unique kmers of the unitigs and of a test tig file, and log the mismatch analysis if requested.
+///
+/// Unitig kmers missing in the test tigs that are contained in `excused_unitig_kmers` are counted separately.
+fn merge_unique_kmers<KmerType: Ord + Display>(
+    unitig_kmers: &[KmerType],
+    excused_unitig_kmers: &[KmerType],
+    test_tig_kmers: &[KmerType],
+    on_mismatch: impl FnMut(MismatchEvent<KmerType>),
+    test_tigs_name: &str,
+    config: &Config,
+) -> MergeCounts {
+    phase_info!(config, "Comparing kmer content");
+    let start = Instant::now();
+    let mut mismatch_analysis = config.mismatch_analysis.then(MismatchAnalysis::default);
+    let merge_counts = merge_kmers(
+        unitig_kmers.iter(),
+        test_tig_kmers.iter(),
+        excused_unitig_kmers,
+        on_mismatch,
+        config.mismatch_log_limit,
+        mismatch_analysis.as_mut(),
+    );
+    log_duration(
+        &format!("Comparing test tigs {test_tigs_name}"),
+        start,
+        config,
+    );
+    if let Some(mismatch_analysis) = &mismatch_analysis {
+        mismatch_analysis.log(test_tigs_name);
+    }
+    merge_counts
+}
+
+/// Returns true if the unitig kmers equal the complements of the test tig kmers.
+///
+/// Both kmer sets are given in sorted order, and are canonical unless `--strand-specific` is given.
+fn complement_matches<KmerType: Ord + Display + Kmer + Send>(
+    unitig_kmers: &[KmerType],
+    test_tig_kmers: &[KmerType],
+    config: &Config,
+) -> bool {
+    let mut complemented_test_tig_kmers: Vec<_> = test_tig_kmers
+        .iter()
+        .map(|kmer| {
+            let complement = kmer.complement();
+            if config.strand_specific {
+                complement
+            } else {
+                complement.into_canonical()
+            }
+        })
+        .collect();
+    sort_kmers(&mut complemented_test_tig_kmers, config);
+
+    unitig_kmers == complemented_test_tig_kmers.as_slice()
+}
+
+/// Compare the kmer content of the unitigs and the test tigs without collecting them,
+/// assuming that both inputs yield their canonical kmers in sorted order.
+fn compare_sorted_kmer_streams<KmerType: FromIterator<u8> + Ord + Clone + Display + Kmer>(
+    mut kmer_iter_unitigs: KmerIterator<impl Read, KmerType>,
+    test_tigs_name: String,
+    mut kmer_iter_test_tigs: KmerIterator<impl Read, KmerType>,
+    mut mismatch_output: Option<&mut impl Write>,
+    on_mismatch: &mut impl FnMut(MismatchEvent<KmerType>),
+    config: &Config,
+) -> Result<(), Error> {
+    let normalise = |kmer: KmerType| {
+        if config.strand_specific {
+            kmer
+        } else {
+            kmer.into_canonical()
+        }
+    };
+
+    if let Some(mismatch_output) = mismatch_output.as_mut() {
+        writeln!(mismatch_output, "# {test_tigs_name}").unwrap();
+    }
+
+    let mut unitig_sampler = kmer_sampler(config);
+    let mut test_tig_sampler = kmer_sampler(config);
+    // Without --sorted-window, a window of a single kmer leaves the order unchanged.
+    let window_size = config.sorted_window.unwrap_or(1);
+
+    phase_info!(config, "Comparing sorted kmer streams");
+    let start = Instant::now();
+    let mut sorted_unitig_kmers = SortedUniqueKmers::new(SortingWindow::new(
+        kmer_iter_unitigs.by_ref().map(normalise).filter(|kmer| {
+            unitig_sampler
+                .as_mut()
+                .is_none_or(|sampler| sampler.keeps(kmer))
+        }),
+        window_size,
+    ));
+    let mut sorted_test_tig_kmers = SortedUniqueKmers::new(SortingWindow::new(
+        kmer_iter_test_tigs.by_ref().map(normalise).filter(|kmer| {
+            test_tig_sampler
+                .as_mut()
+                .is_none_or(|sampler| sampler.keeps(kmer))
+        }),
+        window_size,
+    ));
+    let mut mismatch_analysis = config.mismatch_analysis.then(MismatchAnalysis::default);
+    let merge_counts = merge_kmers(
+        sorted_unitig_kmers.by_ref(),
+        sorted_test_tig_kmers.by_ref(),
+        &[],
+        |event| visit_mismatch(event, mismatch_output.as_deref_mut(), on_mismatch),
+        config.mismatch_log_limit,
+        mismatch_analysis.as_mut(),
+    );
+
+    // The merge stops early at the first kmer that is out of order.
+    check_sorted(&sorted_unitig_kmers, "unitigs", config)?;
+    check_sorted(&sorted_test_tig_kmers, "test tigs", config)?;
+    log_duration("Reading and comparing sorted kmer streams", start, config);
+    if let Some(mismatch_analysis) = &mismatch_analysis {
+        mismatch_analysis.log(&test_tigs_name);
+    }
+
+    warn_about_short_sequences(&kmer_iter_unitigs, "unitigs", config);
+    check_not_empty(kmer_iter_unitigs.counts(), "unitigs", config)?;
+    note_format_difference(
+        kmer_iter_unitigs.format(),
+        &kmer_iter_test_tigs,
+        &test_tigs_name,
+    );
+    report_comparison(
+        kmer_iter_unitigs.counts(),
+        &kmer_iter_test_tigs,
+        test_tigs_name,
+        Some(merge_counts),
+        None,
+        BTreeMap::new(),
+        config,
+    )
+}
+
+/// Compare the kmer content of each of the test tig files against the unitigs,
+/// spilling the kmers of each input file to disk in sorted runs of at most `--spill-threshold` kmers.
+///
+/// The runs of the unitigs are kept until all test tig files are compared.
+/// Since the kmers are never all in memory at once, the kmer set statistics are not computed.
+fn compare_spilled_kmer_sets<
+    KmerType: FromIterator<u8> + Ord + Clone + Display + Kmer + Send,
+    TestTigs: Read + LengthHint,
+>(
+    mut kmer_iter_unitigs: KmerIterator<impl Read, KmerType>,
+    test_tigs: impl IntoIterator<Item = (String, TestTigs)>,
+    mut mismatch_output: Option<&mut impl Write>,
+    on_mismatch: &mut impl FnMut(MismatchEvent<KmerType>),
+    config: &Config,
+) -> Result<(), Error> {
+    let unitig_kmers = spill_unique_kmers(&mut kmer_iter_unitigs, "first input file", config)?;
+    warn_about_short_sequences(&kmer_iter_unitigs, "first input file", config);
+    check_not_empty(kmer_iter_unitigs.counts(), "first input file", config)?;
+
+    let mut result: Result<(), Error> = Ok(());
+    for (test_tigs_name, test_tigs) in test_tigs {
+        let mut kmer_iter_test_tigs = kmer_iterator::<_, KmerType>(
+            test_tigs,
+            &format!("test tigs {test_tigs_name}"),
+            config,
+        )?;
+        let comparison_result = spill_unique_kmers(
+            &mut kmer_iter_test_tigs,
+            &format!("test tigs {test_tigs_name}"),
+            config,
+        )
+        .and_then(|test_tig_kmers| {
+            if let Some(mismatch_output) = mismatch_output.as_mut() {
+                writeln!(mismatch_output, "# {test_tigs_name}").unwrap();
+            }
+
+            phase_info!(config, "Comparing kmer content");
+            let start = Instant::now();
+            let mut mismatch_analysis = config.mismatch_analysis.then(MismatchAnalysis::default);
+            let mut unitig_kmer_iter = unitig_kmers
+                .iter()
+                .map_err(|error| spill_error("first input file", error))?;
+            let mut test_tig_kmer_iter = test_tig_kmers
+                .iter()
+                .map_err(|error| spill_error(&format!("test tigs {test_tigs_name}"), error))?;
+            let merge_counts = merge_kmers(
+                unitig_kmer_iter.by_ref(),
+                test_tig_kmer_iter.by_ref(),
+                &[],
+                |event| visit_mismatch(event, mismatch_output.as_deref_mut(), on_mismatch),
+                config.mismatch_log_limit,
+                mismatch_analysis.as_mut(),
+            );
+            if let Some(error) = unitig_kmer_iter.take_error() {
+                return Err(spill_error("first input file", error));
+            }
+            if let Some(error) = test_tig_kmer_iter.take_error() {
+                return Err(spill_error(&format!("test tigs {test_tigs_name}"), error));
+            }
+            log_duration(
+                &format!("Comparing test tigs {test_tigs_name}"),
+                start,
+                config,
+            );
+            if let Some(mismatch_analysis) = &mismatch_analysis {
+                mismatch_analysis.log(&test_tigs_name);
+            }
+
+            note_format_difference(
+                kmer_iter_unitigs.format(),
+                &kmer_iter_test_tigs,
+                &test_tigs_name,
+            );
+            report_comparison(
+                kmer_iter_unitigs.counts(),
+                &kmer_iter_test_tigs,
+                test_tigs_name,
+                Some(merge_counts),
+                None,
+                BTreeMap::new(),
+                config,
+            )
+        });
+
+        if let Err(error) = comparison_result {
+            result = Err(match result {
+                Ok(()) => error,
+                Err(previous_error) => previous_error.combine(error),
+            });
+        }
+    }
+
+    result
+}
+
+/// Log the failure to write or read the spilled kmers of the input, and return the corresponding error.
+fn spill_error(input_name: &str, error: std::io::Error) -> Error {
+    error!("Spilling kmers of {input_name} failed: {error}");
+    Error::Io
+}
+
+/// Read all kmers from the iterator like [`collect_unique_kmers`] with the sort dedup strategy,
+/// but sort and deduplicate them in runs of `--spill-threshold` kmers that are written to disk.
+///
+/// The last run is kept in memory, so nothing is written to disk if the input has at most `--spill-threshold` kmers.
+fn spill_unique_kmers<KmerType: FromIterator<u8> + Ord + Clone + Kmer + Send>(
+    kmer_iter: &mut KmerIterator<impl Read, KmerType>,
+    input_name: &str,
+    config: &Config,
+) -> Result<SpilledKmers<KmerType>, Error> {
+    let spill_threshold = config.spill_threshold.unwrap();
+    let mut spilled_kmers = SpilledKmers::new(config.kmer_length());
+    let mut kmers = Vec::new();
+
+    let mut sampler = kmer_sampler(config);
+    let sampled_kmers = kmer_iter
+        .by_ref()
+        .map(|kmer| {
+            if config.strand_specific {
+                kmer
+            } else {
+                kmer.into_canonical()
+            }
+        })
+        .filter(|kmer| sampler.as_mut().is_none_or(|sampler| sampler.keeps(kmer)));
+
+    phase_info!(config, "Reading {input_name}");
+    let start = Instant::now();
+    for (kmer_count, kmer) in sampled_kmers.enumerate() {
+        if Some(kmer_count) == config.max_kmers {
+            error!(
+                "{input_name} contains more than --max-kmers = {} kmers",
+                config.max_kmers.unwrap()
+            );
+            return Err(Error::TooManyKmers);
+        }
+        kmers.push(kmer);
+
+        if kmers.len() == spill_threshold {
+            sort_kmers(&mut kmers, config);
+            kmers.dedup();
+            spilled_kmers
+                .spill(&kmers)
+                .map_err(|error| spill_error(input_name, error))?;
+            kmers.clear();
+        }
+    }
+    sort_kmers(&mut kmers, config);
+    kmers.dedup();
+    spilled_kmers.keep_in_memory(kmers);
+    log_duration(
+        &format!(
+            "Reading {input_name} into {} spilled runs",
+            spilled_kmers.spilled_run_count()
+        ),
+        start,
+        config,
+    );
+
+    Ok(spilled_kmers)
+}
+
+/// Write a mismatching kmer to the `--mismatch-output`, if given, and pass it on to `on_mismatch`.
+fn visit_mismatch<KmerType: Display>(
+    event: MismatchEvent<KmerType>,
+    mismatch_output: Option<&mut impl Write>,
+    on_mismatch: &mut impl FnMut(MismatchEvent<KmerType>),
+) {
+    if let Some(mismatch_output) = mismatch_output {
+        let side = match event.kind {
+            MismatchKind::MissingInTest => "unitigs",
+            MismatchKind::ExtraInTest => "test_tigs",
+        };
+        writeln!(mismatch_output, "{side}\t{}", event.kmer).unwrap();
+    }
+    on_mismatch(event);
+}
+
+/// Fail if a sorted kmer stream was found to be not sorted.
+fn check_sorted<Iter: Iterator<Item: Ord + Display>>(
+    sorted_kmers: &SortedUniqueKmers<Iter>,
+    input_name: &str,
+    config: &Config,
+) -> Result<(), Error> {
+    if let Some((kmer, next_kmer)) = sorted_kmers.out_of_order_kmers() {
+        if let Some(sorted_window) = config.sorted_window {
+            error!("The {input_name} are not sorted within a window of --sorted-window = {sorted_window} kmers: kmer {kmer} is followed by {next_kmer}");
+        } else {
+            error!("The {input_name} are not sorted: kmer {kmer} is followed by {next_kmer}");
+        }
+        Err(Error::UnsortedInput)
+    } else {
+        Ok(())
+    }
+}
+
+/// Print the report for the comparison of the test tigs against the unitigs, and decide if the comparison succeeded.
+///
+/// The kmer set statistics are given for the unitigs and the test tigs, in this order, if the kmers were collected.
+/// The merge counts are not given if the test tigs were rejected by `--fail-fast` before they were merged with the unitigs.
+fn report_comparison<KmerType>(
+    unitig_counts: InputCounts,
+    kmer_iter_test_tigs: &KmerIterator<impl Read, KmerType>,
+    test_tigs_name: String,
+    merge_counts: Option<MergeCounts>,
+    set_statistics: Option<(KmerSetStatistics, KmerSetStatistics)>,
+    multiplicity_histogram: BTreeMap<usize, usize>,
+    config: &Config,
+) -> Result<(), Error> {
+    let MergeCounts {
+        superfluous_unitig_kmer_count,
+        excused_unitig_kmer_count,
+        superfluous_test_tig_kmer_count,
+        multiplicity_mismatches,
+        ..
+    } = merge_counts.unwrap_or_default();
+
+    let unexcused_unitig_kmer_count = superfluous_unitig_kmer_count - excused_unitig_kmer_count;
+    if unexcused_unitig_kmer_count != 0 {
+        info!("Test tigs miss {unexcused_unitig_kmer_count} kmers that are present in unitigs");
+    }
+    if excused_unitig_kmer_count != 0 {
+        info!("Test tigs miss {excused_unitig_kmer_count} kmers without superstrings that are present in unitigs, which are excused because cuttlefish2 errors are allowed");
+    }
+    if superfluous_test_tig_kmer_count != 0 {
+        info!("Test tigs contain {superfluous_test_tig_kmer_count} kmers that are not present in unitigs");
+    }
+    if let Some(multiplicity_mismatches) = multiplicity_mismatches {
+        if multiplicity_mismatches.lower_in_test_tigs_count != 0 {
+            info!(
+                "Test tigs contain {} kmers less often than unitigs",
+                multiplicity_mismatches.lower_in_test_tigs_count
+            );
+        }
+        if multiplicity_mismatches.higher_in_test_tigs_count != 0 {
+            info!(
+                "Test tigs contain {} kmers more often than unitigs",
+                multiplicity_mismatches.higher_in_test_tigs_count
+            );
+        }
+    }
+    if let (Some(unitigs_gc), Some(test_tigs_gc)) =
+        (unitig_counts.gc_content(), kmer_iter_test_tigs.gc_content())
+    {
+        info!("GC content of unitigs: {unitigs_gc:.3}, of test tigs: {test_tigs_gc:.3}");
+    }
+
+    warn_about_short_sequences(
+        kmer_iter_test_tigs,
+        &format!("test tigs {test_tigs_name}"),
+        config,
+    );
+    check_not_empty(
+        kmer_iter_test_tigs.counts(),
+        &format!("test tigs {test_tigs_name}"),
+        config,
+    )?;
+
+    let report = verification_report(
+        test_tigs_name,
+        unitig_counts,
+        kmer_iter_test_tigs.counts(),
+        merge_counts,
+        set_statistics,
+        multiplicity_histogram,
+        config,
+    );
+    let status = report.status;
+    let unique_kmer_count = report.unique_kmer_count;
+    let test_tigs_kmer_count = kmer_iter_test_tigs.kmer_position_count();
+    let has_superfluous_kmers_unitigs = unexcused_unitig_kmer_count != 0;
+    // Rejected test tigs contain at least the kmer they were rejected at.
+    let has_superfluous_kmers_test_tigs =
+        superfluous_test_tig_kmer_count != 0 || merge_counts.is_none();
+
+    write_statistics(|output| report.write(config.format, output), config);
+
+    if config.strict_equality || config.multiset {
+        match status {
+            VerificationStatus::Match => {}
+            VerificationStatus::MissingKmers => {
+                error!("Test tigs miss kmers that are present in unitigs")
+            }
+            VerificationStatus::SuperfluousKmers => {
+                error!("Test tigs contain kmers that are missing in unitigs")
+            }
+            VerificationStatus::MissingAndSuperfluousKmers => {
+                error!(
+                    "Test tigs both miss kmers and contain kmers that are not present in unitigs"
+                )
+            }
+        }
+    } else if config.allow_subset {
+        if has_superfluous_kmers_test_tigs {
+            error!("Test tigs contain kmers that are missing in unitigs");
+        } else if superfluous_unitig_kmer_count != 0 {
+            info!("Test tigs miss kmers that are present in unitigs, which is allowed by --allow-subset");
+        }
+    } else if config.allow_superset {
+        if has_superfluous_kmers_unitigs {
+            error!("Test tigs miss kmers that are present in unitigs");
+        } else if has_superfluous_kmers_test_tigs {
+            info!("Test tigs contain kmers that are missing in unitigs, which is allowed by --allow-superset");
+        }
+    } else if !has_superfluous_kmers_unitigs && !has_superfluous_kmers_test_tigs {
+        match unique_kmer_count.cmp(&test_tigs_kmer_count) {
+            Ordering::Greater => {
+                debug!("Unitig kmer count: {unique_kmer_count}");
+                debug!("Test tigs kmer count: {test_tigs_kmer_count}");
+                if config.allow_cuttlefish2_errors {
+                    debug!("Missing kmers in test tigs are ignored because cuttlefish2 errors are allowed.");
+                } else {
+                    error!("Test tigs are missing kmers. Note that the test tigs are assumed to contain no duplicate kmers.");
+                }
+            }
+            Ordering::Equal => {}
+            Ordering::Less => {
+                debug!("Unitig kmer count: {unique_kmer_count}");
+                debug!("Test tigs kmer count: {test_tigs_kmer_count}");
+                info!("Test tigs contain more kmers than unitigs. This may happen if they contain duplicates.");
+            }
+        }
+    } else if !has_superfluous_kmers_unitigs {
+        error!("Test tigs contain kmers that are missing in unitigs");
+    } else if !has_superfluous_kmers_test_tigs {
+        error!("Test tigs miss kmers that are present in unitigs");
+    } else {
+        error!("Test tigs both miss kmers and contain kmers that are not present in unitigs");
+    }
+
+    match status {
+        VerificationStatus::Match => {
+            info!("Success!");
+            Ok(())
+        }
+        VerificationStatus::MissingKmers => Err(Error::MissingKmers),
+        VerificationStatus::SuperfluousKmers => Err(Error::SuperfluousKmers),
+        VerificationStatus::MissingAndSuperfluousKmers => Err(Error::MissingAndSuperfluousKmers),
+    }
+}
+
+/// Build the report of comparing the unitigs against the test tigs, given the counts of the inputs and of the merge.
+///
+/// Without merge counts, the test tigs are reported as containing superfluous kmers,
+/// and the metrics that require the merge are left out.
+fn verification_report(
+    test_tigs_name: String,
+    unitig_counts: InputCounts,
+    test_tig_counts: InputCounts,
+    merge_counts: Option<MergeCounts>,
+    set_statistics: Option<(KmerSetStatistics, KmerSetStatistics)>,
+    multiplicity_histogram: BTreeMap<usize, usize>,
+    config: &Config,
+) -> VerificationReport {
+    let MergeCounts {
+        superfluous_unitig_kmer_count,
+        excused_unitig_kmer_count,
+        superfluous_test_tig_kmer_count,
+        shared_kmer_count,
+        multiplicity_mismatches,
+    } = merge_counts.unwrap_or_default();
+    let merged = merge_counts.is_some();
+    let has_superfluous_kmers_unitigs = superfluous_unitig_kmer_count != excused_unitig_kmer_count;
+    let has_superfluous_kmers_test_tigs = superfluous_test_tig_kmer_count != 0 || !merged;
+    // Kmers that occur less often in the test tigs are partially missing, and vice versa.
+    let multiplicity_mismatches = multiplicity_mismatches.unwrap_or_default();
+    let has_missing_kmer_occurrences =
+        has_superfluous_kmers_unitigs || multiplicity_mismatches.lower_in_test_tigs_count != 0;
+    let has_superfluous_kmer_occurrences =
+        has_superfluous_kmers_test_tigs || multiplicity_mismatches.higher_in_test_tigs_count != 0;
+
+    let unitigs_sequence_size = unitig_counts.character_count;
+    let test_tigs_sequence_size = test_tig_counts.character_count;
+    let unitigs_string_count = unitig_counts.sequence_count;
+    let test_tigs_string_count = test_tig_counts.sequence_count;
+    let compression_rate = test_tigs_sequence_size as f64 / unitigs_sequence_size as f64;
+    let string_count_rate = test_tigs_string_count as f64 / unitigs_string_count as f64;
+    let unique_kmer_count = unitig_counts.kmer_position_count;
+    let test_tigs_kmer_count = test_tig_counts.kmer_position_count;
+    // The merge counts are of distinct kmers, so duplicates and sampling do not skew the index.
+    let union_kmer_count =
+        shared_kmer_count + superfluous_unitig_kmer_count + superfluous_test_tig_kmer_count;
+    let jaccard = if union_kmer_count == 0 {
+        1.0
+    } else {
+        shared_kmer_count as f64 / union_kmer_count as f64
+    };
+
+    let status = if config.strict_equality || config.multiset {
+        // Only the merges of the distinct kmers and their multiplicities decide, so the kmer counts are ignored.
+        match (
+            has_missing_kmer_occurrences,
+            has_superfluous_kmer_occurrences,
+        ) {
+            (false, false) => VerificationStatus::Match,
+            (true, false) => VerificationStatus::MissingKmers,
+            (false, true) => VerificationStatus::SuperfluousKmers,
+            (true, true) => VerificationStatus::MissingAndSuperfluousKmers,
+        }
+    } else if config.allow_subset {
+        // Only the merge of the distinct kmers decides, so duplicate test tig kmers are not mistaken for superfluous ones.
+        if has_superfluous_kmers_test_tigs {
+            VerificationStatus::SuperfluousKmers
+        } else {
+            VerificationStatus::Match
+        }
+    } else if config.allow_superset {
+        // Only the merge of the distinct kmers decides, so duplicate test tig kmers cannot hide missing unitig kmers.
+        if has_superfluous_kmers_unitigs {
+            VerificationStatus::MissingKmers
+        } else {
+            VerificationStatus::Match
+        }
+    } else if !has_superfluous_kmers_unitigs && !has_superfluous_kmers_test_tigs {
+        if unique_kmer_count > test_tigs_kmer_count && !config.allow_cuttlefish2_errors {
+            VerificationStatus::MissingKmers
+        } else {
+            VerificationStatus::Match
+        }
+    } else if !has_superfluous_kmers_unitigs {
+        VerificationStatus::SuperfluousKmers
+    } else if !has_superfluous_kmers_test_tigs {
+        VerificationStatus::MissingKmers
+    } else {
+        VerificationStatus::MissingAndSuperfluousKmers
+    };
+
+    VerificationReport {
+        test_tigs: test_tigs_name,
+        strands: config
+            .both_strands_report
+            .then_some(if config.strand_specific {
+                Strands::Forward
+            } else {
+                Strands::Canonical
+            }),
+        ground_truth_size: unitigs_sequence_size,
+        test_size: test_tigs_sequence_size,
+        ground_truth_str_cnt: unitigs_string_count,
+        test_str_cnt: test_tigs_string_count,
+        compression_rate,
+        str_cnt_rate: string_count_rate,
+        ground_truth_gc: unitig_counts.gc_content(),
+        test_gc: test_tig_counts.gc_content(),
+        unique_kmer_count,
+        kmer_positions: unique_kmer_count,
+        distinct_canonical_kmers: set_statistics.map(|(unitigs, _)| unitigs.kmer_count),
+        superfluous_unitig_kmer_count: merged.then_some(superfluous_unitig_kmer_count),
+        excused_unitig_kmer_count: merged.then_some(excused_unitig_kmer_count),
+        superfluous_test_tig_kmer_count: merged.then_some(superfluous_test_tig_kmer_count),
+        jaccard: merged.then_some(jaccard),
+        unitig_palindrome_count: set_statistics.map(|(unitigs, _)| unitigs.palindrome_count),
+        test_tig_palindrome_count: set_statistics.map(|(_, test_tigs)| test_tigs.palindrome_count),
+        unitig_kmer_set_fingerprint: set_statistics.map(|(unitigs, _)| unitigs.fingerprint),
+        test_tig_kmer_set_fingerprint: set_statistics.map(|(_, test_tigs)| test_tigs.fingerprint),
+        distinct_k_plus_1: set_statistics.and_then(|(unitigs, _)| unitigs.k_plus_1_mer_count),
+        multiplicity_mismatch_count: config.multiset.then_some(multiplicity_mismatches.total()),
+        multiplicity_histogram,
+        status,
+    }
+}
+
+/// Read all kmers from the iterator without collecting them, and print the size statistics of the input.
+fn scan_input<KmerType: FromIterator<u8> + Kmer>(
+    mut kmer_iter: KmerIterator<impl Read, KmerType>,
+    input_name: String,
+    config: &Config,
+) {
+    phase_info!(config, "Scanning {input_name}");
+    assert!(kmer_iter.by_ref().all(|_| true));
+    warn_about_short_sequences(&kmer_iter, &input_name, config);
+
+    let report = ScanReport {
+        input: input_name,
+        character_count: kmer_iter.character_count(),
+        sequence_count: kmer_iter.sequence_count(),
+        kmer_count: kmer_iter.kmer_position_count(),
+    };
+
+    write_statistics(|output| report.write(config.format, output), config);
+}
+
+/// Read all inputs without collecting their kmers, and print the projected peak memory of verifying them.
+fn estimate_memory<KmerType: FromIterator<u8> + Kmer, TestTigs: Read + LengthHint>(
+    mut kmer_iter_unitigs: KmerIterator<impl Read, KmerType>,
+    test_tigs: impl IntoIterator<Item = (String, TestTigs)>,
+    config: &Config,
+) -> Result<(), Error> {
+    // Mirrors the capacity that `collect_unique_kmers` reserves before reading an input.
+    let reserved_kmer_count = |upper_bound: Option<usize>| {
+        if kmer_sampler(config).is_some() {
+            0
+        } else {
+            upper_bound
+                .unwrap_or(0)
+                .min(config.max_kmers.unwrap_or(usize::MAX))
+        }
+    };
+
+    phase_info!(config, "Scanning first input file");
+    let unitig_reserved_kmer_count = reserved_kmer_count(kmer_iter_unitigs.size_hint().1);
+    assert!(kmer_iter_unitigs.by_ref().all(|_| true));
+    warn_about_short_sequences(&kmer_iter_unitigs, "first input file", config);
+    let unitig_kmer_count = kmer_iter_unitigs.kmer_position_count();
+
+    let mut test_tig_kmer_counts = Vec::new();
+    for (test_tigs_name, test_tigs) in test_tigs {
+        phase_info!(config, "Scanning test tigs {test_tigs_name}");
+        let test_tigs_name = format!("test tigs {test_tigs_name}");
+        let mut kmer_iter_test_tigs =
+            kmer_iterator::<_, KmerType>(test_tigs, &test_tigs_name, config)?;
+        let test_tig_reserved_kmer_count = reserved_kmer_count(kmer_iter_test_tigs.size_hint().1);
+        assert!(kmer_iter_test_tigs.by_ref().all(|_| true));
+        warn_about_short_sequences(&kmer_iter_test_tigs, &test_tigs_name, config);
+        test_tig_kmer_counts.push((
+            kmer_iter_test_tigs.kmer_position_count(),
+            test_tig_reserved_kmer_count,
+        ));
+    }
+
+    // The unique kmers of the first input file stay in memory while each test tig file is collected.
+    let estimated_memory_bytes = |kmer_size| {
+        test_tig_kmer_counts
+            .iter()
+            .map(|&(kmer_count, reserved_kmer_count)| {
+                unitig_kmer_count * kmer_size
+                    + collection_memory(kmer_count, reserved_kmer_count, kmer_size, config)
+            })
+            .fold(
+                collection_memory(
+                    unitig_kmer_count,
+                    unitig_reserved_kmer_count,
+                    kmer_size,
+                    config,
+                ),
+                usize::max,
+            )
+    };
+    let report = MemoryEstimateReport {
+        unitig_kmer_count,
+        max_test_tig_kmer_count: test_tig_kmer_counts
+            .iter()
+            .map(|&(kmer_count, _)| kmer_count)
+            .max()
+            .unwrap_or(0),
+        estimated_memory_bytes: estimated_memory_bytes(KmerType::memory_size(config.kmer_length())),
+        // Mirrors the choice of the kmer type in `compare_inputs`.
+        vector_kmer_estimated_memory_bytes: (config.k <= 128 && config.gap.is_none())
+            .then(|| estimated_memory_bytes(BitPackedVectorKmer::memory_size(config.k))),
+    };
+
+    write_statistics(|output| report.write(config.format, output), config);
+    Ok(())
+}
+
+/// The projected peak memory in bytes of collecting the kmers of one input with the configured `--dedup-strategy`.
+///
+/// The `kmer_count` includes duplicates, and the `reserved_kmer_count` is the capacity that is reserved before reading.
+fn collection_memory(
+    kmer_count: usize,
+    reserved_kmer_count: usize,
+    kmer_size: usize,
+    config: &Config,
+) -> usize {
+    match config.dedup_strategy {
+        // The capacity is reserved from the number of bytes of the input, and the kmers are sorted in place.
+        DedupStrategy::Sort => kmer_count.max(reserved_kmer_count) * kmer_size,
+        // The hash table has a control byte per bucket and is at most 7/8 full,
+        // and it is only freed after the unique kmers are moved into a vector for sorting.
+        DedupStrategy::Hashset => {
+            let bucket_count = (kmer_count.saturating_mul(8) / 7).next_power_of_two();
+            bucket_count * (kmer_size + 1) + kmer_count * kmer_size
+        }
+        // The B-tree nodes are at least half full, and the set is moved into a vector after reading.
+        DedupStrategy::Btreeset => 3 * kmer_count * kmer_size,
+    }
+}
+
+/// Write the statistics to `--stats-output` if given, and to stdout otherwise.
+fn write_statistics(write: impl FnOnce(&mut dyn Write) -> std::io::Result<()>, config: &Config) {
+    if let Some(path) = &config.stats_output {
+        let mut output = BufWriter::new(
+            OpenOptions::new()
+                .append(true)
+                .open(path)
+                .unwrap_or_else(|_| panic!("--stats-output points to a writable file: {path:?}")),
+        );
+        write(&mut output).unwrap();
+        output.flush().unwrap();
+    } else if !config.terse {
+        // Flush both streams, such that the statistics are not interleaved with the log.
+        std::io::stdout().flush().unwrap();
+        std::io::stderr().flush().unwrap();
+        write(&mut std::io::stdout().lock()).unwrap();
+        std::io::stdout().flush().unwrap();
+        std::io::stderr().flush().unwrap();
+    }
+}
+
+/// Write the sorted unique kmers of the input to the output, one per line.
+fn list_kmers<KmerType: FromIterator<u8> + Ord + Hash + Clone + Display + Kmer + Send>(
+    mut kmer_iter: KmerIterator<impl Read, KmerType>,
+    mut output: impl Write,
+    config: &Config,
+) -> Result<(), Error> {
+    let kmers =
+        match collect_unique_kmers(&mut kmer_iter, "first input file", None, None, None, config) {
+            Ok(kmers) => kmers,
+            Err(CollectError::TooManyKmers) => return Err(Error::TooManyKmers),
+            Err(CollectError::InternalInvariant) => return Err(Error::InternalInvariant),
+            Err(CollectError::RejectedKmer(_)) => {
+                unreachable!("Kmers are only rejected by a prefilter")
+            }
+        };
+    warn_about_short_sequences(&kmer_iter, "first input file", config);
+
+    phase_info!(config, "Writing {} kmers", kmers.len());
+    let start = Instant::now();
+    for kmer in &kmers {
+        writeln!(output, "{kmer}").unwrap();
+    }
+    output.flush().unwrap();
+    log_duration("Writing kmers", start, config);
+    export_fasta(&kmers, config);
+
+    Ok(())
+}
+
+/// Write the sorted unique kmers as FASTA if `--export-fasta` is given.
+fn export_fasta<KmerType: Display + Kmer>(kmers: &[KmerType], config: &Config) {
+    let Some(path) = &config.export_fasta else {
+        return;
+    };
+
+    phase_info!(
+        config,
+        "Exporting {} kmers as FASTA to {path:?}",
+        kmers.len()
+    );
+    let start = Instant::now();
+    let output = BufWriter::new(
+        File::create(path)
+            .unwrap_or_else(|_| panic!("--export-fasta points to a writable file: {path:?}")),
+    );
+    write_kmer_fasta(output, kmers)
+        .unwrap_or_else(|error| panic!("Writing {path:?} failed: {error}"));
+    log_duration("Exporting kmers as FASTA", start, config);
+}
+
+/// Read all kmers from the iterator and return them canonicalised, sorted and without duplicates.
+///
+/// If `--strand-specific` is given, then the kmers are not canonicalised.
+/// If `--sample` is given, then only the sampled kmers are returned.
+/// If a multiplicity histogram is given, then it is filled with the number of distinct kmers per multiplicity.
+/// If a vector of multiplicities is given, then the multiplicity of each returned kmer is appended to it.
+/// These are only supported by the sort dedup strategy.
+/// If a prefilter is given, then reading stops at the first kmer that is not contained in it,
+/// and this kmer is returned as error.
+/// If more kmers than `--max-kmers` are collected, then reading stops as well.
+fn collect_unique_kmers<KmerType: FromIterator<u8> + Ord + Hash + Clone + Kmer + Send>(
+    kmer_iter: &mut KmerIterator<impl Read, KmerType>,
+    input_name: &str,
+    multiplicity_histogram: Option<&mut BTreeMap<usize, usize>>,
+    mut multiplicities: Option<&mut Vec<usize>>,
+    prefilter: Option<&GroundTruthIndex<KmerType>>,
+    config: &Config,
+) -> Result<Vec<KmerType>, CollectError<KmerType>> {
+    let normalise = |kmer: KmerType| {
+        if config.strand_specific {
+            kmer
+        } else {
+            kmer.into_canonical()
+        }
+    };
+
+    let mut sampler = kmer_sampler(config);
+    let is_sampled = sampler.is_some();
+    let mut discarded_kmer_amount = 0;
+    let mut rejected_kmer = None;
+    let normalised_kmers = kmer_iter
+        .by_ref()
+        .map(normalise)
+        .filter(|kmer| {
+            let keep = sampler.as_mut().is_none_or(|sampler| sampler.keeps(kmer));
+            discarded_kmer_amount += usize::from(!keep);
+            keep
+        })
+        .take_while(|kmer| {
+            if prefilter.is_some_and(|prefilter| !prefilter.contains(kmer)) {
+                rejected_kmer = Some(kmer.clone());
+                false
+            } else {
+                true
+            }
+        });
+
+    phase_info!(config, "Reading {input_name}");
+    let start = Instant::now();
+    let (kmers, input_kmer_amount) = match config.dedup_strategy {
+        DedupStrategy::Sort => {
+            // The upper bound is close to the number of kmers if the input consists mostly of sequences.
+            // It also counts headers, line breaks and the first k - 1 characters of each sequence,
+            // so the unused capacity is released after removing duplicates.
+            let mut kmers = Vec::with_capacity(if !is_sampled {
+                normalised_kmers
+                    .size_hint()
+                    .1
+                    .unwrap_or(0)
+                    .min(config.max_kmers.unwrap_or(usize::MAX))
+            } else {
+                0
+            });
+            for kmer in normalised_kmers {
+                if Some(kmers.len()) == config.max_kmers {
+                    return Err(too_many_kmers(input_name, config));
+                }
+                kmers.push(kmer);
+            }
+            let input_kmer_amount = kmers.len();
+            log_duration(&format!("Reading {input_name}"), start, config);
+
+            phase_info!(config, "Sorting kmers in {input_name}");
+            let start = Instant::now();
+            sort_kmers(&mut kmers, config);
+            log_duration(&format!("Sorting {input_name}"), start, config);
+
+            if multiplicity_histogram.is_some() || multiplicities.is_some() {
+                phase_info!(config, "Counting kmer multiplicities in {input_name}");
+                let mut multiplicity_histogram = multiplicity_histogram;
+                for run in kmers.chunk_by(|a, b| a == b) {
+                    if let Some(multiplicity_histogram) = multiplicity_histogram.as_mut() {
+                        *multiplicity_histogram.entry(run.len()).or_default() += 1;
+                    }
+                    if let Some(multiplicities) = multiplicities.as_mut() {
+                        multiplicities.push(run.len());
+                    }
+                }
+            }
+
+            phase_info!(config, "Removing duplicates from {input_name}");
+            let start = Instant::now();
+            let mut previous_kmer = None;
+            kmers.retain(|kmer| {
+                if let Some(previous_kmer) = previous_kmer.as_mut() {
+                    let result = kmer != previous_kmer;
+                    *previous_kmer = kmer.clone();
+                    result
+                } else {
+                    previous_kmer = Some(kmer.clone());
+                    true
+                }
+            });
+            kmers.shrink_to_fit();
+            log_duration(
+                &format!("Removing duplicates from {input_name}"),
+                start,
+                config,
+            );
+
+            (kmers, input_kmer_amount)
+        }
+        DedupStrategy::Hashset => {
+            // `--multiplicity-histogram` and `--multiset` are rejected with this strategy when checking the arguments.
+            debug_assert!(
+                multiplicity_histogram.is_none() && multiplicities.is_none(),
+                "the hashset dedup strategy does not support multiplicities"
+            );
+            let mut input_kmer_amount = 0;
+            let mut kmers = HashSet::new();
+            for kmer in normalised_kmers {
+                input_kmer_amount += 1;
+                kmers.insert(kmer);
+                if config
+                    .max_kmers
+                    .is_some_and(|max_kmers| kmers.len() > max_kmers)
+                {
+                    return Err(too_many_kmers(input_name, config));
+                }
+            }
+            log_duration(
+                &format!("Reading and deduplicating {input_name}"),
+                start,
+                config,
+            );
+
+            phase_info!(config, "Sorting unique kmers in {input_name}");
+            let start = Instant::now();
+            let mut kmers: Vec<_> = kmers.into_iter().collect();
+            sort_kmers(&mut kmers, config);
+            log_duration(&format!("Sorting {input_name}"), start, config);
+
+            (kmers, input_kmer_amount)
+        }
+        DedupStrategy::Btreeset => {
+            // `--multiplicity-histogram` and `--multiset` are rejected with this strategy when checking the arguments.
+            debug_assert!(
+                multiplicity_histogram.is_none() && multiplicities.is_none(),
+                "the btreeset dedup strategy does not support multiplicities"
+            );
+            let mut input_kmer_amount = 0;
+            let mut kmers = BTreeSet::new();
+            for kmer in normalised_kmers {
+                input_kmer_amount += 1;
+                kmers.insert(kmer);
+                if config
+                    .max_kmers
+                    .is_some_and(|max_kmers| kmers.len() > max_kmers)
+                {
+                    return Err(too_many_kmers(input_name, config));
+                }
+            }
+            // The kmers are already sorted.
+            let kmers: Vec<_> = kmers.into_iter().collect();
+            log_duration(
+                &format!("Reading and deduplicating {input_name}"),
+                start,
+                config,
+            );
+
+            (kmers, input_kmer_amount)
+        }
+    };
+
+    if let Some(rejected_kmer) = rejected_kmer {
+        return Err(CollectError::RejectedKmer(rejected_kmer));
+    }
+
+    let duplicate_kmer_amount = input_kmer_amount - kmers.len();
+    if input_kmer_amount == 0 {
+        debug!("Duplicate kmers: 0/0 (n/a)");
+    } else {
+        debug!(
+            "Duplicate kmers: {duplicate_kmer_amount}/{input_kmer_amount} ({:.0}%)",
+            100.0 * duplicate_kmer_amount as f64 / input_kmer_amount as f64
+        );
+    }
+
+    let collected_kmer_amount = kmers.len() + duplicate_kmer_amount + discarded_kmer_amount;
+    let expected_kmer_amount = kmer_iter.kmer_position_count() + kmer_iter.expanded_kmer_count();
+    if collected_kmer_amount != expected_kmer_amount {
+        return Err(internal_invariant(
+            format!(
+                "{input_name}: collected {collected_kmer_amount} kmers, but expected {expected_kmer_amount}; \
+                 character_count: {}; sequence_count: {}; short_sequence_count: {}; k: {}",
+                kmer_iter.character_count(),
+                kmer_iter.sequence_count(),
+                kmer_iter.short_sequence_count(),
+                config.k
+            ),
+            config,
+        ));
+    }
+
+    Ok(kmers)
+}
+
+/// The reasons for which [`collect_unique_kmers`] stops reading early.
+enum CollectError<KmerType> {
+    /// The kmer is not contained in the prefilter.
+    RejectedKmer(KmerType),
+    /// More kmers than `--max-kmers` were collected.
+    TooManyKmers,
+    /// An internal consistency check failed.
+    InternalInvariant,
+}
+
+fn too_many_kmers<KmerType>(input_name: &str, config: &Config) -> CollectError<KmerType> {
+    error!(
+        "{input_name} contains more than --max-kmers = {} kmers",
+        config.max_kmers.unwrap()
+    );
+    CollectError::TooManyKmers
+}
+
+/// Report a failed internal consistency check.
+///
+/// Panics with the given message if `--debug-checks` is given, and logs it as error otherwise.
+fn internal_invariant<KmerType>(message: String, config: &Config) -> CollectError<KmerType> {
+    if config.debug_checks {
+        panic!("Internal invariant violated: {message}");
+    }
+    error!("Internal invariant violated: {message}");
+    CollectError::InternalInvariant
+}
+
+/// The sampler that chooses the kmers for `--sample`, or `None` if all kmers are verified.
+fn kmer_sampler(config: &Config) -> Option<KmerSampler> {
+    (config.sample < 1.0).then(|| KmerSampler::new(config.sample, config.seed))
+}
+
+/// Log the wall-clock duration of a phase that started at `start`.
+fn log_duration(phase: &str, start: Instant, config: &Config) {
+    phase_info!(config, "{phase} took {:.1}s", start.elapsed().as_secs_f64());
+}
+
+/// Warn if the input contains sequences that are too short to contain a kmer.
+///
+/// This may indicate that the wrong k was chosen.
+/// Since this is called once for each input after reading it, it also writes the `--per-sequence-stats` of the input.
+fn warn_about_short_sequences<KmerType>(
+    kmer_iter: &KmerIterator<impl Read, KmerType>,
+    input_name: &str,
+    config: &Config,
+) {
+    write_per_sequence_stats(kmer_iter, input_name, config);
+
+    let short_sequence_count = kmer_iter.short_sequence_count();
+    if short_sequence_count != 0 {
+        warn!(
+            "{input_name}: {short_sequence_count}/{} sequences are shorter than {} and contain no kmers",
+            kmer_iter.sequence_count(),
+            match config.gap {
+                Some(gap) => format!("2k + gap = {}", 2 * config.k + gap),
+                None => format!("k = {}", config.k),
+            }
+        );
+    }
+
+    let skipped_sequence_count = kmer_iter.skipped_sequence_count();
+    if skipped_sequence_count != 0 {
+        info!(
+            "{input_name}: skipped {skipped_sequence_count} sequences shorter than --min-sequence-length = {}",
+            config.min_sequence_length
+        );
+    }
+
+    let excluded_sequence_count = kmer_iter.excluded_sequence_count();
+    if excluded_sequence_count != 0 {
+        info!("{input_name}: skipped {excluded_sequence_count} sequences with IDs matching --exclude-id");
+    }
+
+    let duplicate_id_count = kmer_iter.duplicate_id_count();
+    if duplicate_id_count != 0 {
+        warn!("{input_name}: {duplicate_id_count} sequences have an ID that occurred before");
+    }
+
+    let missing_sequence_segment_count = kmer_iter.missing_sequence_segment_count();
+    if missing_sequence_segment_count != 0 {
+        warn!("{input_name}: skipped {missing_sequence_segment_count} GFA segments without sequence (`*`)");
+    }
+
+    let low_count_kmer_count = kmer_iter.low_count_kmer_count();
+    if low_count_kmer_count != 0 {
+        info!(
+            "{input_name}: skipped {low_count_kmer_count} kmers with a count below --min-count = {}",
+            config.min_count
+        );
+    }
+}
+
+/// Append the length and the number of kmer positions of each sequence of the input to the `--per-sequence-stats` file.
+fn write_per_sequence_stats<KmerType>(
+    kmer_iter: &KmerIterator<impl Read, KmerType>,
+    input_name: &str,
+    config: &Config,
+) {
+    let Some(path) = &config.per_sequence_stats else {
+        return;
+    };
+
+    let mut output = BufWriter::new(
+        OpenOptions::new()
+            .append(true)
+            .open(path)
+            .unwrap_or_else(|_| panic!("--per-sequence-stats points to a writable file: {path:?}")),
+    );
+    writeln!(output, "# {input_name}").unwrap();
+    for (index, &length) in kmer_iter.sequence_lengths().iter().enumerate() {
+        let kmer_count = (length + 1).saturating_sub(kmer_iter.window_length());
+        writeln!(output, "{index}\t{length}\t{kmer_count}").unwrap();
+    }
+    output.flush().unwrap();
+}
+
+/// Note if the unitigs and the test tigs have different formats,
+/// since format-specific parsing may then cause mismatches.
+fn note_format_difference<KmerType>(
+    unitig_format: Format,
+    kmer_iter_test_tigs: &KmerIterator<impl Read, KmerType>,
+    test_tigs_name: &str,
+) {
+    let test_tig_format = kmer_iter_test_tigs.format();
+    if unitig_format != Format::None
+        && test_tig_format != Format::None
+        && unitig_format != test_tig_format
+    {
+        info!("The unitigs are {unitig_format}, but the test tigs {test_tigs_name} are {test_tig_format}");
+    }
+}
+
+/// Fail if the input contains no sequences, unless `--allow-empty-input` is given.
+///
+/// An empty input would otherwise be compared like any other, which may result in a confusing success.
+fn check_not_empty(counts: InputCounts, input_name: &str, config: &Config) -> Result<(), Error> {
+    if counts.sequence_count != 0 {
+        Ok(())
+    } else if config.allow_empty_input {
+        warn!("{input_name} contains no sequences");
+        Ok(())
+    } else {
+        error!("{input_name} contains no sequences");
+        Err(Error::EmptyInput)
+    }
+}
+
+#[cfg_attr(not(feature = "rayon"), allow(unused_variables))]
+fn sort_kmers<KmerType: Ord + Send>(kmers: &mut [KmerType], config: &Config) {
+    #[cfg(feature = "rayon")]
+    if config.threads != 1 {
+        use rayon::slice::ParallelSliceMut;
+        kmers.par_sort_unstable();
+        return;
+    }
+
+    kmers.sort_unstable();
+}
+
+/// Count the kmers that are their own reverse complement, which is only possible for even k.
+fn count_palindromes<KmerType: Kmer>(kmers: &[KmerType], k: usize) -> usize {
+    if k % 2 == 1 {
+        return 0;
+    }
+
+    kmers
+        .iter()
+        .filter(|&kmer| *kmer == kmer.reverse_complement())
+        .count()
+}
+
+/// Count the distinct (k+1)-mers whose prefix and suffix kmers are both contained in the sorted `kmers`.
+///
+/// If `strand_specific` is false, then `kmers` must be canonical, and a (k+1)-mer and its reverse complement count as one.
+fn count_k_plus_1_mers<KmerType: Ord + Clone + Kmer>(
+    kmers: &[KmerType],
+    strand_specific: bool,
+) -> usize {
+    debug_assert!(kmers.is_sorted());
+    let contains = |kmer: &KmerType| kmers.binary_search(kmer).is_ok();
+
+    if strand_specific {
+        return kmers
+            .iter()
+            .map(|kmer| {
+                kmer.successors()
+                    .iter()
+                    .filter(|&successor| contains(successor))
+                    .count()
+            })
+            .sum();
+    }
+
+    // Each (k+1)-mer is found once when extending its prefix kmer by its last character,
+    // and once when extending the reverse complement of its suffix kmer by the complement of its first character.
+    // These are the same extension only if the (k+1)-mer is its own reverse complement.
+    let mut extension_count = 0;
+    let mut palindrome_count = 0;
+    for kmer in kmers {
+        let reverse_complement = kmer.reverse_complement();
+        // Each orientation is paired with its reverse complement, and a palindromic kmer has only one orientation.
+        let orientations = [(kmer, &reverse_complement), (&reverse_complement, kmer)];
+        let orientation_count = if reverse_complement == *kmer { 1 } else { 2 };
+
+        for &(orientation, orientation_reverse_complement) in &orientations[..orientation_count] {
+            for successor in orientation.successors() {
+                if contains(&successor.canonical()) {
+                    extension_count += 1;
+                    if successor == *orientation_reverse_complement {
+                        palindrome_count += 1;
+                    }
+                }
+            }
+        }
+    }
+
+    (extension_count + palindrome_count) / 2
+}
+
+/// Parse the command line, run the comparison and return its exit code.
+pub fn cli_main() -> ExitCode {
+    match run() {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(error) => {
+            eprintln!("Error: {error:?}");
+            error.exit_code()
+        }
+    }
+}
+
+/// Set the kmer size from the header of the unitigs, and return the unitigs with the header still included.
+fn read_k_from_header(unitigs_file: Input, config: &mut Config) -> Result<Input, Error> {
+    if config.ground_truth_dump.is_some() {
+        error!("--k-from-header cannot be combined with --ground-truth-dump");
+        return Err(Error::IncompatibleArguments);
+    }
+
+    let length_hint = unitigs_file.length_hint();
+    let mut unitigs_file = BufReader::new(unitigs_file);
+    let mut first_line = Vec::new();
+    unitigs_file
+        .read_until(b'\n', &mut first_line)
+        .unwrap_or_else(|error| panic!("Reading {:?} failed: {error}", config.unitigs));
+
+    match (k_from_header(&first_line), config.k) {
+        (None, 0) => {
+            error!("The header of the unitigs does not specify k, and -k was not given");
+            return Err(Error::IllegalKmerSize { kmer_size: 0 });
+        }
+        (None, k) => {
+            warn!("The header of the unitigs does not specify k, using -k {k}");
+        }
+        (Some(header_k), 0) => {
+            info!("Using k = {header_k} from the header of the unitigs");
+            config.k = header_k;
+        }
+        (Some(header_k), k) if header_k != k => {
+            error!("The header of the unitigs specifies k = {header_k}, but -k {k} was given");
+            return Err(Error::IncompatibleArguments);
+        }
+        (Some(_), _) => {}
+    }
+
+    Ok(Input::new(
+        Box::new(std::io::Cursor::new(first_line).chain(unitigs_file)),
+        length_hint,
+    ))
+}
+
+fn run() -> Result<(), Error> {
+    let config = Config::parse();
+    if config.terse {
+        initialise_logging_with_mode(config.log_level, TerminalMode::Stderr);
+    } else {
+        initialise_logging(config.log_level);
+    }
+    phase_info!(config, "Logging initialised successfully");
+    debug!("{config:?}");
+
+    if config.terse && (config.scan_only || config.estimate_memory || config.list_kmers) {
+        error!("--terse cannot be combined with --scan-only, --estimate-memory or --list-kmers");
+        return Err(Error::IncompatibleArguments);
+    }
+
+    #[cfg(feature = "rayon")]
+    rayon::ThreadPoolBuilder::new()
+        .num_threads(config.threads)
+        .build_global()
+        .unwrap();
+
+    let input_opener = InputOpener::new(&config);
+    let terse = config.terse;
+    let result = compare_configured_inputs(config, &input_opener);
+    if terse {
+        print_terse_result(&result);
+    }
+    result
+}
+
+/// Print `MATCH` or `MISMATCH` as the last line of stdout, or nothing if the comparison failed for another reason.
+fn print_terse_result(result: &Result<(), Error>) {
+    let token = match result {
+        Ok(()) => "MATCH",
+        Err(Error::MissingKmers | Error::SuperfluousKmers | Error::MissingAndSuperfluousKmers) => {
+            "MISMATCH"
+        }
+        Err(_) => return,
+    };
+
+    log::logger().flush();
+    std::io::stderr().flush().unwrap();
+    let mut stdout = std::io::stdout().lock();
+    writeln!(stdout, "{token}").unwrap();
+    stdout.flush().unwrap();
+}
+
+/// Compare the inputs as configured, either once, or for each kmer size of `--k-range`,
+/// or for both strands with `--both-strands-report`.
+fn compare_configured_inputs(config: Config, input_opener: &InputOpener) -> Result<(), Error> {
+    if let Some(k_range) = config.k_range.clone() {
+        return compare_k_range(config, k_range, input_opener);
+    }
+    if !config.both_strands_report {
+        return compare_inputs(config, input_opener);
+    }
+
+    if config.strand_specific
+        || config.scan_only
+        || config.estimate_memory
+        || config.list_kmers
+        || config.mismatch_output.is_some()
+        || config.stats_output.is_some()
+        || config.per_sequence_stats.is_some()
+        || config.dump_kmers.is_some()
+        || config.ground_truth_dump.is_some()
+        || config.export_fasta.is_some()
+    {
+        error!("--both-strands-report cannot be combined with --strand-specific, --scan-only, --estimate-memory, --list-kmers, --mismatch-output, --stats-output, --per-sequence-stats, --dump-kmers, --ground-truth-dump or --export-fasta");
+        return Err(Error::IncompatibleArguments);
+    }
+
+    phase_info!(config, "Comparing canonical kmers");
+    let canonical_result = compare_inputs(config.clone(), input_opener);
+    phase_info!(config, "Comparing forward kmers");
+    let forward_result = compare_inputs(
+        Config {
+            strand_specific: true,
+            ..config
+        },
+        input_opener,
+    );
+
+    match forward_result {
+        Ok(())
+        | Err(Error::MissingKmers)
+        | Err(Error::SuperfluousKmers)
+        | Err(Error::MissingAndSuperfluousKmers) => canonical_result,
+        Err(error) => canonical_result.and(Err(error)),
+    }
+}
+
+/// Parse a kmer size range of the form `MIN..MAX`, where both bounds are inclusive.
+fn parse_k_range(range: &str) -> Result<RangeInclusive<usize>, String> {
+    let (min, max) = range
+        .split_once("..")
+        .ok_or_else(|| format!("expected MIN..MAX, found {range:?}"))?;
+    let max = max.strip_prefix('=').unwrap_or(max);
+    let min: usize = min
+        .parse()
+        .map_err(|error| format!("invalid MIN: {error}"))?;
+    let max: usize = max
+        .parse()
+        .map_err(|error| format!("invalid MAX: {error}"))?;
+
+    if min == 0 {
+        return Err("MIN must be positive".to_string());
+    }
+    if min > max {
+        return Err(format!("MIN {min} is larger than MAX {max}"));
+    }
+    Ok(min..=max)
+}
+
+/// Compare the inputs once for each kmer size in the range, and log whether they match for each kmer size.
+fn compare_k_range(
+    config: Config,
+    k_range: RangeInclusive<usize>,
+    input_opener: &InputOpener,
+) -> Result<(), Error> {
+    if config.k != 0
+        || config.k_from_header
+        || config.both_strands_report
+        || config.scan_only
+        || config.estimate_memory
+        || config.list_kmers
+        || config.mismatch_output.is_some()
+        || config.stats_output.is_some()
+        || config.per_sequence_stats.is_some()
+        || config.dump_kmers.is_some()
+        || config.ground_truth_dump.is_some()
+        || config.export_fasta.is_some()
+        || config.two_bit_input
+    {
+        error!("--k-range cannot be combined with -k, --k-from-header, --both-strands-report, --scan-only, --estimate-memory, --list-kmers, --mismatch-output, --stats-output, --per-sequence-stats, --dump-kmers, --ground-truth-dump, --export-fasta or --two-bit-input");
+        return Err(Error::IncompatibleArguments);
+    }
+
+    let mut results = Vec::new();
+    for k in k_range {
+        phase_info!(config, "Comparing kmers of size {k}");
+        let result = compare_inputs(
+            Config {
+                k,
+                k_range: None,
+                ..config.clone()
+            },
+            input_opener,
+        );
+        results.push((k, result));
+    }
+
+    let table: Vec<_> = results
+        .iter()
+        .map(|(k, result)| {
+            let outcome = match result {
+                Ok(()) => "ok",
+                Err(Error::MissingKmers)
+                | Err(Error::SuperfluousKmers)
+                | Err(Error::MissingAndSuperfluousKmers) => "mismatch",
+                Err(_) => "error",
+            };
+            format!("k={k}: {outcome}")
+        })
+        .collect();
+    info!("{}", table.join(", "));
+
+    match results.into_iter().find(|(_, result)| result.is_err()) {
+        Some((k, result)) => {
+            info!("The comparison fails first at k = {k}");
+            result
+        }
+        None => Ok(()),
+    }
+}
+
+/// Open the inputs given in the config and compare them with the kmer type that fits the kmer size.
+fn compare_inputs(mut config: Config, input_opener: &InputOpener) -> Result<(), Error> {
+    if config.two_bit_input
+        && (config.k_from_header
+            || config.gap.is_some()
+            || config.count_k_plus_1
+            || config.gfa_spell_paths
+            || config.input_format != InputFormat::Auto)
+    {
+        error!("--two-bit-input cannot be combined with --k-from-header, --gap, --count-k-plus-1, --gfa-spell-paths or --input-format");
+        return Err(Error::IncompatibleArguments);
+    }
+    // Superstrings and (k+1)-mers are only searched for by extending with `A`, `C`, `G` and `T`.
+    if config.alphabet == Alphabet::Acgtn
+        && (config.ambiguous_characters == AmbiguousCharacterPolicy::Expand
+            || config.gap.is_some()
+            || config.two_bit_input
+            || config.allow_cuttlefish2_errors
+            || config.count_k_plus_1)
+    {
+        error!("--alphabet acgtn cannot be combined with --ambiguous-characters expand, --gap, --two-bit-input, --allow-cuttlefish2-errors or --count-k-plus-1");
+        return Err(Error::IncompatibleArguments);
+    }
+    if config.report_gc_content && (config.two_bit_input || config.ground_truth_dump.is_some()) {
+        error!(
+            "--report-gc-content cannot be combined with --two-bit-input or --ground-truth-dump"
+        );
+        return Err(Error::IncompatibleArguments);
+    }
+
+    let unitigs_file = if config.ground_truth_dump.is_some() {
+        if !config.unitigs_shard.is_empty() {
+            error!("--unitigs-shard cannot be combined with --ground-truth-dump");
+            return Err(Error::IncompatibleArguments);
+        }
+        Input::new(Box::new(std::io::empty()), Some(0))
+    } else if config.unitigs_shard.is_empty() {
+        input_opener.open(&config.unitigs, "--unitigs")
+    } else {
+        Input::chain(
+            std::iter::once(input_opener.open(&config.unitigs, "--unitigs")).chain(
+                config
+                    .unitigs_shard
+                    .iter()
+                    .map(|path| input_opener.open(path, "--unitigs-shard")),
+            ),
+        )
+    };
+    let unitigs_file = if config.k_from_header {
+        read_k_from_header(unitigs_file, &mut config)?
+    } else {
+        unitigs_file
+    };
+    let test_tigs_list = config
+        .test_tigs_list
+        .as_ref()
+        .map(|path| {
+            let file = File::open(path)
+                .unwrap_or_else(|_| panic!("--test-tigs-list points to a file: {path:?}"));
+            read_test_tigs_list(BufReader::new(file))
+                .unwrap_or_else(|error| panic!("Reading {path:?} failed: {error}"))
+        })
+        .unwrap_or_default();
+    let test_tigs_files: Vec<_> = config
+        .test_tigs
+        .iter()
+        .chain(&test_tigs_list)
+        .map(|path| {
+            (
+                path.display().to_string(),
+                input_opener.open(path, "--test-tigs"),
+            )
+        })
+        .collect();
+    if test_tigs_files.is_empty() && !config.list_kmers {
+        warn!("No test tigs given, the --test-tigs-list is empty");
+    }
+
+    // This is not the most clever way to handle different kmer sizes in the type system, but it gets the job done.
+    // It results in larger binary sizes, but therefore we can have e.g. a Display implementation for Kmer.
+    match config.k {
+        0 => {
+            error!("Kmer size cannot be zero, give a positive -k or use --k-from-header");
+            Err(Error::IllegalKmerSize {
+                kmer_size: config.k,
+            })
+        }
+        // Pairs of kmers are rare enough that one kmer type for all kmer sizes suffices.
+        _ if config.gap.is_some() => compare_kmer_sets::<PairedKmer<BitPackedVectorKmer>, _>(
+            unitigs_file,
+            test_tigs_files,
+            config,
+        ),
+        // Only the three-bit kmer type can store `N`, and it supports all kmer sizes.
+        _ if config.alphabet == Alphabet::Acgtn => {
+            compare_kmer_sets::<ThreeBitKmer, _>(unitigs_file, test_tigs_files, config)
+        }
+        1 => compare_kmer_sets::<BitPackedKmer<1, u8>, _>(unitigs_file, test_tigs_files, config),
+        2 => compare_kmer_sets::<BitPackedKmer<2, u8>, _>(unitigs_file, test_tigs_files, config),
+        3 => compare_kmer_sets::<BitPackedKmer<3, u8>, _>(unitigs_file, test_tigs_files, config),
+        4 => compare_kmer_sets::<BitPackedKmer<4, u8>, _>(unitigs_file, test_tigs_files, config),
+        5 => compare_kmer_sets::<BitPackedKmer<5, u16>, _>(unitigs_file, test_tigs_files, config),
+        6 => compare_kmer_sets::<BitPackedKmer<6, u16>, _>(unitigs_file, test_tigs_files, config),
+        7 => compare_kmer_sets::<BitPackedKmer<7, u16>, _>(unitigs_file, test_tigs_files, config),
+        8 => compare_kmer_sets::<BitPackedKmer<8, u16>, _>(unitigs_file, test_tigs_files, config),
+        9 => compare_kmer_sets::<BitPackedKmer<9, u32>, _>(unitigs_file, test_tigs_files, config),
+        10 => compare_kmer_sets::<BitPackedKmer<10, u32>, _>(unitigs_file, test_tigs_files, config),
+        11 => compare_kmer_sets::<BitPackedKmer<11, u32>, _>(unitigs_file, test_tigs_files, config),
+        12 => compare_kmer_sets::<BitPackedKmer<12, u32>, _>(unitigs_file, test_tigs_files, config),
+        13 => compare_kmer_sets::<BitPackedKmer<13, u32>, _>(unitigs_file, test_tigs_files, config),
+        14 => compare_kmer_sets::<BitPackedKmer<14, u32>, _>(unitigs_file, test_tigs_files, config),
+        15 => compare_kmer_sets::<BitPackedKmer<15, u32>, _>(unitigs_file, test_tigs_files, config),
+        16 => compare_kmer_sets::<BitPackedKmer<16, u32>, _>(unitigs_file, test_tigs_files, config),
+        17 => compare_kmer_sets::<BitPackedKmer<17, u64>, _>(unitigs_file, test_tigs_files, config),
+        18 => compare_kmer_sets::<BitPackedKmer<18, u64>, _>(unitigs_file, test_tigs_files, config),
+        19 => compare_kmer_sets::<BitPackedKmer<19, u64>, _>(unitigs_file, test_tigs_files, config),
+        20 => compare_kmer_sets::<BitPackedKmer<20, u64>, _>(unitigs_file, test_tigs_files, config),
+        21 => compare_kmer_sets::<BitPackedKmer<21, u64>, _>(unitigs_file, test_tigs_files, config),
+        22 => compare_kmer_sets::<BitPackedKmer<22, u64>, _>(unitigs_file, test_tigs_files, config),
+        23 => compare_kmer_sets::<BitPackedKmer<23, u64>, _>(unitigs_file, test_tigs_files, config),
+        24 => compare_kmer_sets::<BitPackedKmer<24, u64>, _>(unitigs_file, test_tigs_files, config),
+        25 => compare_kmer_sets::<BitPackedKmer<25, u64>, _>(unitigs_file, test_tigs_files, config),
+        26 => compare_kmer_sets::<BitPackedKmer<26, u64>, _>(unitigs_file, test_tigs_files, config),
+        27 => compare_kmer_sets::<BitPackedKmer<27, u64>, _>(unitigs_file, test_tigs_files, config),
+        28 => compare_kmer_sets::<BitPackedKmer<28, u64>, _>(unitigs_file, test_tigs_files, config),
+        29 => compare_kmer_sets::<BitPackedKmer<29, u64>, _>(unitigs_file, test_tigs_files, config),
+        30 => compare_kmer_sets::<BitPackedKmer<30, u64>, _>(unitigs_file, test_tigs_files, config),
+        31 => compare_kmer_sets::<BitPackedKmer<31, u64>, _>(unitigs_file, test_tigs_files, config),
+        32 => compare_kmer_sets::<BitPackedKmer<32, u64>, _>(unitigs_file, test_tigs_files, config),
+        33 => {
+            compare_kmer_sets::<BitPackedKmer<33, u128>, _>(unitigs_file, test_tigs_files, config)
+        }
+        34 => {
+            compare_kmer_sets::<BitPackedKmer<34, u128>, _>(unitigs_file, test_tigs_files, config)
+        }
+        35 => {
+            compare_kmer_sets::<BitPackedKmer<35, u128>, _>(unitigs_file, test_tigs_files, config)
+        }
+        36 => {
+            compare_kmer_sets::<BitPackedKmer<36, u128>, _>(unitigs_file, test_tigs_files, config)
+        }
+        37 => {
+            compare_kmer_sets::<BitPackedKmer<37, u128>, _>(unitigs_file, test_tigs_files, config)
+        }
+        38 => {
+            compare_kmer_sets::<BitPackedKmer<38, u128>, _>(unitigs_file, test_tigs_files, config)
+        }
+        39 => {
+            compare_kmer_sets::<BitPackedKmer<39, u128>, _>(unitigs_file, test_tigs_files, config)
+        }
+        40 => {
+            compare_kmer_sets::<BitPackedKmer<40, u128>, _>(unitigs_file, test_tigs_files, config)
+        }
+        41 => {
+            compare_kmer_sets::<BitPackedKmer<41, u128>, _>(unitigs_file, test_tigs_files, config)
+        }
+        42 => {
+            compare_kmer_sets::<BitPackedKmer<42, u128>, _>(unitigs_file, test_tigs_files, config)
+        }
+        43 => {
+            compare_kmer_sets::<BitPackedKmer<43, u128>, _>(unitigs_file, test_tigs_files, config)
+        }
+        44 => {
+            compare_kmer_sets::<BitPackedKmer<44, u128>, _>(unitigs_file, test_tigs_files, config)
+        }
+        45 => {
+            compare_kmer_sets::<BitPackedKmer<45, u128>, _>(unitigs_file, test_tigs_files, config)
+        }
+        46 => {
+            compare_kmer_sets::<BitPackedKmer<46, u128>, _>(unitigs_file, test_tigs_files, config)
+        }
+        47 => {
+            compare_kmer_sets::<BitPackedKmer<47, u128>, _>(unitigs_file, test_tigs_files, config)
+        }
+        48 => {
+            compare_kmer_sets::<BitPackedKmer<48, u128>, _>(unitigs_file, test_tigs_files, config)
+        }
+        49 => {
+            compare_kmer_sets::<BitPackedKmer<49, u128>, _>(unitigs_file, test_tigs_files, config)
+        }
+        50 => {
+            compare_kmer_sets::<BitPackedKmer<50, u128>, _>(unitigs_file, test_tigs_files, config)
+        }
+        51 => {
+            compare_kmer_sets::<BitPackedKmer<51, u128>, _>(unitigs_file, test_tigs_files, config)
+        }
+        52 => {
+            compare_kmer_sets::<BitPackedKmer<52, u128>, _>(unitigs_file, test_tigs_files, config)
+        }
+        53 => {
+            compare_kmer_sets::<BitPackedKmer<53, u128>, _>(unitigs_file, test_tigs_files, config)
+        }
+        54 => {
+            compare_kmer_sets::<BitPackedKmer<54, u128>, _>(unitigs_file, test_tigs_files, config)
+        }
+        55 => {
+            compare_kmer_sets::<BitPackedKmer<55, u128>, _>(unitigs_file, test_tigs_files, config)
+        }
+        56 => {
+            compare_kmer_sets::<BitPackedKmer<56, u128>, _>(unitigs_file, test_tigs_files, config)
+        }
+        57 => {
+            compare_kmer_sets::<BitPackedKmer<57, u128>, _>(unitigs_file, test_tigs_files, config)
+        }
+        58 => {
+            compare_kmer_sets::<BitPackedKmer<58, u128>, _>(unitigs_file, test_tigs_files, config)
+        }
+        59 => {
+            compare_kmer_sets::<BitPackedKmer<59, u128>, _>(unitigs_file, test_tigs_files, config)
+        }
+        60 => {
+            compare_kmer_sets::<BitPackedKmer<60, u128>, _>(unitigs_file, test_tigs_files, config)
+        }
+        61 => {
+            compare_kmer_sets::<BitPackedKmer<61, u128>, _>(unitigs_file, test_tigs_files, config)
+        }
+        62 => {
+            compare_kmer_sets::<BitPackedKmer<62, u128>, _>(unitigs_file, test_tigs_files, config)
+        }
+        63 => {
+            compare_kmer_sets::<BitPackedKmer<63, u128>, _>(unitigs_file, test_tigs_files, config)
+        }
+        64 => {
+            compare_kmer_sets::<BitPackedKmer<64, u128>, _>(unitigs_file, test_tigs_files, config)
+        }
+        65 => {
+            compare_kmer_sets::<BitPackedArrayKmer<65, 3>, _>(unitigs_file, test_tigs_files, config)
+        }
+        66 => {
+            compare_kmer_sets::<BitPackedArrayKmer<66, 3>, _>(unitigs_file, test_tigs_files, config)
+        }
+        67 => {
+            compare_kmer_sets::<BitPackedArrayKmer<67, 3>, _>(unitigs_file, test_tigs_files, config)
+        }
+        68 => {
+            compare_kmer_sets::<BitPackedArrayKmer<68, 3>, _>(unitigs_file, test_tigs_files, config)
+        }
+        69 => {
+            compare_kmer_sets::<BitPackedArrayKmer<69, 3>, _>(unitigs_file, test_tigs_files, config)
+        }
+        70 => {
+            compare_kmer_sets::<BitPackedArrayKmer<70, 3>, _>(unitigs_file, test_tigs_files, config)
+        }
+        71 => {
+            compare_kmer_sets::<BitPackedArrayKmer<71, 3>, _>(unitigs_file, test_tigs_files, config)
+        }
+        72 => {
+            compare_kmer_sets::<BitPackedArrayKmer<72, 3>, _>(unitigs_file, test_tigs_files, config)
+        }
+        73 => {
+            compare_kmer_sets::<BitPackedArrayKmer<73, 3>, _>(unitigs_file, test_tigs_files, config)
+        }
+        74 => {
+            compare_kmer_sets::<BitPackedArrayKmer<74, 3>, _>(unitigs_file, test_tigs_files, config)
+        }
+        75 => {
+            compare_kmer_sets::<BitPackedArrayKmer<75, 3>, _>(unitigs_file, test_tigs_files, config)
+        }
+        76 => {
+            compare_kmer_sets::<BitPackedArrayKmer<76, 3>, _>(unitigs_file, test_tigs_files, config)
+        }
+        77 => {
+            compare_kmer_sets::<BitPackedArrayKmer<77, 3>, _>(unitigs_file, test_tigs_files, config)
+        }
+        78 => {
+            compare_kmer_sets::<BitPackedArrayKmer<78, 3>, _>(unitigs_file, test_tigs_files, config)
+        }
+        79 => {
+            compare_kmer_sets::<BitPackedArrayKmer<79, 3>, _>(unitigs_file, test_tigs_files, config)
+        }
+        80 => {
+            compare_kmer_sets::<BitPackedArrayKmer<80, 3>, _>(unitigs_file, test_tigs_files, config)
+        }
+        81 => {
+            compare_kmer_sets::<BitPackedArrayKmer<81, 3>, _>(unitigs_file, test_tigs_files, config)
+        }
+        82 => {
+            compare_kmer_sets::<BitPackedArrayKmer<82, 3>, _>(unitigs_file, test_tigs_files, config)
+        }
+        83 => {
+            compare_kmer_sets::<BitPackedArrayKmer<83, 3>, _>(unitigs_file, test_tigs_files, config)
+        }
+        84 => {
+            compare_kmer_sets::<BitPackedArrayKmer<84, 3>, _>(unitigs_file, test_tigs_files, config)
+        }
+        85 => {
+            compare_kmer_sets::<BitPackedArrayKmer<85, 3>, _>(unitigs_file, test_tigs_files, config)
+        }
+        86 => {
+            compare_kmer_sets::<BitPackedArrayKmer<86, 3>, _>(unitigs_file, test_tigs_files, config)
+        }
+        87 => {
+            compare_kmer_sets::<BitPackedArrayKmer<87, 3>, _>(unitigs_file, test_tigs_files, config)
+        }
+        88 => {
+            compare_kmer_sets::<BitPackedArrayKmer<88, 3>, _>(unitigs_file, test_tigs_files, config)
+        }
+        89 => {
+            compare_kmer_sets::<BitPackedArrayKmer<89, 3>, _>(unitigs_file, test_tigs_files, config)
+        }
+        90 => {
+            compare_kmer_sets::<BitPackedArrayKmer<90, 3>, _>(unitigs_file, test_tigs_files, config)
+        }
+        91 => {
+            compare_kmer_sets::<BitPackedArrayKmer<91, 3>, _>(unitigs_file, test_tigs_files, config)
+        }
+        92 => {
+            compare_kmer_sets::<BitPackedArrayKmer<92, 3>, _>(unitigs_file, test_tigs_files, config)
+        }
+        93 => {
+            compare_kmer_sets::<BitPackedArrayKmer<93, 3>, _>(unitigs_file, test_tigs_files, config)
+        }
+        94 => {
+            compare_kmer_sets::<BitPackedArrayKmer<94, 3>, _>(unitigs_file, test_tigs_files, config)
+        }
+        95 => {
+            compare_kmer_sets::<BitPackedArrayKmer<95, 3>, _>(unitigs_file, test_tigs_files, config)
+        }
+        96 => {
+            compare_kmer_sets::<BitPackedArrayKmer<96, 3>, _>(unitigs_file, test_tigs_files, config)
+        }
+        97 => {
+            compare_kmer_sets::<BitPackedArrayKmer<97, 4>, _>(unitigs_file, test_tigs_files, config)
+        }
+        98 => {
+            compare_kmer_sets::<BitPackedArrayKmer<98, 4>, _>(unitigs_file, test_tigs_files, config)
+        }
+        99 => {
+            compare_kmer_sets::<BitPackedArrayKmer<99, 4>, _>(unitigs_file, test_tigs_files, config)
+        }
+        100 => compare_kmer_sets::<BitPackedArrayKmer<100, 4>, _>(
+            unitigs_file,
+            test_tigs_files,
+            config,
+        ),
+        101 => compare_kmer_sets::<BitPackedArrayKmer<101, 4>, _>(
+            unitigs_file,
+            test_tigs_files,
+            config,
+        ),
+        102 => compare_kmer_sets::<BitPackedArrayKmer<102, 4>, _>(
+            unitigs_file,
+            test_tigs_files,
+            config,
+        ),
+        103 => compare_kmer_sets::<BitPackedArrayKmer<103, 4>, _>(
+            unitigs_file,
+            test_tigs_files,
+            config,
+        ),
+        104 => compare_kmer_sets::<BitPackedArrayKmer<104, 4>, _>(
+            unitigs_file,
+            test_tigs_files,
+            config,
+        ),
+        105 => compare_kmer_sets::<BitPackedArrayKmer<105, 4>, _>(
+            unitigs_file,
+            test_tigs_files,
+            config,
+        ),
+        106 => compare_kmer_sets::<BitPackedArrayKmer<106, 4>, _>(
+            unitigs_file,
+            test_tigs_files,
+            config,
+        ),
+        107 => compare_kmer_sets::<BitPackedArrayKmer<107, 4>, _>(
+            unitigs_file,
+            test_tigs_files,
+            config,
+        ),
+        108 => compare_kmer_sets::<BitPackedArrayKmer<108, 4>, _>(
+            unitigs_file,
+            test_tigs_files,
+            config,
+        ),
+        109 => compare_kmer_sets::<BitPackedArrayKmer<109, 4>, _>(
+            unitigs_file,
+            test_tigs_files,
+            config,
+        ),
+        110 => compare_kmer_sets::<BitPackedArrayKmer<110, 4>, _>(
+            unitigs_file,
+            test_tigs_files,
+            config,
+        ),
+        111 => compare_kmer_sets::<BitPackedArrayKmer<111, 4>, _>(
+            unitigs_file,
+            test_tigs_files,
+            config,
+        ),
+        112 => compare_kmer_sets::<BitPackedArrayKmer<112, 4>, _>(
+            unitigs_file,
+            test_tigs_files,
+            config,
+        ),
+        113 => compare_kmer_sets::<BitPackedArrayKmer<113, 4>, _>(
+            unitigs_file,
+            test_tigs_files,
+            config,
+        ),
+        114 => compare_kmer_sets::<BitPackedArrayKmer<114, 4>, _>(
+            unitigs_file,
+            test_tigs_files,
+            config,
+        ),
+        115 => compare_kmer_sets::<BitPackedArrayKmer<115, 4>, _>(
+            unitigs_file,
+            test_tigs_files,
+            config,
+        ),
+        116 => compare_kmer_sets::<BitPackedArrayKmer<116, 4>, _>(
+            unitigs_file,
+            test_tigs_files,
+            config,
+        ),
+        117 => compare_kmer_sets::<BitPackedArrayKmer<117, 4>, _>(
+            unitigs_file,
+            test_tigs_files,
+            config,
+        ),
+        118 => compare_kmer_sets::<BitPackedArrayKmer<118, 4>, _>(
+            unitigs_file,
+            test_tigs_files,
+            config,
+        ),
+        119 => compare_kmer_sets::<BitPackedArrayKmer<119, 4>, _>(
+            unitigs_file,
+            test_tigs_files,
+            config,
+        ),
+        120 => compare_kmer_sets::<BitPackedArrayKmer<120, 4>, _>(
+            unitigs_file,
+            test_tigs_files,
+            config,
+        ),
+        121 => compare_kmer_sets::<BitPackedArrayKmer<121, 4>, _>(
+            unitigs_file,
+            test_tigs_files,
+            config,
+        ),
+        122 => compare_kmer_sets::<BitPackedArrayKmer<122, 4>, _>(
+            unitigs_file,
+            test_tigs_files,
+            config,
+        ),
+        123 => compare_kmer_sets::<BitPackedArrayKmer<123, 4>, _>(
+            unitigs_file,
+            test_tigs_files,
+            config,
+        ),
+        124 => compare_kmer_sets::<BitPackedArrayKmer<124, 4>, _>(
+            unitigs_file,
+            test_tigs_files,
+            config,
+        ),
+        125 => compare_kmer_sets::<BitPackedArrayKmer<125, 4>, _>(
+            unitigs_file,
+            test_tigs_files,
+            config,
+        ),
+        126 => compare_kmer_sets::<BitPackedArrayKmer<126, 4>, _>(
+            unitigs_file,
+            test_tigs_files,
+            config,
+        ),
+        127 => compare_kmer_sets::<BitPackedArrayKmer<127, 4>, _>(
+            unitigs_file,
+            test_tigs_files,
+            config,
+        ),
+        128 => compare_kmer_sets::<BitPackedArrayKmer<128, 4>, _>(
+            unitigs_file,
+            test_tigs_files,
+            config,
+        ),
+        _ => compare_kmer_sets::<BitPackedVectorKmer, _>(unitigs_file, test_tigs_files, config),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::kmer::{BitPackedVectorKmer, Kmer, PairedKmer};
+    use crate::kmer_iterator::ParseOptions;
+    use crate::merge::MismatchKind;
+    use crate::test_util::TempFile;
+    use crate::{
+        compare_kmer_sets, compare_kmer_sets_with_visitor, complement_matches, count_k_plus_1_mers,
+        count_palindromes, initialise_logging, internal_invariant, list_kmers, parse_k_range,
+        BitPackedKmer, CollectError, Config, ConfigBuilder, DedupStrategy, Error, KmerIterator,
+    };
+    use clap::Parser;
+    use log::LevelFilter;
+    use std::collections::HashSet;
+    use std::process::ExitCode;
+
+    #[test]
+    fn test_config_builder_defaults() {
+        let parsed = Config::parse_from(["verify-same-kmer-content", "-k", "3", "u.fa", "t.fa"]);
+        let built = Config {
+            unitigs: "u.fa".into(),
+            test_tigs: vec!["t.fa".into()],
+            ..ConfigBuilder::new(3).build()
+        };
+        assert_eq!(format!("{parsed:?}"), format!("{built:?}"));
+    }
+
+    #[test]
+    fn test_simple() {
+        initialise_logging(LevelFilter::Debug);
+        let unitigs = ">a\nTAAACTG";
+        let test_tigs = ">\nTAAAC\n>\nCAGT\n";
+
+        let result = compare_kmer_sets::<BitPackedKmer<3, u8>, _>(
+            unitigs.as_bytes(),
+            [(String::new(), test_tigs.as_bytes())],
+            ConfigBuilder::new(3)
+                .log_level(LevelFilter::Debug)
+                .panic_on_parse_error(true)
+                .build(),
+        );
+
+        assert!(result.is_ok(), "Expected ok result, but got {result:?}");
+    }
+
+    #[test]
+    fn test_trailing_mismatches() {
+        initialise_logging(LevelFilter::Debug);
+        // The largest kmers of one side have no counterpart in the other side.
+        for (unitigs, test_tigs, expected_result) in [
+            (">a\nAAA", ">\nAAAC\n", Err(Error::SuperfluousKmers)),
+            (">a\nAAAC", ">\nAAA\n", Err(Error::MissingKmers)),
+        ] {
+            for (assume_sorted, spill_threshold) in [(false, None), (true, None), (false, Some(1))]
+            {
+                let result = compare_kmer_sets::<BitPackedKmer<3, u8>, _>(
+                    unitigs.as_bytes(),
+                    [(String::new(), test_tigs.as_bytes())],
+                    ConfigBuilder::new(3)
+                        .log_level(LevelFilter::Debug)
+                        .panic_on_parse_error(true)
+                        .assume_sorted(assume_sorted)
+                        .spill_threshold(spill_threshold)
+                        .build(),
+                );
+                assert_eq!(
+                    result, expected_result,
+                    "{unitigs:?} {test_tigs:?} assume_sorted: {assume_sorted}, spill_threshold: {spill_threshold:?}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_mismatch_visitor() {
+        initialise_logging(LevelFilter::Debug);
+        for (assume_sorted, spill_threshold) in [(false, None), (true, None), (false, Some(1))] {
+            let mut mismatches = Vec::new();
+            let result = compare_kmer_sets_with_visitor::<BitPackedKmer<3, u8>, _>(
+                ">a\nAAAC\n".as_bytes(),
+                [(String::new(), ">\nAACG\n".as_bytes())],
+                ConfigBuilder::new(3)
+                    .log_level(LevelFilter::Debug)
+                    .panic_on_parse_error(true)
+                    .assume_sorted(assume_sorted)
+                    .spill_threshold(spill_threshold)
+                    .build(),
+                |event| mismatches.push((event.kmer.to_string(), event.kind)),
+            );
+
+            assert_eq!(result, Err(Error::MissingAndSuperfluousKmers));
+            assert_eq!(
+                mismatches,
+                [
+                    ("AAA".to_string(), MismatchKind::MissingInTest),
+                    ("ACG".to_string(), MismatchKind::ExtraInTest),
+                ],
+                "assume_sorted: {assume_sorted}, spill_threshold: {spill_threshold:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_fail_fast() {
+        initialise_logging(LevelFilter::Debug);
+        let unitigs = ">a\nTAAACTG";
+
+        for (test_tigs, expected_result) in [
+            (">\nTAAAC\n>\nCAGT\n", Ok(())),
+            (">\nTAAAC\n", Err(Error::MissingKmers)),
+            (">\nTAAACTGCCCCC\n", Err(Error::SuperfluousKmers)),
+        ] {
+            let result = compare_kmer_sets::<BitPackedKmer<3, u8>, _>(
+                unitigs.as_bytes(),
+                [(String::new(), test_tigs.as_bytes())],
+                ConfigBuilder::new(3)
+                    .log_level(LevelFilter::Debug)
+                    .panic_on_parse_error(true)
+                    .fail_fast(true)
+                    .build(),
+            );
+
+            assert_eq!(result, expected_result);
+        }
+
+        // The report is written before failing early, without the metrics that require merging all kmers.
+        let stats_file = TempFile::new("fail-fast");
+        let result = compare_kmer_sets::<BitPackedKmer<3, u8>, _>(
+            ">a\nACGTAC".as_bytes(),
+            [(String::new(), ">\nACGTACTTTT\n>\nACGTAC\n".as_bytes())],
+            ConfigBuilder::new(3)
+                .log_level(LevelFilter::Debug)
+                .panic_on_parse_error(true)
+                .fail_fast(true)
+                .stats_output(Some(stats_file.path().to_path_buf()))
+                .build(),
+        );
+        assert_eq!(result, Err(Error::SuperfluousKmers));
+        let stats = std::fs::read_to_string(stats_file.path()).unwrap();
+        assert!(stats.contains("\ntest_str_cnt: 1\n"), "{stats}");
+        assert!(!stats.contains("jaccard"), "{stats}");
+        assert!(!stats.contains("superfluous_unitig_kmer_count"), "{stats}");
+
+        let result = compare_kmer_sets::<BitPackedKmer<3, u8>, _>(
+            unitigs.as_bytes(),
+            [(String::new(), unitigs.as_bytes())],
+            ConfigBuilder::new(3)
+                .fail_fast(true)
+                .allow_superset(true)
+                .build(),
+        );
+        assert_eq!(result, Err(Error::IncompatibleArguments));
+    }
+
+    #[test]
+    fn test_stats_output() {
+        initialise_logging(LevelFilter::Debug);
+        let stats_file = TempFile::new("stats-output");
+        std::fs::write(stats_file.path(), "outdated").unwrap();
+
+        let result = compare_kmer_sets::<BitPackedKmer<3, u8>, _>(
+            ">a\nTAAACTG".as_bytes(),
+            [
+                ("first".to_string(), ">\nTAAAC\n>\nCAGT\n".as_bytes()),
+                ("second".to_string(), ">\nTAAACTG\n".as_bytes()),
+            ],
+            ConfigBuilder::new(3)
+                .log_level(LevelFilter::Debug)
+                .panic_on_parse_error(true)
+                .stats_output(Some(stats_file.path().to_path_buf()))
+                .build(),
+        );
+        assert_eq!(result, Ok(()));
+
+        let stats = std::fs::read_to_string(stats_file.path()).unwrap();
+        assert!(stats.starts_with("test_tigs: first\n"), "{stats}");
+        assert!(stats.contains("\ntest_tigs: second\n"), "{stats}");
+        assert!(!stats.contains("outdated"), "{stats}");
+    }
+
+    #[test]
+    fn test_estimate_memory() {
+        initialise_logging(LevelFilter::Debug);
+        let stats_file = TempFile::new("estimate-memory");
+
+        let result = compare_kmer_sets::<BitPackedKmer<3, u8>, _>(
+            ">a\nTAAACTG".as_bytes(),
+            [
+                ("first".to_string(), ">\nTAAAC\n>\nCAGT\n".as_bytes()),
+                ("second".to_string(), ">\nTAAACTGG\n".as_bytes()),
+            ],
+            ConfigBuilder::new(3)
+                .log_level(LevelFilter::Debug)
+                .panic_on_parse_error(true)
+                .estimate_memory(true)
+                .stats_output(Some(stats_file.path().to_path_buf()))
+                .build(),
+        );
+        assert_eq!(result, Ok(()));
+
+        let stats = std::fs::read_to_string(stats_file.path()).unwrap();
+        // The 5 unitig kmers, and the capacity reserved for the 15 bytes of the first test tig file.
+        assert_eq!(
+            stats,
+            format!(
+                "unitig_kmer_count: 5\nmax_test_tig_kmer_count: 6\nestimated_memory_bytes: 20\nvector_kmer_estimated_memory_bytes: {}\n",
+                20 * BitPackedVectorKmer::memory_size(3)
+            )
+        );
+    }
+
+    #[test]
+    fn test_kmer_dump() {
+        initialise_logging(LevelFilter::Debug);
+        let unitigs = ">a\nTAAACTG";
+        let dump_file = TempFile::new("kmer-dump");
+        let config = |k, dump_kmers, ground_truth_dump| {
+            ConfigBuilder::new(k)
+                .log_level(LevelFilter::Debug)
+                .panic_on_parse_error(true)
+                .dump_kmers(dump_kmers)
+                .ground_truth_dump(ground_truth_dump)
+                .build()
+        };
+
+        let result = compare_kmer_sets::<BitPackedKmer<3, u8>, _>(
+            unitigs.as_bytes(),
+            [(String::new(), ">\nTAAAC\n>\nCAGT\n".as_bytes())],
+            config(3, Some(dump_file.path().to_path_buf()), None),
+        );
+        assert_eq!(result, Ok(()));
+
+        for (test_tigs, expected_result) in [
+            (">\nTAAAC\n>\nCAGT\n", Ok(())),
+            (">\nTAAAC\n", Err(Error::MissingKmers)),
+            (">\nTAAACTGCCCCC\n", Err(Error::SuperfluousKmers)),
+        ] {
+            let result = compare_kmer_sets::<BitPackedKmer<3, u8>, _>(
+                "".as_bytes(),
+                [(String::new(), test_tigs.as_bytes())],
+                config(3, None, Some(dump_file.path().to_path_buf())),
+            );
+            assert_eq!(result, expected_result);
+        }
+
+        let result = compare_kmer_sets::<BitPackedKmer<4, u8>, _>(
+            "".as_bytes(),
+            [(String::new(), ">\nTAAACTG\n".as_bytes())],
+            config(4, None, Some(dump_file.path().to_path_buf())),
+        );
+        assert_eq!(result, Err(Error::InvalidKmerDump));
+    }
+
+    #[test]
+    fn test_empty_input() {
+        initialise_logging(LevelFilter::Debug);
+        let config = |allow_empty_input, assume_sorted| {
+            ConfigBuilder::new(3)
+                .log_level(LevelFilter::Debug)
+                .panic_on_parse_error(true)
+                .allow_empty_input(allow_empty_input)
+                .assume_sorted(assume_sorted)
+                .build()
+        };
+
+        for assume_sorted in [false, true] {
+            for (unitigs, test_tigs) in [("", ">\nACG\n"), (">\nACG\n", ""), ("", "")] {
+                let result = compare_kmer_sets::<BitPackedKmer<3, u8>, _>(
+                    unitigs.as_bytes(),
+                    [(String::new(), test_tigs.as_bytes())],
+                    config(false, assume_sorted),
+                );
+                assert_eq!(result, Err(Error::EmptyInput));
+            }
+
+            let result = compare_kmer_sets::<BitPackedKmer<3, u8>, _>(
+                "".as_bytes(),
+                [(String::new(), "".as_bytes())],
+                config(true, assume_sorted),
+            );
+            assert_eq!(result, Ok(()));
+        }
+    }
+
+    #[test]
+    fn test_max_kmers() {
+        initialise_logging(LevelFilter::Debug);
+        let unitigs = ">a\nTAAACTG";
+        let test_tigs = ">\nTAAAC\n>\nCAGT\n";
+
+        for dedup_strategy in [
+            DedupStrategy::Sort,
+            DedupStrategy::Hashset,
+            DedupStrategy::Btreeset,
+        ] {
+            for (max_kmers, expected_result) in [
+                (None, Ok(())),
+                (Some(5), Ok(())),
+                (Some(4), Err(Error::TooManyKmers)),
+            ] {
+                let result = compare_kmer_sets::<BitPackedKmer<3, u8>, _>(
+                    unitigs.as_bytes(),
+                    [(String::new(), test_tigs.as_bytes())],
+                    ConfigBuilder::new(3)
+                        .log_level(LevelFilter::Debug)
+                        .panic_on_parse_error(true)
+                        .dedup_strategy(dedup_strategy)
+                        .max_kmers(max_kmers)
+                        .build(),
+                );
+
+                assert_eq!(result, expected_result);
+            }
+        }
+    }
+
+    #[test]
+    fn test_spill_threshold() {
+        initialise_logging(LevelFilter::Debug);
+        let unitigs = ">a\nTAAACTG\n>b\nGGGCCCA";
+
+        for (test_tigs, expected_result) in [
+            (">\nTAAAC\n>\nCAGT\n>\nTGGGCCC\n", Ok(())),
+            (">\nTAAACTG\n", Err(Error::MissingKmers)),
+            (">\nTAAACTGT\n>\nGGGCCCA\n", Err(Error::SuperfluousKmers)),
+            (">\nTAAAC\n>\nACA\n", Err(Error::MissingAndSuperfluousKmers)),
+        ] {
+            for spill_threshold in [None, Some(1), Some(2), Some(3), Some(100)] {
+                let result = compare_kmer_sets::<BitPackedKmer<3, u8>, _>(
+                    unitigs.as_bytes(),
+                    [(String::new(), test_tigs.as_bytes())],
+                    ConfigBuilder::new(3)
+                        .log_level(LevelFilter::Debug)
+                        .panic_on_parse_error(true)
+                        .spill_threshold(spill_threshold)
+                        .build(),
+                );
+
+                assert_eq!(
+                    result, expected_result,
+                    "test tigs: {test_tigs:?}, spill threshold: {spill_threshold:?}"
+                );
+            }
+        }
+
+        let result = compare_kmer_sets::<BitPackedKmer<3, u8>, _>(
+            unitigs.as_bytes(),
+            [(String::new(), unitigs.as_bytes())],
+            ConfigBuilder::new(3)
+                .spill_threshold(Some(2))
+                .fail_fast(true)
+                .build(),
+        );
+        assert_eq!(result, Err(Error::IncompatibleArguments));
+    }
+
+    #[test]
+    fn test_sample() {
+        initialise_logging(LevelFilter::Debug);
+        let unitigs = ">a\nTAAACTG\n>b\nGGGCCCA";
+        let test_tigs = ">\nTAAACTG\n>\nGGGCCCA\n>\nACA\n";
+        let compare = |sample, seed, spill_threshold| {
+            compare_kmer_sets::<BitPackedKmer<3, u8>, _>(
+                unitigs.as_bytes(),
+                [(String::new(), test_tigs.as_bytes())],
+                ConfigBuilder::new(3)
+                    .log_level(LevelFilter::Debug)
+                    .panic_on_parse_error(true)
+                    .sample(sample)
+                    .seed(seed)
+                    .spill_threshold(spill_threshold)
+                    .build(),
+            )
+        };
+
+        assert_eq!(compare(1.0, 0, None), Err(Error::SuperfluousKmers));
+        assert_eq!(compare(0.0, 0, None), Err(Error::IncompatibleArguments));
+        assert_eq!(compare(1.5, 0, None), Err(Error::IncompatibleArguments));
+
+        // The superfluous kmer is only detected if it is sampled, which depends on the seed.
+        let results: Vec<_> = (0..32).map(|seed| compare(0.5, seed, None)).collect();
+        assert!(results.contains(&Ok(())));
+        assert!(results.contains(&Err(Error::SuperfluousKmers)));
+        for (seed, result) in results.into_iter().enumerate() {
+            assert!(matches!(result, Ok(()) | Err(Error::SuperfluousKmers)));
+            assert_eq!(compare(0.5, seed as u64, Some(2)), result);
+        }
+    }
+
+    #[test]
+    fn test_parallel_read() {
+        initialise_logging(LevelFilter::Debug);
+        let unitigs = ">a\nTAAACTG\n>b\nGGGCCCA";
+        let compare = |test_tigs: &[&str], parallel_read, fail_fast| {
+            compare_kmer_sets::<BitPackedKmer<3, u8>, _>(
+                unitigs.as_bytes(),
+                test_tigs
+                    .iter()
+                    .map(|test_tigs| (String::new(), test_tigs.as_bytes())),
+                ConfigBuilder::new(3)
+                    .log_level(LevelFilter::Debug)
+                    .panic_on_parse_error(true)
+                    .parallel_read(parallel_read)
+                    .fail_fast(fail_fast)
+                    .multiplicity_histogram(true)
+                    .build(),
+            )
+        };
+
+        for test_tigs in [
+            &[">\nTAAACTG\n>\nGGGCCCA\n"][..],
+            &[">\nTAAACTGT\n>\nGGGCCCA\n"],
+            &[">\nTAAAC\n>\nGGGCCCA\n"],
+            &[">\nTAAACTG\n>\nGGGCCCA\n", ">\nTAAAC\n>\nGGGCCCA\n"],
+            &[">\nTAAAC\n>\nGGGCCCA\n", ">\nTAAACTGT\n>\nGGGCCCA\n"],
+            &[],
+        ] {
+            assert_eq!(
+                compare(test_tigs, true, false),
+                compare(test_tigs, false, false),
+                "{test_tigs:?}"
+            );
+        }
+        assert_eq!(
+            compare(&[">\nTAAACTGT\n>\nGGGCCCA\n"], true, false),
+            Err(Error::SuperfluousKmers)
+        );
+        assert_eq!(
+            compare(&[">\nTAAACTG\n>\nGGGCCCA\n"], true, true),
+            Err(Error::IncompatibleArguments)
+        );
+    }
+
+    #[test]
+    fn test_complement_only() {
+        initialise_logging(LevelFilter::Debug);
+        let kmers = |sequence: &str, strand_specific| {
+            let mut kmers: Vec<_> = sequence
+                .as_bytes()
+                .windows(3)
+                .map(|kmer| {
+                    let kmer = BitPackedKmer::<3, u8>::from_ascii(kmer).unwrap();
+                    if strand_specific {
+                        kmer
+                    } else {
+                        kmer.into_canonical()
+                    }
+                })
+                .collect();
+            kmers.sort();
+            kmers.dedup();
+            kmers
+        };
+
+        for strand_specific in [false, true] {
+            let config = ConfigBuilder::new(3)
+                .strand_specific(strand_specific)
+                .build();
+            let unitig_kmers = kmers("AAACTG", strand_specific);
+            assert!(complement_matches(
+                &unitig_kmers,
+                &kmers("TTTGAC", strand_specific),
+                &config
+            ));
+            assert!(!complement_matches(
+                &unitig_kmers,
+                &kmers("AAACTG", strand_specific),
+                &config
+            ));
+            assert!(!complement_matches(
+                &unitig_kmers,
+                &kmers("TTTGA", strand_specific),
+                &config
+            ));
+        }
+
+        let compare = |sample| {
+            compare_kmer_sets::<BitPackedKmer<3, u8>, _>(
+                ">a\nAAACTG\n".as_bytes(),
+                [(String::new(), ">\nTTTGAC\n".as_bytes())],
+                ConfigBuilder::new(3)
+                    .log_level(LevelFilter::Debug)
+                    .panic_on_parse_error(true)
+                    .complement_only(true)
+                    .sample(sample)
+                    .build(),
+            )
+        };
+        // The diagnostic does not change the outcome of the comparison.
+        assert_eq!(compare(1.0), Err(Error::MissingAndSuperfluousKmers));
+        assert_eq!(compare(0.5), Err(Error::IncompatibleArguments));
+    }
+
+    #[test]
+    fn test_parse_k_range() {
+        assert_eq!(parse_k_range("21..23"), Ok(21..=23));
+        assert_eq!(parse_k_range("21..=23"), Ok(21..=23));
+        assert_eq!(parse_k_range("5..5"), Ok(5..=5));
+        assert!(parse_k_range("0..3").is_err());
+        assert!(parse_k_range("4..3").is_err());
+        assert!(parse_k_range("21").is_err());
+        assert!(parse_k_range("a..3").is_err());
+        assert!(parse_k_range("3..").is_err());
+    }
+
+    #[test]
+    fn test_min_count() {
+        initialise_logging(LevelFilter::Debug);
+        let compare = |min_count| {
+            compare_kmer_sets::<BitPackedKmer<3, u8>, _>(
+                ">a\nAAACTG\n".as_bytes(),
+                [(
+                    String::new(),
+                    "AAA\t5\nAAC\t4\nACA\t1\nAGT\t3\nCAG\t2\n".as_bytes(),
+                )],
+                ConfigBuilder::new(3)
+                    .log_level(LevelFilter::Debug)
+                    .panic_on_parse_error(true)
+                    .min_count(min_count)
+                    .build(),
+            )
+        };
+
+        assert_eq!(compare(0), Err(Error::SuperfluousKmers));
+        assert_eq!(compare(2), Ok(()));
+        assert_eq!(compare(3), Err(Error::MissingKmers));
+    }
+
+    #[test]
+    fn test_list_kmers() {
+        initialise_logging(LevelFilter::Debug);
+        let unitigs = ">a\nTAAACTG\n>b\nCAGT\n";
+        let mut output = Vec::new();
+
+        let result = list_kmers(
+            KmerIterator::<_, BitPackedKmer<3, u8>>::new(
+                unitigs.as_bytes(),
+                3,
+                ParseOptions {
+                    panic_on_parse_error: true,
+                    ..Default::default()
+                },
+            ),
+            &mut output,
+            &ConfigBuilder::new(3)
+                .log_level(LevelFilter::Debug)
+                .panic_on_parse_error(true)
+                .list_kmers(true)
+                .build(),
+        );
+
+        assert_eq!(result, Ok(()));
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "AAA\nAAC\nACT\nCAG\nTAA\n"
+        );
+    }
+
+    #[test]
+    fn test_export_fasta() {
+        initialise_logging(LevelFilter::Debug);
+        let fasta_file = TempFile::new("export-fasta");
+
+        let result = compare_kmer_sets::<BitPackedKmer<3, u8>, _>(
+            ">a\nTAAACTG\n".as_bytes(),
+            [(String::new(), ">\nTAAAC\n>\nCAGT\n".as_bytes())],
+            ConfigBuilder::new(3)
+                .log_level(LevelFilter::Debug)
+                .panic_on_parse_error(true)
+                .export_fasta(Some(fasta_file.path().to_path_buf()))
+                .build(),
+        );
+        assert_eq!(result, Ok(()));
+        assert_eq!(
+            std::fs::read_to_string(fasta_file.path()).unwrap(),
+            ">kmer_0\nAAA\n>kmer_1\nAAC\n>kmer_2\nACT\n>kmer_3\nCAG\n>kmer_4\nTAA\n"
+        );
+    }
+
+    #[test]
+    fn test_count_k_plus_1_mers() {
+        fn reverse_complement(sequence: &[u8]) -> Vec<u8> {
+            sequence
+                .iter()
+                .rev()
+                .map(|character| match character {
+                    b'A' => b'T',
+                    b'C' => b'G',
+                    b'G' => b'C',
+                    _ => b'A',
+                })
+                .collect()
+        }
+
+        for unitigs in [
+            &["TAAACTG"][..],
+            &["ACGTACGTT"],
+            &["AAAAAA", "CCCGGG"],
+            &["ACGTTGCAACGGT", "TTAGCA"],
+        ] {
+            for strand_specific in [false, true] {
+                let normalise = |kmer: BitPackedKmer<3, u8>| {
+                    if strand_specific {
+                        kmer
+                    } else {
+                        kmer.canonical()
+                    }
+                };
+                let mut kmers: Vec<_> = unitigs
+                    .iter()
+                    .flat_map(|unitig| unitig.as_bytes().windows(3))
+                    .map(|kmer| normalise(BitPackedKmer::from_ascii(kmer).unwrap()))
+                    .collect();
+                kmers.sort();
+                kmers.dedup();
+
+                // Enumerate all 4-mers, and keep those whose prefix and suffix are kmers.
+                let mut expected = HashSet::new();
+                for index in 0..256usize {
+                    let k_plus_1_mer: Vec<_> = (0..4)
+                        .map(|position| b"ACGT"[(index >> (2 * position)) & 3])
+                        .collect();
+                    let [prefix, suffix] = [&k_plus_1_mer[..3], &k_plus_1_mer[1..]]
+                        .map(|kmer| normalise(BitPackedKmer::from_ascii(kmer).unwrap()));
+                    if kmers.binary_search(&prefix).is_ok() && kmers.binary_search(&suffix).is_ok()
+                    {
+                        let reverse_complement = reverse_complement(&k_plus_1_mer);
+                        expected.insert(if strand_specific {
+                            k_plus_1_mer
+                        } else {
+                            k_plus_1_mer.min(reverse_complement)
+                        });
+                    }
+                }
+
+                assert_eq!(
+                    count_k_plus_1_mers(&kmers, strand_specific),
+                    expected.len(),
+                    "unitigs: {unitigs:?}, strand specific: {strand_specific}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_count_palindromes() {
+        let kmers = ["AAAA", "AATT", "ACGT", "CCGA", "GGCC"]
+            .map(|kmer| BitPackedKmer::<4, u8>::from_iter(kmer.bytes()));
+        assert_eq!(count_palindromes(&kmers, 4), 3);
+
+        let kmers = ["AAT", "ACG"].map(|kmer| BitPackedKmer::<3, u8>::from_iter(kmer.bytes()));
+        assert_eq!(count_palindromes(&kmers, 3), 0);
+    }
+
+    #[test]
+    fn test_allow_superset() {
+        initialise_logging(LevelFilter::Debug);
+        let unitigs = ">a\nTAAACTG";
+
+        for (test_tigs, expected_result) in [
+            (">\nTAAAC\n>\nCAGT\n", Ok(())),
+            (">\nTAAACTGCCCCC\n", Ok(())),
+            (">\nTAAAC\n", Err(Error::MissingKmers)),
+            (">\nTAAACCCCC\n", Err(Error::MissingKmers)),
+            // The test tigs end before the largest unitig kmers.
+            (">\nAAAC\n>\nAAG\n", Err(Error::MissingKmers)),
+        ] {
+            let result = compare_kmer_sets::<BitPackedKmer<3, u8>, _>(
+                unitigs.as_bytes(),
+                [(String::new(), test_tigs.as_bytes())],
+                ConfigBuilder::new(3)
+                    .log_level(LevelFilter::Debug)
+                    .panic_on_parse_error(true)
+                    .allow_superset(true)
+                    .build(),
+            );
+
+            assert_eq!(result, expected_result, "test tigs: {test_tigs:?}");
+        }
+
+        // Duplicate unitig kmers outnumber the test tig kmers, but none of them is missing.
+        let result = compare_kmer_sets::<BitPackedKmer<3, u8>, _>(
+            ">a\nTAAACTG\n>b\nTAAACTG".as_bytes(),
+            [(String::new(), ">\nTAAACTGCC\n".as_bytes())],
+            ConfigBuilder::new(3)
+                .log_level(LevelFilter::Debug)
+                .panic_on_parse_error(true)
+                .allow_superset(true)
+                .build(),
+        );
+        assert_eq!(result, Ok(()));
+    }
+
+    #[test]
+    fn test_allow_subset() {
+        initialise_logging(LevelFilter::Debug);
+        let unitigs = ">a\nTAAACTG";
+
+        for (test_tigs, expected_result) in [
+            (">\nTAAAC\n>\nCAGT\n", Ok(())),
+            (">\nTAAAC\n", Ok(())),
+            // Duplicate test tig kmers are not superfluous.
+            (">\nTAAAC\n>\nTAAAC\n", Ok(())),
+            (">\nTAAACTGCCCCC\n", Err(Error::SuperfluousKmers)),
+            (">\nTAAACCCCC\n", Err(Error::SuperfluousKmers)),
+            // The unitigs end before the largest test tig kmer.
+            (">\nAAAC\n>\nTCA\n", Err(Error::SuperfluousKmers)),
+        ] {
+            let result = compare_kmer_sets::<BitPackedKmer<3, u8>, _>(
+                unitigs.as_bytes(),
+                [(String::new(), test_tigs.as_bytes())],
+                ConfigBuilder::new(3)
+                    .log_level(LevelFilter::Debug)
+                    .panic_on_parse_error(true)
+                    .allow_subset(true)
+                    .build(),
+            );
+
+            assert_eq!(result, expected_result, "test tigs: {test_tigs:?}");
+        }
+    }
+
+    #[test]
+    fn test_strict_equality() {
+        initialise_logging(LevelFilter::Debug);
+        let unitigs = ">a\nTAAACTG\n>b\nTAAAC\n";
+
+        for (test_tigs, expected_default_result, expected_strict_result) in [
+            // The duplicate unitig kmers make the default fail, since it compares the counts including duplicates.
+            (">\nTAAAC\n>\nCAGT\n", Err(Error::MissingKmers), Ok(())),
+            (">\nTAAACTG\n>\nAAACT\n", Ok(()), Ok(())),
+            (
+                ">\nTAAAC\n",
+                Err(Error::MissingKmers),
+                Err(Error::MissingKmers),
+            ),
+            (
+                ">\nTAAACTGCCCCC\n",
+                Err(Error::SuperfluousKmers),
+                Err(Error::SuperfluousKmers),
+            ),
+        ] {
+            for (strict_equality, expected_result) in [
+                (false, expected_default_result),
+                (true, expected_strict_result),
+            ] {
+                for spill_threshold in [None, Some(2)] {
+                    let result = compare_kmer_sets::<BitPackedKmer<3, u8>, _>(
+                        unitigs.as_bytes(),
+                        [(String::new(), test_tigs.as_bytes())],
+                        ConfigBuilder::new(3)
+                            .log_level(LevelFilter::Debug)
+                            .panic_on_parse_error(true)
+                            .strict_equality(strict_equality)
+                            .spill_threshold(spill_threshold)
+                            .build(),
+                    );
+
+                    assert_eq!(
+                        result, expected_result,
+                        "test tigs: {test_tigs:?}, strict_equality: {strict_equality}, spill_threshold: {spill_threshold:?}"
+                    );
+                }
+            }
+        }
+
+        let result = compare_kmer_sets::<BitPackedKmer<3, u8>, _>(
+            unitigs.as_bytes(),
+            [(String::new(), ">\nTAAAC\n".as_bytes())],
+            ConfigBuilder::new(3)
+                .log_level(LevelFilter::Debug)
+                .panic_on_parse_error(true)
+                .strict_equality(true)
+                .allow_subset(true)
+                .build(),
+        );
+        assert_eq!(result, Err(Error::IncompatibleArguments));
+    }
+
+    #[test]
+    fn test_multiset() {
+        initialise_logging(LevelFilter::Debug);
+        let unitigs = ">a\nTAAACTG\n>b\nTAAAC\n";
+
+        for (test_tigs, expected_result) in [
+            (">\nTAAACTG\n>\nTAAAC\n", Ok(())),
+            // The kmers of the second unitig occur only once.
+            (">\nTAAACTG\n", Err(Error::MissingKmers)),
+            (
+                ">\nTAAACTG\n>\nTAAAC\n>\nAAAC\n",
+                Err(Error::SuperfluousKmers),
+            ),
+            (
+                ">\nTAAACTG\n>\nTAAA\n>\nCAGT\n",
+                Err(Error::MissingAndSuperfluousKmers),
+            ),
+        ] {
+            let result = compare_kmer_sets::<BitPackedKmer<3, u8>, _>(
+                unitigs.as_bytes(),
+                [(String::new(), test_tigs.as_bytes())],
+                ConfigBuilder::new(3)
+                    .log_level(LevelFilter::Debug)
+                    .panic_on_parse_error(true)
+                    .multiset(true)
+                    .build(),
+            );
+            assert_eq!(result, expected_result, "test tigs: {test_tigs:?}");
+        }
+
+        let result = compare_kmer_sets::<BitPackedKmer<3, u8>, _>(
+            unitigs.as_bytes(),
+            [(String::new(), ">\nTAAAC\n".as_bytes())],
+            ConfigBuilder::new(3)
+                .log_level(LevelFilter::Debug)
+                .panic_on_parse_error(true)
+                .multiset(true)
+                .dedup_strategy(DedupStrategy::Hashset)
+                .build(),
+        );
+        assert_eq!(result, Err(Error::IncompatibleArguments));
+    }
+
+    #[test]
+    fn test_self_complemental_node() {
+        initialise_logging(LevelFilter::Debug);
+        let unitigs = ">a\nTAATTACTG";
+        let test_tigs = ">\nTAATTA\n>\nCAGTAA\n";
+        // The unitig kmer `ACTG` occurs only as its reverse complement `CAGT` in the test tigs.
+        let kmer = |kmer: &str| kmer.bytes().collect::<BitPackedKmer<4, u8>>();
+        assert!(kmer("ACTG").same_content(&kmer("CAGT")));
+        assert!(kmer("AATT").same_content(&kmer("AATT")));
+        assert!(compare_kmer_sets::<BitPackedKmer<4, u8>, _>(
+            unitigs.as_bytes(),
+            [(String::new(), test_tigs.as_bytes())],
+            ConfigBuilder::new(4)
+                .log_level(LevelFilter::Debug)
+                .panic_on_parse_error(true)
+                .build()
+        )
+        .is_ok());
+    }
+
+    #[test]
+    fn test_hashset_dedup_strategy() {
+        initialise_logging(LevelFilter::Debug);
+        let unitigs = ">a\nTAAACTG";
+        let test_tigs = ">\nTAAAC\n>\nCAGT\n>\nAAACT\n";
+
+        let result = compare_kmer_sets::<BitPackedKmer<3, u8>, _>(
+            unitigs.as_bytes(),
+            [(String::new(), test_tigs.as_bytes())],
+            ConfigBuilder::new(3)
+                .log_level(LevelFilter::Debug)
+                .panic_on_parse_error(true)
+                .dedup_strategy(DedupStrategy::Hashset)
+                .build(),
+        );
+
+        assert!(result.is_ok(), "Expected ok result, but got {result:?}");
+    }
+
+    #[test]
+    fn test_btreeset_dedup_strategy() {
+        initialise_logging(LevelFilter::Debug);
+        let unitigs = ">a\nTAAACTG\n>b\nAAACT";
+
+        for (test_tigs, expected_result) in [
+            (">\nTAAAC\n>\nCAGT\n>\nAAACT\n", Ok(())),
+            (">\nTAAAC\n>\nAAACT\n", Err(Error::MissingKmers)),
+            (">\nTAAACTGG\n", Err(Error::SuperfluousKmers)),
+        ] {
+            let result = compare_kmer_sets::<BitPackedKmer<3, u8>, _>(
+                unitigs.as_bytes(),
+                [(String::new(), test_tigs.as_bytes())],
+                ConfigBuilder::new(3)
+                    .log_level(LevelFilter::Debug)
+                    .panic_on_parse_error(true)
+                    .dedup_strategy(DedupStrategy::Btreeset)
+                    .build(),
+            );
+
+            assert_eq!(result, expected_result, "test tigs: {test_tigs:?}");
+        }
+    }
+
+    #[test]
+    fn test_multiple_test_tigs() {
+        initialise_logging(LevelFilter::Debug);
+        let unitigs = ">a\nTAAACTG";
+        let matching_test_tigs = ">\nTAAAC\n>\nCAGT\n";
+        let mismatching_test_tigs = ">\nTAAAG\n";
+
+        let result = compare_kmer_sets::<BitPackedKmer<3, u8>, _>(
+            unitigs.as_bytes(),
+            [
+                ("matching".to_string(), matching_test_tigs.as_bytes()),
+                ("mismatching".to_string(), mismatching_test_tigs.as_bytes()),
+            ],
+            ConfigBuilder::new(3)
+                .log_level(LevelFilter::Debug)
+                .panic_on_parse_error(true)
+                .build(),
+        );
+
+        assert_eq!(result, Err(Error::MissingAndSuperfluousKmers));
+    }
+
+    #[test]
+    fn test_combine_errors() {
+        assert_eq!(
+            Error::MissingKmers.combine(Error::SuperfluousKmers),
+            Error::MissingAndSuperfluousKmers
+        );
+        assert_eq!(
+            Error::MissingKmers.combine(Error::MissingKmers),
+            Error::MissingKmers
+        );
+        assert_eq!(
+            Error::MissingAndSuperfluousKmers.combine(Error::SuperfluousKmers),
+            Error::MissingAndSuperfluousKmers
+        );
+        assert_eq!(
+            Error::SuperfluousKmers.combine(Error::MissingAndSuperfluousKmers),
+            Error::MissingAndSuperfluousKmers
+        );
+    }
+
+    #[test]
+    fn test_internal_invariant() {
+        initialise_logging(LevelFilter::Debug);
+
+        let config = ConfigBuilder::new(3).build();
+        assert!(matches!(
+            internal_invariant::<u8>("test".to_string(), &config),
+            CollectError::InternalInvariant
+        ));
+        assert_eq!(Error::InternalInvariant.exit_code(), ExitCode::from(8));
+    }
+
+    #[test]
+    #[should_panic(expected = "Internal invariant violated: test")]
+    fn test_internal_invariant_debug_checks() {
+        let config = ConfigBuilder::new(3).debug_checks(true).build();
+        internal_invariant::<u8>("test".to_string(), &config);
+    }
+
+    #[test]
+    fn test_strand_specific() {
+        initialise_logging(LevelFilter::Debug);
+        let unitigs = ">a\nAAACT";
+        let test_tigs = ">\nAGTTT\n";
+
+        for (strand_specific, expect_ok) in [(false, true), (true, false)] {
+            let result = compare_kmer_sets::<BitPackedKmer<3, u8>, _>(
+                unitigs.as_bytes(),
+                [(String::new(), test_tigs.as_bytes())],
+                ConfigBuilder::new(3)
+                    .log_level(LevelFilter::Debug)
+                    .panic_on_parse_error(true)
+                    .strand_specific(strand_specific)
+                    .build(),
+            );
+
+            assert_eq!(result.is_ok(), expect_ok, "{result:?}");
+        }
+    }
+
+    #[test]
+    fn test_gap() {
+        initialise_logging(LevelFilter::Debug);
+        for (unitigs, test_tigs, expected_result) in [
+            (">a\nACGTTAC", ">\nGTAACGT\n", Ok(())),
+            // Only the gap characters differ.
+            (">a\nACGTT", ">\nACATT\n", Ok(())),
+            // The pair `GT-AC` of the last record is the largest in both inputs.
+            (
+                ">a\nACGTT\n>b\nGTAAC",
+                ">\nACGTA\n>\nGTAAC\n",
+                Err(Error::MissingAndSuperfluousKmers),
+            ),
+            (
+                ">a\nACGTTAC",
+                ">\nACGTT\n>\nGTAAC\n",
+                Err(Error::MissingKmers),
+            ),
+        ] {
+            let result = compare_kmer_sets::<PairedKmer<BitPackedVectorKmer>, _>(
+                unitigs.as_bytes(),
+                [(String::new(), test_tigs.as_bytes())],
+                ConfigBuilder::new(2)
+                    .log_level(LevelFilter::Debug)
+                    .panic_on_parse_error(true)
+                    .gap(Some(1))
+                    .build(),
+            );
+            assert_eq!(result, expected_result, "{unitigs:?} {test_tigs:?}");
+        }
+
+        // Paired kmers cannot be extended by a character, since the gap is not stored.
+        for config in [
+            ConfigBuilder::new(2).gap(Some(1)).count_k_plus_1(true),
+            ConfigBuilder::new(2)
+                .gap(Some(1))
+                .allow_cuttlefish2_errors(true),
+        ] {
+            let result = compare_kmer_sets::<PairedKmer<BitPackedVectorKmer>, _>(
+                ">a\nACGTT".as_bytes(),
+                [(String::new(), ">\nACGTT\n".as_bytes())],
+                config.build(),
+            );
+            assert_eq!(result, Err(Error::IncompatibleArguments));
+        }
+    }
+
+    #[test]
+    fn test_assume_sorted() {
+        initialise_logging(LevelFilter::Debug);
+        let unitigs = ">\nAAA\n>\nAAC\n>\nACG\n";
+
+        for (test_tigs, expect_ok) in [
+            (">\nAAA\n>\nGTT\n>\nCGT\n", true),
+            (">\nAAA\n>\nACG\n", false),
+            (">\nACG\n>\nAAA\n>\nAAC\n", false),
+        ] {
+            let result = compare_kmer_sets::<BitPackedKmer<3, u8>, _>(
+                unitigs.as_bytes(),
+                [(String::new(), test_tigs.as_bytes())],
+                ConfigBuilder::new(3)
+                    .log_level(LevelFilter::Debug)
+                    .panic_on_parse_error(true)
+                    .assume_sorted(true)
+                    .build(),
+            );
+
+            assert_eq!(result.is_ok(), expect_ok, "{test_tigs}: {result:?}");
+        }
+    }
+
+    #[test]
+    fn test_sorted_window() {
+        initialise_logging(LevelFilter::Debug);
+        let unitigs = ">\nAAA\n>\nAAC\n>\nACG\n";
+        // AAA is preceded by the two larger kmers AAC and ACG.
+        let test_tigs = ">\nAAC\n>\nACG\n>\nAAA\n";
+
+        for (sorted_window, expected_result) in [
+            (None, Err(Error::UnsortedInput)),
+            (Some(2), Err(Error::UnsortedInput)),
+            (Some(3), Ok(())),
+            (Some(0), Err(Error::IncompatibleArguments)),
+        ] {
+            let result = compare_kmer_sets::<BitPackedKmer<3, u8>, _>(
+                unitigs.as_bytes(),
+                [(String::new(), test_tigs.as_bytes())],
+                ConfigBuilder::new(3)
+                    .log_level(LevelFilter::Debug)
+                    .panic_on_parse_error(true)
+                    .assume_sorted(sorted_window.is_none())
+                    .sorted_window(sorted_window)
+                    .build(),
+            );
+
+            assert_eq!(result, expected_result, "sorted_window: {sorted_window:?}");
+        }
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn test_parallel_sort() {
+        use crate::{sort_kmers, Kmer};
+        use clap::Parser;
+
+        let mut state = 1u32;
+        let sequence: Vec<_> = (0..100_000)
+            .map(|_| {
+                state = state.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+                b"ACGT"[(state >> 30) as usize]
+            })
+            .collect();
+        let kmers: Vec<_> = sequence
+            .windows(7)
+            .map(|window| BitPackedKmer::<7, u16>::from_iter(window.iter().copied()).canonical())
+            .collect();
+
+        let mut sequential_kmers = kmers.clone();
+        sort_kmers(
+            &mut sequential_kmers,
+            &Config::parse_from(["", "-k", "7", "--threads", "1", "a", "b"]),
+        );
+        sequential_kmers.dedup();
+
+        let mut parallel_kmers = kmers;
+        sort_kmers(
+            &mut parallel_kmers,
+            &Config::parse_from(["", "-k", "7", "--threads", "0", "a", "b"]),
+        );
+        parallel_kmers.dedup();
+
+        assert_eq!(sequential_kmers, parallel_kmers);
+    }
+}
@@ -0,0 +1,525 @@
+use log::{debug, info};
+use std::borrow::Borrow;
+use std::cmp::{Ordering, Reverse};
+use std::collections::{BTreeMap, BinaryHeap};
+use std::fmt::Display;
+use std::iter::Peekable;
+
+/// The numbers of kmers found in only one or in both of the two merged kmer sets.
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq)]
+pub struct MergeCounts {
+    /// The number of unitig kmers that are missing in the test tigs, including excused kmers.
+    pub superfluous_unitig_kmer_count: usize,
+    /// The number of unitig kmers that are missing in the test tigs,
+    /// but are contained in the set of excused kmers.
+    pub excused_unitig_kmer_count: usize,
+    /// The number of test tig kmers that are missing in the unitigs.
+    pub superfluous_test_tig_kmer_count: usize,
+    /// The number of kmers contained in both the unitigs and the test tigs.
+    pub shared_kmer_count: usize,
+    /// The numbers of shared kmers with different multiplicities, if the multiplicities were compared.
+    pub multiplicity_mismatches: Option<MultiplicityMismatchCounts>,
+}
+
+/// The numbers of kmers contained in both merged kmer multisets, but with different multiplicities.
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq)]
+pub struct MultiplicityMismatchCounts {
+    /// The number of shared kmers that occur less often in the test tigs than in the unitigs.
+    pub lower_in_test_tigs_count: usize,
+    /// The number of shared kmers that occur more often in the test tigs than in the unitigs.
+    pub higher_in_test_tigs_count: usize,
+}
+
+impl MultiplicityMismatchCounts {
+    /// The number of shared kmers with different multiplicities.
+    pub fn total(&self) -> usize {
+        self.lower_in_test_tigs_count + self.higher_in_test_tigs_count
+    }
+}
+
+/// Which of the two merged kmer sets lacks a mismatching kmer.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum MismatchKind {
+    /// The kmer is contained in the unitigs, but missing in the test tigs.
+    MissingInTest,
+    /// The kmer is contained in the test tigs, but missing in the unitigs.
+    ExtraInTest,
+}
+
+/// A kmer that is contained in only one of the two merged kmer sets, as passed to the visitor of [`merge_kmers`].
+///
+/// The kmer is canonical, unless the kmers are compared with `--strand-specific`.
+#[derive(Debug, Eq, PartialEq)]
+pub struct MismatchEvent<'kmer, KmerType> {
+    pub kmer: &'kmer KmerType,
+    pub kind: MismatchKind,
+}
+
+/// For each mismatching kmer, the length of the longest prefix it shares with a kmer of the other kmer set.
+///
+/// Since both kmer sets are sorted, this is the longer of the prefixes shared with its two neighbours in the other set.
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct MismatchAnalysis {
+    /// For each prefix length, the number of unexcused unitig kmers missing in the test tigs.
+    pub superfluous_unitig_kmers: BTreeMap<usize, usize>,
+    /// For each prefix length, the number of test tig kmers missing in the unitigs.
+    pub superfluous_test_tig_kmers: BTreeMap<usize, usize>,
+}
+
+impl MismatchAnalysis {
+    fn record(
+        histogram: &mut BTreeMap<usize, usize>,
+        kmer: &impl Display,
+        neighbours: [Option<&impl Display>; 2],
+    ) {
+        let kmer = kmer.to_string();
+        let prefix_length = neighbours
+            .into_iter()
+            .flatten()
+            .map(|neighbour| {
+                kmer.bytes()
+                    .zip(neighbour.to_string().bytes())
+                    .take_while(|(a, b)| a == b)
+                    .count()
+            })
+            .max()
+            .unwrap_or(0);
+        *histogram.entry(prefix_length).or_default() += 1;
+    }
+
+    /// Log the histograms at info level.
+    pub fn log(&self, test_tigs_name: &str) {
+        info!("Longest prefixes shared by mismatching kmers of test tigs {test_tigs_name} with the other kmer set:");
+        for (prefix_length, count) in &self.superfluous_unitig_kmers {
+            info!("Prefix length {prefix_length}: {count} unitig kmers missing in test tigs");
+        }
+        for (prefix_length, count) in &self.superfluous_test_tig_kmers {
+            info!("Prefix length {prefix_length}: {count} test tig kmers missing in unitigs");
+        }
+    }
+}
+
+/// Merge two sorted sequences of unique kmers and count the kmers that occur in only one of them.
+///
+/// Unitig kmers missing in the test tigs that are contained in `excused_unitig_kmers` are counted separately.
+/// All other mismatching kmers are passed to `on_mismatch`, and recorded in `mismatch_analysis`, if given.
+/// At most `mismatch_log_limit` mismatching kmers of each side are logged individually.
+pub fn merge_kmers<KmerType: Ord + Display, Item: Borrow<KmerType>>(
+    unitig_kmers: impl Iterator<Item = Item>,
+    test_tig_kmers: impl Iterator<Item = Item>,
+    excused_unitig_kmers: &[KmerType],
+    mut on_mismatch: impl FnMut(MismatchEvent<KmerType>),
+    mismatch_log_limit: usize,
+    mut mismatch_analysis: Option<&mut MismatchAnalysis>,
+) -> MergeCounts {
+    let mut unitig_kmer_iterator = unitig_kmers.peekable();
+    let mut test_tig_kmer_iterator = test_tig_kmers.peekable();
+    let mut counts = MergeCounts::default();
+    // The last kmers taken from each side, which are the predecessors of mismatching kmers of the other side.
+    let mut previous_unitig_kmer: Option<Item> = None;
+    let mut previous_test_tig_kmer: Option<Item> = None;
+
+    loop {
+        // Once one side is exhausted, all remaining kmers of the other side are mismatches.
+        let unitig_kmer = unitig_kmer_iterator.peek().map(Borrow::borrow);
+        let test_tig_kmer = test_tig_kmer_iterator.peek().map(Borrow::borrow);
+        let ordering = match (unitig_kmer, test_tig_kmer) {
+            (Some(unitig_kmer), Some(test_tig_kmer)) => unitig_kmer.cmp(test_tig_kmer),
+            (Some(_), None) => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+            (None, None) => break,
+        };
+
+        match ordering {
+            Ordering::Less => {
+                let unitig_kmer = unitig_kmer.unwrap();
+                counts.superfluous_unitig_kmer_count += 1;
+                if excused_unitig_kmers.binary_search(unitig_kmer).is_ok() {
+                    counts.excused_unitig_kmer_count += 1;
+                } else {
+                    if counts.superfluous_unitig_kmer_count - counts.excused_unitig_kmer_count
+                        <= mismatch_log_limit
+                    {
+                        debug!("Unitigs contain kmer that is missing in test tigs: {unitig_kmer}");
+                    }
+                    on_mismatch(MismatchEvent {
+                        kmer: unitig_kmer,
+                        kind: MismatchKind::MissingInTest,
+                    });
+                    if let Some(mismatch_analysis) = mismatch_analysis.as_mut() {
+                        MismatchAnalysis::record(
+                            &mut mismatch_analysis.superfluous_unitig_kmers,
+                            unitig_kmer,
+                            [
+                                previous_test_tig_kmer.as_ref().map(Borrow::borrow),
+                                test_tig_kmer,
+                            ],
+                        );
+                    }
+                }
+                previous_unitig_kmer = unitig_kmer_iterator.next();
+            }
+            Ordering::Equal => {
+                counts.shared_kmer_count += 1;
+                previous_unitig_kmer = unitig_kmer_iterator.next();
+                previous_test_tig_kmer = test_tig_kmer_iterator.next();
+            }
+            Ordering::Greater => {
+                let test_tig_kmer = test_tig_kmer.unwrap();
+                counts.superfluous_test_tig_kmer_count += 1;
+                if counts.superfluous_test_tig_kmer_count <= mismatch_log_limit {
+                    debug!("Test tigs contains kmer that is missing in unitigs: {test_tig_kmer}");
+                }
+                on_mismatch(MismatchEvent {
+                    kmer: test_tig_kmer,
+                    kind: MismatchKind::ExtraInTest,
+                });
+                if let Some(mismatch_analysis) = mismatch_analysis.as_mut() {
+                    MismatchAnalysis::record(
+                        &mut mismatch_analysis.superfluous_test_tig_kmers,
+                        test_tig_kmer,
+                        [
+                            previous_unitig_kmer.as_ref().map(Borrow::borrow),
+                            unitig_kmer,
+                        ],
+                    );
+                }
+                previous_test_tig_kmer = test_tig_kmer_iterator.next();
+            }
+        }
+    }
+
+    let unexcused_unitig_kmer_count =
+        counts.superfluous_unitig_kmer_count - counts.excused_unitig_kmer_count;
+    if unexcused_unitig_kmer_count > mismatch_log_limit {
+        debug!(
+            "... and {} more kmers that unitigs contain but test tigs miss",
+            unexcused_unitig_kmer_count - mismatch_log_limit
+        );
+    }
+    if counts.superfluous_test_tig_kmer_count > mismatch_log_limit {
+        debug!(
+            "... and {} more kmers that test tigs contain but unitigs miss",
+            counts.superfluous_test_tig_kmer_count - mismatch_log_limit
+        );
+    }
+
+    counts
+}
+
+/// Merge two sorted sequences of unique kmers and count the kmers that occur in both, but with different multiplicities.
+///
+/// The multiplicities are given in the same order as the kmers.
+/// Kmers that occur in only one of the sequences are ignored, since [`merge_kmers`] counts them.
+/// At most `mismatch_log_limit` kmers with different multiplicities are logged individually.
+pub fn merge_multiplicities<KmerType: Ord + Display>(
+    unitig_kmers: &[KmerType],
+    unitig_multiplicities: &[usize],
+    test_tig_kmers: &[KmerType],
+    test_tig_multiplicities: &[usize],
+    mismatch_log_limit: usize,
+) -> MultiplicityMismatchCounts {
+    debug_assert_eq!(unitig_kmers.len(), unitig_multiplicities.len());
+    debug_assert_eq!(test_tig_kmers.len(), test_tig_multiplicities.len());
+
+    let mut counts = MultiplicityMismatchCounts::default();
+    let (mut unitig_index, mut test_tig_index) = (0, 0);
+    while unitig_index < unitig_kmers.len() && test_tig_index < test_tig_kmers.len() {
+        let kmer = &unitig_kmers[unitig_index];
+        match kmer.cmp(&test_tig_kmers[test_tig_index]) {
+            Ordering::Less => unitig_index += 1,
+            Ordering::Greater => test_tig_index += 1,
+            Ordering::Equal => {
+                let unitig_multiplicity = unitig_multiplicities[unitig_index];
+                let test_tig_multiplicity = test_tig_multiplicities[test_tig_index];
+                match unitig_multiplicity.cmp(&test_tig_multiplicity) {
+                    Ordering::Greater => counts.lower_in_test_tigs_count += 1,
+                    Ordering::Less => counts.higher_in_test_tigs_count += 1,
+                    Ordering::Equal => {}
+                }
+                if unitig_multiplicity != test_tig_multiplicity
+                    && counts.total() <= mismatch_log_limit
+                {
+                    debug!("Kmer {kmer} occurs {unitig_multiplicity} times in unitigs, but {test_tig_multiplicity} times in test tigs");
+                }
+                unitig_index += 1;
+                test_tig_index += 1;
+            }
+        }
+    }
+
+    if counts.total() > mismatch_log_limit {
+        debug!(
+            "... and {} more kmers with different multiplicities",
+            counts.total() - mismatch_log_limit
+        );
+    }
+
+    counts
+}
+
+/// An iterator adapter that removes consecutive duplicates from a sorted sequence of kmers,
+/// and stops if the sequence is not sorted.
+pub struct SortedUniqueKmers<Iter: Iterator> {
+    iter: Peekable<Iter>,
+    out_of_order_kmers: Option<(Iter::Item, Iter::Item)>,
+}
+
+impl<Iter: Iterator> SortedUniqueKmers<Iter> {
+    pub fn new(iter: Iter) -> Self {
+        Self {
+            iter: iter.peekable(),
+            out_of_order_kmers: None,
+        }
+    }
+
+    /// Returns the first pair of consecutive kmers that was found to be out of order, if any.
+    pub fn out_of_order_kmers(&self) -> Option<&(Iter::Item, Iter::Item)> {
+        self.out_of_order_kmers.as_ref()
+    }
+}
+
+impl<Iter: Iterator<Item: Ord>> Iterator for SortedUniqueKmers<Iter> {
+    type Item = Iter::Item;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.out_of_order_kmers.is_some() {
+            return None;
+        }
+
+        let kmer = self.iter.next()?;
+        while let Some(next_kmer) = self.iter.peek() {
+            match kmer.cmp(next_kmer) {
+                Ordering::Less => break,
+                Ordering::Equal => {
+                    self.iter.next().unwrap();
+                }
+                Ordering::Greater => {
+                    let next_kmer = self.iter.next().unwrap();
+                    self.out_of_order_kmers = Some((kmer, next_kmer));
+                    return None;
+                }
+            }
+        }
+
+        Some(kmer)
+    }
+}
+
+/// An iterator adapter that sorts a nearly sorted sequence of kmers using a window of a bounded number of kmers.
+///
+/// The window is filled from the inner iterator, and the smallest kmer in the window is returned each time.
+/// Hence, the output is sorted if no kmer is preceded by `window_size` or more larger kmers in the input.
+/// Otherwise, the output is not sorted, which is detected by wrapping this in [`SortedUniqueKmers`].
+/// With a window size of one, the kmers are returned unchanged.
+pub struct SortingWindow<Iter: Iterator> {
+    iter: Iter,
+    window: BinaryHeap<Reverse<Iter::Item>>,
+    window_size: usize,
+}
+
+impl<Iter: Iterator<Item: Ord>> SortingWindow<Iter> {
+    pub fn new(iter: Iter, window_size: usize) -> Self {
+        // A window size of zero is rejected when checking the arguments.
+        debug_assert!(window_size > 0, "the window size must be positive");
+        Self {
+            iter,
+            window: BinaryHeap::with_capacity(window_size),
+            window_size,
+        }
+    }
+}
+
+impl<Iter: Iterator<Item: Ord>> Iterator for SortingWindow<Iter> {
+    type Item = Iter::Item;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.window.len() < self.window_size {
+            let Some(kmer) = self.iter.next() else {
+                break;
+            };
+            self.window.push(Reverse(kmer));
+        }
+
+        self.window.pop().map(|Reverse(kmer)| kmer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::merge::{
+        merge_kmers, merge_multiplicities, MergeCounts, MismatchAnalysis, MismatchKind,
+        MultiplicityMismatchCounts, SortedUniqueKmers, SortingWindow,
+    };
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn test_sorted_unique_kmers() {
+        let mut iter = SortedUniqueKmers::new([1, 2, 2, 3, 5, 5].into_iter());
+        assert_eq!(iter.by_ref().collect::<Vec<_>>(), vec![1, 2, 3, 5]);
+        assert_eq!(iter.out_of_order_kmers(), None);
+
+        let mut iter = SortedUniqueKmers::new([1, 2, 4, 3, 5].into_iter());
+        assert_eq!(iter.by_ref().collect::<Vec<_>>(), vec![1, 2]);
+        assert_eq!(iter.out_of_order_kmers(), Some(&(4, 3)));
+    }
+
+    #[test]
+    fn test_sorting_window() {
+        let input = [2, 1, 3, 5, 4, 4, 6];
+        assert_eq!(
+            SortingWindow::new(input.into_iter(), 1).collect::<Vec<_>>(),
+            input
+        );
+        assert_eq!(
+            SortingWindow::new(input.into_iter(), 2).collect::<Vec<_>>(),
+            vec![1, 2, 3, 4, 4, 5, 6]
+        );
+
+        // The 1 is preceded by three larger kmers, which exceeds a window of three.
+        let mut iter = SortedUniqueKmers::new(SortingWindow::new([2, 3, 4, 1, 5].into_iter(), 3));
+        assert_eq!(iter.by_ref().collect::<Vec<_>>(), Vec::<i32>::new());
+        assert_eq!(iter.out_of_order_kmers(), Some(&(2, 1)));
+        let iter = SortedUniqueKmers::new(SortingWindow::new([2, 3, 4, 1, 5].into_iter(), 4));
+        assert_eq!(iter.collect::<Vec<_>>(), vec![1, 2, 3, 4, 5]);
+    }
+
+    #[test]
+    fn test_merge_kmers() {
+        // The log limit only affects logging, so both limits yield the same counts.
+        for mismatch_log_limit in [100, 0] {
+            let counts = merge_kmers::<i32, _>(
+                [1, 2, 4, 6, 8].iter(),
+                [2, 3, 4, 8].iter(),
+                &[6],
+                |_| {},
+                mismatch_log_limit,
+                None,
+            );
+            assert_eq!(
+                counts,
+                MergeCounts {
+                    superfluous_unitig_kmer_count: 2,
+                    excused_unitig_kmer_count: 1,
+                    superfluous_test_tig_kmer_count: 1,
+                    shared_kmer_count: 3,
+                    multiplicity_mismatches: None,
+                }
+            );
+        }
+    }
+
+    #[test]
+    fn test_mismatch_events() {
+        let mut mismatches = Vec::new();
+        merge_kmers::<i32, _>(
+            [1, 2, 4, 6, 8].iter(),
+            [2, 3, 4, 8, 9].iter(),
+            &[6],
+            |event| mismatches.push((*event.kmer, event.kind)),
+            100,
+            None,
+        );
+        // The excused kmer 6 is not a mismatch.
+        assert_eq!(
+            mismatches,
+            [
+                (1, MismatchKind::MissingInTest),
+                (3, MismatchKind::ExtraInTest),
+                (9, MismatchKind::ExtraInTest),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_merge_multiplicities() {
+        for mismatch_log_limit in [100, 0] {
+            let counts = merge_multiplicities(
+                &[1, 2, 4, 6, 8],
+                &[1, 3, 2, 1, 1],
+                &[2, 3, 4, 6, 8],
+                &[3, 1, 1, 2, 1],
+                mismatch_log_limit,
+            );
+            assert_eq!(
+                counts,
+                MultiplicityMismatchCounts {
+                    lower_in_test_tigs_count: 1,
+                    higher_in_test_tigs_count: 1,
+                }
+            );
+            assert_eq!(counts.total(), 2);
+        }
+    }
+
+    #[test]
+    fn test_merge_kmers_with_trailing_mismatches() {
+        for (unitig_kmers, test_tig_kmers, expected_counts) in [
+            (
+                &[1, 2, 3, 5, 7][..],
+                &[1, 2][..],
+                MergeCounts {
+                    superfluous_unitig_kmer_count: 3,
+                    excused_unitig_kmer_count: 1,
+                    superfluous_test_tig_kmer_count: 0,
+                    shared_kmer_count: 2,
+                    multiplicity_mismatches: None,
+                },
+            ),
+            (
+                &[1, 2],
+                &[1, 2, 3, 5, 7],
+                MergeCounts {
+                    superfluous_unitig_kmer_count: 0,
+                    excused_unitig_kmer_count: 0,
+                    superfluous_test_tig_kmer_count: 3,
+                    shared_kmer_count: 2,
+                    multiplicity_mismatches: None,
+                },
+            ),
+            (
+                &[],
+                &[3, 5],
+                MergeCounts {
+                    superfluous_test_tig_kmer_count: 2,
+                    ..MergeCounts::default()
+                },
+            ),
+        ] {
+            let counts = merge_kmers::<i32, _>(
+                unitig_kmers.iter(),
+                test_tig_kmers.iter(),
+                &[5],
+                |_| {},
+                100,
+                None,
+            );
+            assert_eq!(
+                counts, expected_counts,
+                "{unitig_kmers:?} {test_tig_kmers:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_mismatch_analysis() {
+        let mut mismatch_analysis = MismatchAnalysis::default();
+        let counts = merge_kmers::<&str, _>(
+            ["AAC", "ACG", "CGC", "GGT", "TTT"].iter(),
+            ["AAA", "ACG", "CCA", "GGT", "TTT"].iter(),
+            &[],
+            |_| {},
+            100,
+            Some(&mut mismatch_analysis),
+        );
+        assert_eq!(counts.superfluous_unitig_kmer_count, 2);
+        assert_eq!(counts.superfluous_test_tig_kmer_count, 2);
+        assert_eq!(
+            mismatch_analysis,
+            MismatchAnalysis {
+                superfluous_unitig_kmers: BTreeMap::from([(1, 1), (2, 1)]),
+                superfluous_test_tig_kmers: BTreeMap::from([(1, 1), (2, 1)]),
+            }
+        );
+    }
+}
@@ -0,0 +1,445 @@
+use crate::fingerprint::KmerSetFingerprint;
+use std::collections::BTreeMap;
+use std::io::Write;
+
+/// The statistics and the outcome of a single verification run.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "json", derive(serde::Serialize))]
+pub struct VerificationReport {
+    pub test_tigs: String,
+    /// Which strands of the kmers were compared.
+    ///
+    /// Only given with `--both-strands-report`, to tell the two reports apart.
+    #[cfg_attr(feature = "json", serde(skip_serializing_if = "Option::is_none"))]
+    pub strands: Option<Strands>,
+    pub ground_truth_size: usize,
+    pub test_size: usize,
+    pub ground_truth_str_cnt: usize,
+    pub test_str_cnt: usize,
+    pub compression_rate: f64,
+    pub str_cnt_rate: f64,
+    /// The fraction of G and C characters among the A, C, G and T characters of the unitigs.
+    ///
+    /// Only computed with `--report-gc-content`.
+    #[cfg_attr(feature = "json", serde(skip_serializing_if = "Option::is_none"))]
+    pub ground_truth_gc: Option<f64>,
+    /// The fraction of G and C characters among the A, C, G and T characters of the test tigs.
+    ///
+    /// Only computed with `--report-gc-content`.
+    #[cfg_attr(feature = "json", serde(skip_serializing_if = "Option::is_none"))]
+    pub test_gc: Option<f64>,
+    /// The number of kmer positions in the unitigs, including duplicate kmers.
+    ///
+    /// Despite its name, this equals `kmer_positions`, and is kept for compatibility.
+    pub unique_kmer_count: usize,
+    /// The number of kmer positions in the unitigs,
+    /// i.e. their character count minus `k - 1` for each sequence, including duplicate kmers.
+    pub kmer_positions: usize,
+    /// The number of distinct unitig kmers, which are canonical unless `--strand-specific` is given.
+    ///
+    /// Only computed if the kmers are collected, i.e. not with `--assume-sorted` or `--do-not-verify`.
+    #[cfg_attr(feature = "json", serde(skip_serializing_if = "Option::is_none"))]
+    pub distinct_canonical_kmers: Option<usize>,
+    /// The number of unitig kmers that are missing in the test tigs, including excused kmers.
+    ///
+    /// Unknown if the test tigs were rejected by `--fail-fast` before they were merged with the unitigs.
+    #[cfg_attr(feature = "json", serde(skip_serializing_if = "Option::is_none"))]
+    pub superfluous_unitig_kmer_count: Option<usize>,
+    /// The number of unitig kmers that are missing in the test tigs,
+    /// but are excused because cuttlefish2 errors are allowed.
+    ///
+    /// Unknown if the test tigs were rejected by `--fail-fast`.
+    #[cfg_attr(feature = "json", serde(skip_serializing_if = "Option::is_none"))]
+    pub excused_unitig_kmer_count: Option<usize>,
+    /// The number of test tig kmers that are missing in the unitigs.
+    ///
+    /// Unknown if the test tigs were rejected by `--fail-fast`.
+    #[cfg_attr(feature = "json", serde(skip_serializing_if = "Option::is_none"))]
+    pub superfluous_test_tig_kmer_count: Option<usize>,
+    /// The Jaccard index of the unitig kmers and the test tig kmers,
+    /// i.e. the number of shared kmers divided by the number of kmers contained in any of the two.
+    ///
+    /// Unknown if the test tigs were rejected by `--fail-fast`.
+    #[cfg_attr(feature = "json", serde(skip_serializing_if = "Option::is_none"))]
+    pub jaccard: Option<f64>,
+    /// The number of distinct unitig kmers that are their own reverse complement.
+    ///
+    /// Only computed if the kmers are collected, i.e. not with `--assume-sorted` or `--do-not-verify`.
+    #[cfg_attr(feature = "json", serde(skip_serializing_if = "Option::is_none"))]
+    pub unitig_palindrome_count: Option<usize>,
+    /// The number of distinct test tig kmers that are their own reverse complement.
+    ///
+    /// Only computed if the kmers are collected, i.e. not with `--assume-sorted` or `--do-not-verify`.
+    #[cfg_attr(feature = "json", serde(skip_serializing_if = "Option::is_none"))]
+    pub test_tig_palindrome_count: Option<usize>,
+    /// An order-independent fingerprint of the distinct unitig kmers.
+    ///
+    /// Only computed if the kmers are collected, i.e. not with `--assume-sorted` or `--do-not-verify`.
+    #[cfg_attr(feature = "json", serde(skip_serializing_if = "Option::is_none"))]
+    pub unitig_kmer_set_fingerprint: Option<KmerSetFingerprint>,
+    /// An order-independent fingerprint of the distinct test tig kmers.
+    ///
+    /// Only computed if the kmers are collected, i.e. not with `--assume-sorted` or `--do-not-verify`.
+    #[cfg_attr(feature = "json", serde(skip_serializing_if = "Option::is_none"))]
+    pub test_tig_kmer_set_fingerprint: Option<KmerSetFingerprint>,
+    /// The number of distinct (k+1)-mers whose prefix and suffix kmers are both unitig kmers.
+    ///
+    /// Only computed with `--count-k-plus-1`.
+    #[cfg_attr(feature = "json", serde(skip_serializing_if = "Option::is_none"))]
+    pub distinct_k_plus_1: Option<usize>,
+    /// The number of kmers shared by the unitigs and the test tigs that occur with different multiplicities.
+    ///
+    /// Only computed with `--multiset`.
+    #[cfg_attr(feature = "json", serde(skip_serializing_if = "Option::is_none"))]
+    pub multiplicity_mismatch_count: Option<usize>,
+    /// For each multiplicity, the number of distinct test tig kmers that occur this often.
+    ///
+    /// Empty unless `--multiplicity-histogram` is given.
+    #[cfg_attr(feature = "json", serde(skip_serializing_if = "BTreeMap::is_empty"))]
+    pub multiplicity_histogram: BTreeMap<usize, usize>,
+    pub status: VerificationStatus,
+}
+
+/// The size statistics of a single input file, computed without collecting its kmers.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "json", derive(serde::Serialize))]
+pub struct ScanReport {
+    pub input: String,
+    pub character_count: usize,
+    pub sequence_count: usize,
+    /// The number of kmers including duplicates.
+    pub kmer_count: usize,
+}
+
+/// The projected peak memory of verifying the inputs, computed from their kmer counts without collecting their kmers.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "json", derive(serde::Serialize))]
+pub struct MemoryEstimateReport {
+    /// The number of kmers of the first input file, including duplicates.
+    pub unitig_kmer_count: usize,
+    /// The largest number of kmers of any test tig file, including duplicates.
+    pub max_test_tig_kmer_count: usize,
+    /// The estimated peak memory in bytes with the kmer type chosen for this kmer size.
+    pub estimated_memory_bytes: usize,
+    /// The estimated peak memory in bytes with the vector kmer type that is used for kmers larger than 128.
+    ///
+    /// Only given if the kmer type chosen for this kmer size is a different one.
+    #[cfg_attr(feature = "json", serde(skip_serializing_if = "Option::is_none"))]
+    pub vector_kmer_estimated_memory_bytes: Option<usize>,
+}
+
+/// The outcome of comparing the kmer content of the test tigs against the unitigs.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+#[cfg_attr(feature = "json", derive(serde::Serialize))]
+#[cfg_attr(feature = "json", serde(rename_all = "snake_case"))]
+pub enum VerificationStatus {
+    /// The kmer sets are considered equal.
+    Match,
+    /// The test tigs miss kmers that are present in the unitigs.
+    MissingKmers,
+    /// The test tigs contain kmers that are not present in the unitigs.
+    SuperfluousKmers,
+    /// The test tigs both miss kmers and contain kmers that are not present in the unitigs.
+    MissingAndSuperfluousKmers,
+}
+
+/// Which strands of the kmers are compared.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+#[cfg_attr(feature = "json", derive(serde::Serialize))]
+#[cfg_attr(feature = "json", serde(rename_all = "snake_case"))]
+pub enum Strands {
+    /// Each kmer is identified with its reverse complement.
+    Canonical,
+    /// Kmers are compared as they are.
+    Forward,
+}
+
+/// The format in which the verification report is printed.
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq, clap::ValueEnum)]
+pub enum ReportFormat {
+    /// Human-readable `key: value` lines.
+    #[default]
+    Plain,
+    /// A single JSON object.
+    #[cfg(feature = "json")]
+    Json,
+}
+
+impl ScanReport {
+    pub fn write(&self, format: ReportFormat, output: impl Write) -> std::io::Result<()> {
+        match format {
+            ReportFormat::Plain => self.write_plain(output),
+            #[cfg(feature = "json")]
+            ReportFormat::Json => self.write_json(output),
+        }
+    }
+
+    fn write_plain(&self, mut output: impl Write) -> std::io::Result<()> {
+        writeln!(output, "input: {}", self.input)?;
+        writeln!(output, "character_count: {}", self.character_count)?;
+        writeln!(output, "sequence_count: {}", self.sequence_count)?;
+        writeln!(output, "kmer_count: {}", self.kmer_count)?;
+        Ok(())
+    }
+
+    #[cfg(feature = "json")]
+    fn write_json(&self, mut output: impl Write) -> std::io::Result<()> {
+        serde_json::to_writer(&mut output, self)?;
+        writeln!(output)
+    }
+}
+
+impl MemoryEstimateReport {
+    pub fn write(&self, format: ReportFormat, output: impl Write) -> std::io::Result<()> {
+        match format {
+            ReportFormat::Plain => self.write_plain(output),
+            #[cfg(feature = "json")]
+            ReportFormat::Json => self.write_json(output),
+        }
+    }
+
+    fn write_plain(&self, mut output: impl Write) -> std::io::Result<()> {
+        writeln!(output, "unitig_kmer_count: {}", self.unitig_kmer_count)?;
+        writeln!(
+            output,
+            "max_test_tig_kmer_count: {}",
+            self.max_test_tig_kmer_count
+        )?;
+        writeln!(
+            output,
+            "estimated_memory_bytes: {}",
+            self.estimated_memory_bytes
+        )?;
+        if let Some(vector_kmer_estimated_memory_bytes) = self.vector_kmer_estimated_memory_bytes {
+            writeln!(
+                output,
+                "vector_kmer_estimated_memory_bytes: {vector_kmer_estimated_memory_bytes}"
+            )?;
+        }
+        Ok(())
+    }
+
+    #[cfg(feature = "json")]
+    fn write_json(&self, mut output: impl Write) -> std::io::Result<()> {
+        serde_json::to_writer(&mut output, self)?;
+        writeln!(output)
+    }
+}
+
+impl VerificationReport {
+    pub fn write(&self, format: ReportFormat, output: impl Write) -> std::io::Result<()> {
+        match format {
+            ReportFormat::Plain => self.write_plain(output),
+            #[cfg(feature = "json")]
+            ReportFormat::Json => self.write_json(output),
+        }
+    }
+
+    fn write_plain(&self, mut output: impl Write) -> std::io::Result<()> {
+        writeln!(output, "test_tigs: {}", self.test_tigs)?;
+        match self.strands {
+            Some(Strands::Canonical) => writeln!(output, "strands: canonical")?,
+            Some(Strands::Forward) => writeln!(output, "strands: forward")?,
+            None => {}
+        }
+        writeln!(output, "ground_truth_size:   {}", self.ground_truth_size)?;
+        writeln!(output, "test_size: {}", self.test_size)?;
+
+        writeln!(
+            output,
+            "ground_truth_str_cnt:   {}",
+            self.ground_truth_str_cnt
+        )?;
+        writeln!(output, "test_str_cnt: {}", self.test_str_cnt)?;
+
+        writeln!(output, "compression_rate: {}", self.compression_rate)?;
+        writeln!(output, "str_cnt_rate: {}", self.str_cnt_rate)?;
+        if let Some(ground_truth_gc) = self.ground_truth_gc {
+            writeln!(output, "ground_truth_gc: {ground_truth_gc}")?;
+        }
+        if let Some(test_gc) = self.test_gc {
+            writeln!(output, "test_gc: {test_gc}")?;
+        }
+
+        writeln!(output, "unique_kmer_count: {}", self.unique_kmer_count)?;
+        writeln!(output, "kmer_positions: {}", self.kmer_positions)?;
+        if let Some(distinct_canonical_kmers) = self.distinct_canonical_kmers {
+            writeln!(
+                output,
+                "distinct_canonical_kmers: {distinct_canonical_kmers}"
+            )?;
+        }
+
+        if let Some(superfluous_unitig_kmer_count) = self.superfluous_unitig_kmer_count {
+            writeln!(
+                output,
+                "superfluous_unitig_kmer_count: {superfluous_unitig_kmer_count}"
+            )?;
+        }
+        if let Some(excused_unitig_kmer_count) = self.excused_unitig_kmer_count {
+            writeln!(
+                output,
+                "excused_unitig_kmer_count: {excused_unitig_kmer_count}"
+            )?;
+        }
+        if let Some(superfluous_test_tig_kmer_count) = self.superfluous_test_tig_kmer_count {
+            writeln!(
+                output,
+                "superfluous_test_tig_kmer_count: {superfluous_test_tig_kmer_count}"
+            )?;
+        }
+        if let Some(jaccard) = self.jaccard {
+            writeln!(output, "jaccard: {jaccard}")?;
+        }
+
+        if let Some(unitig_palindrome_count) = self.unitig_palindrome_count {
+            writeln!(output, "unitig_palindrome_count: {unitig_palindrome_count}")?;
+        }
+        if let Some(test_tig_palindrome_count) = self.test_tig_palindrome_count {
+            writeln!(
+                output,
+                "test_tig_palindrome_count: {test_tig_palindrome_count}"
+            )?;
+        }
+        if let Some(fingerprint) = self.unitig_kmer_set_fingerprint {
+            writeln!(output, "unitig_kmer_set_fingerprint: {fingerprint}")?;
+        }
+        if let Some(fingerprint) = self.test_tig_kmer_set_fingerprint {
+            writeln!(output, "test_tig_kmer_set_fingerprint: {fingerprint}")?;
+        }
+        if let Some(distinct_k_plus_1) = self.distinct_k_plus_1 {
+            writeln!(output, "distinct_k_plus_1: {distinct_k_plus_1}")?;
+        }
+        if let Some(multiplicity_mismatch_count) = self.multiplicity_mismatch_count {
+            writeln!(
+                output,
+                "multiplicity_mismatch_count: {multiplicity_mismatch_count}"
+            )?;
+        }
+
+        for (multiplicity, count) in &self.multiplicity_histogram {
+            writeln!(output, "multiplicity {multiplicity}: {count}")?;
+        }
+        Ok(())
+    }
+
+    #[cfg(feature = "json")]
+    fn write_json(&self, mut output: impl Write) -> std::io::Result<()> {
+        serde_json::to_writer(&mut output, self)?;
+        writeln!(output)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::report::{
+        ReportFormat, ScanReport, Strands, VerificationReport, VerificationStatus,
+    };
+    use std::collections::BTreeMap;
+
+    fn example_report() -> VerificationReport {
+        VerificationReport {
+            test_tigs: "test.fa".to_string(),
+            strands: None,
+            ground_truth_size: 10,
+            test_size: 8,
+            ground_truth_str_cnt: 2,
+            test_str_cnt: 1,
+            compression_rate: 0.8,
+            str_cnt_rate: 0.5,
+            ground_truth_gc: None,
+            test_gc: None,
+            unique_kmer_count: 6,
+            kmer_positions: 6,
+            distinct_canonical_kmers: Some(5),
+            superfluous_unitig_kmer_count: Some(1),
+            excused_unitig_kmer_count: Some(1),
+            superfluous_test_tig_kmer_count: Some(0),
+            jaccard: Some(0.75),
+            unitig_palindrome_count: None,
+            test_tig_palindrome_count: None,
+            unitig_kmer_set_fingerprint: None,
+            test_tig_kmer_set_fingerprint: None,
+            distinct_k_plus_1: None,
+            multiplicity_mismatch_count: None,
+            multiplicity_histogram: BTreeMap::new(),
+            status: VerificationStatus::Match,
+        }
+    }
+
+    #[test]
+    fn test_plain_report() {
+        let mut output = Vec::new();
+        example_report()
+            .write(ReportFormat::Plain, &mut output)
+            .unwrap();
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "test_tigs: test.fa\nground_truth_size:   10\ntest_size: 8\nground_truth_str_cnt:   2\ntest_str_cnt: 1\ncompression_rate: 0.8\nstr_cnt_rate: 0.5\nunique_kmer_count: 6\nkmer_positions: 6\ndistinct_canonical_kmers: 5\nsuperfluous_unitig_kmer_count: 1\nexcused_unitig_kmer_count: 1\nsuperfluous_test_tig_kmer_count: 0\njaccard: 0.75\n"
+        );
+    }
+
+    #[test]
+    fn test_plain_report_multiplicity_histogram() {
+        let mut report = example_report();
+        report.multiplicity_histogram = BTreeMap::from([(1, 5), (3, 1)]);
+        let mut output = Vec::new();
+        report.write(ReportFormat::Plain, &mut output).unwrap();
+        assert!(String::from_utf8(output)
+            .unwrap()
+            .ends_with("jaccard: 0.75\nmultiplicity 1: 5\nmultiplicity 3: 1\n"));
+    }
+
+    #[test]
+    fn test_plain_report_palindrome_counts() {
+        let mut report = example_report();
+        report.unitig_palindrome_count = Some(2);
+        report.test_tig_palindrome_count = Some(1);
+        let mut output = Vec::new();
+        report.write(ReportFormat::Plain, &mut output).unwrap();
+        assert!(String::from_utf8(output).unwrap().ends_with(
+            "jaccard: 0.75\nunitig_palindrome_count: 2\ntest_tig_palindrome_count: 1\n"
+        ));
+    }
+
+    #[test]
+    fn test_plain_report_strands() {
+        let mut report = example_report();
+        report.strands = Some(Strands::Forward);
+        let mut output = Vec::new();
+        report.write(ReportFormat::Plain, &mut output).unwrap();
+        assert!(String::from_utf8(output)
+            .unwrap()
+            .starts_with("test_tigs: test.fa\nstrands: forward\nground_truth_size:"));
+    }
+
+    #[test]
+    fn test_plain_scan_report() {
+        let mut output = Vec::new();
+        ScanReport {
+            input: "test.fa".to_string(),
+            character_count: 10,
+            sequence_count: 2,
+            kmer_count: 6,
+        }
+        .write(ReportFormat::Plain, &mut output)
+        .unwrap();
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "input: test.fa\ncharacter_count: 10\nsequence_count: 2\nkmer_count: 6\n"
+        );
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn test_json_report() {
+        let mut output = Vec::new();
+        example_report()
+            .write(ReportFormat::Json, &mut output)
+            .unwrap();
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "{\"test_tigs\":\"test.fa\",\"ground_truth_size\":10,\"test_size\":8,\"ground_truth_str_cnt\":2,\"test_str_cnt\":1,\"compression_rate\":0.8,\"str_cnt_rate\":0.5,\"unique_kmer_count\":6,\"kmer_positions\":6,\"distinct_canonical_kmers\":5,\"superfluous_unitig_kmer_count\":1,\"excused_unitig_kmer_count\":1,\"superfluous_test_tig_kmer_count\":0,\"jaccard\":0.75,\"status\":\"match\"}\n"
+        );
+    }
+}
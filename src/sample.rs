@@ -0,0 +1,80 @@
+use crate::fingerprint::hash_kmer;
+use crate::kmer::Kmer;
+
+/// Decides deterministically which kmers are kept when verifying only a fraction of the kmers.
+///
+/// The decision depends only on the kmer and the seed,
+/// such that each kmer is either kept in all input files or in none of them.
+/// Like [`crate::fingerprint::KmerSetFingerprint`], it depends on the kmer type, and hence on k.
+#[derive(Debug, Clone)]
+pub struct KmerSampler {
+    /// Kmers are kept if their hash is below this threshold.
+    threshold: u64,
+    seed: u64,
+    /// Reused for encoding the kmers before hashing them.
+    buffer: Vec<u8>,
+}
+
+impl KmerSampler {
+    /// Create a sampler that keeps about the given fraction of all kmers, which must be in the interval `(0, 1)`.
+    pub fn new(fraction: f64, seed: u64) -> Self {
+        debug_assert!(fraction > 0.0 && fraction < 1.0);
+        Self {
+            threshold: (fraction * 2f64.powi(64)) as u64,
+            seed,
+            buffer: Vec::new(),
+        }
+    }
+
+    pub fn keeps(&mut self, kmer: &impl Kmer) -> bool {
+        hash_kmer(kmer, self.seed, &mut self.buffer) < self.threshold
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::kmer::{BitPackedKmer, Kmer};
+    use crate::sample::KmerSampler;
+
+    #[test]
+    fn test_sampler() {
+        let kmers: Vec<_> = (0..1 << 16)
+            .map(|index| {
+                let kmer: Vec<_> = (0..8)
+                    .map(|position| b"ACGT"[(index >> (2 * position)) & 3])
+                    .collect();
+                kmer
+            })
+            .collect();
+
+        for (fraction, seed) in [(0.1, 0), (0.5, 0), (0.5, 1), (0.9, 42)] {
+            let mut sampler = KmerSampler::new(fraction, seed);
+            let kept: Vec<_> = kmers
+                .iter()
+                .map(|kmer| sampler.keeps(&BitPackedKmer::<8, u16>::from_ascii(kmer).unwrap()))
+                .collect();
+            let kept_fraction =
+                kept.iter().filter(|&&kept| kept).count() as f64 / kmers.len() as f64;
+            assert!(
+                (kept_fraction - fraction).abs() < 0.01,
+                "fraction: {fraction}, kept fraction: {kept_fraction}"
+            );
+
+            // The decision does not depend on the sampler instance.
+            let mut other_sampler = KmerSampler::new(fraction, seed);
+            for (kmer, &kept) in kmers.iter().zip(&kept) {
+                assert_eq!(
+                    other_sampler.keeps(&BitPackedKmer::<8, u16>::from_ascii(kmer).unwrap()),
+                    kept
+                );
+            }
+        }
+
+        let mut sampler = KmerSampler::new(0.5, 0);
+        let mut other_sampler = KmerSampler::new(0.5, 1);
+        assert!(kmers.iter().any(|kmer| {
+            let kmer = BitPackedKmer::<8, u16>::from_ascii(kmer).unwrap();
+            sampler.keeps(&kmer) != other_sampler.keeps(&kmer)
+        }));
+    }
+}
@@ -0,0 +1,302 @@
+use crate::kmer::Kmer;
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// The number of spill directories created by this process, used to make their names unique.
+static SPILL_DIRECTORY_COUNT: AtomicUsize = AtomicUsize::new(0);
+
+/// The largest number of spilled runs that are merged at once, which bounds the number of open files.
+const MAX_MERGED_RUN_COUNT: usize = 64;
+
+/// A set of kmers that is partially stored on disk as sorted runs of unique kmers.
+///
+/// The runs are written to a temporary directory that is created on the first spill and removed when the set is dropped.
+/// The last run is kept in memory.
+pub struct SpilledKmers<KmerType> {
+    k: usize,
+    directory: Option<PathBuf>,
+    /// The path and the number of kmers of each spilled run.
+    runs: Vec<(PathBuf, usize)>,
+    /// The number of run files created so far, used to make their names unique.
+    run_file_count: usize,
+    /// The sorted unique kmers that were not spilled.
+    in_memory_kmers: Vec<KmerType>,
+}
+
+impl<KmerType: Kmer> SpilledKmers<KmerType> {
+    pub fn new(k: usize) -> Self {
+        Self {
+            k,
+            directory: None,
+            runs: Vec::new(),
+            run_file_count: 0,
+            in_memory_kmers: Vec::new(),
+        }
+    }
+
+    /// Write a run of sorted unique kmers to a new temporary file.
+    ///
+    /// Once there are more than [`MAX_MERGED_RUN_COUNT`] runs, the shortest of them are merged into one run,
+    /// such that iterating over the kmers never opens more than that many files.
+    pub fn spill(&mut self, kmers: &[KmerType]) -> std::io::Result<()> {
+        debug_assert!(kmers.is_sorted_by(|a, b| a < b));
+        self.write_run(kmers.iter().cloned().map(Ok))?;
+
+        if self.runs.len() > MAX_MERGED_RUN_COUNT {
+            self.merge_spilled_runs()?;
+        }
+        Ok(())
+    }
+
+    /// Keep the last run of sorted unique kmers in memory instead of spilling it.
+    pub fn keep_in_memory(&mut self, kmers: Vec<KmerType>) {
+        debug_assert!(kmers.is_sorted_by(|a, b| a < b));
+        self.in_memory_kmers = kmers;
+    }
+
+    /// The number of runs that are stored on disk.
+    pub fn spilled_run_count(&self) -> usize {
+        self.runs.len()
+    }
+
+    /// Iterate over all kmers in sorted order without duplicates, by merging the runs.
+    ///
+    /// If reading a spilled run fails, the iterator ends early and the error is returned by [`MergedRuns::take_error`].
+    pub fn iter(&self) -> std::io::Result<MergedRuns<'_, KmerType>> {
+        let mut runs = self
+            .runs
+            .iter()
+            .map(|(path, run_length)| Self::read_run(path, *run_length, self.k))
+            .collect::<std::io::Result<Vec<_>>>()?;
+        runs.push(Box::new(self.in_memory_kmers.iter().cloned().map(Ok)));
+        Ok(MergedRuns::new(runs))
+    }
+
+    /// Merge the [`MAX_MERGED_RUN_COUNT`] shortest spilled runs into a new run.
+    ///
+    /// Merging the shortest runs first avoids rewriting long runs each time the runs are merged.
+    fn merge_spilled_runs(&mut self) -> std::io::Result<()> {
+        self.runs
+            .sort_unstable_by_key(|(_, run_length)| Reverse(*run_length));
+        let batch = self
+            .runs
+            .split_off(self.runs.len().saturating_sub(MAX_MERGED_RUN_COUNT));
+
+        let runs = batch
+            .iter()
+            .map(|(path, run_length)| Self::read_run(path, *run_length, self.k))
+            .collect::<std::io::Result<Vec<_>>>()?;
+        let mut merged_runs = MergedRuns::new(runs);
+        self.write_run(merged_runs.by_ref().map(Ok))?;
+        if let Some(error) = merged_runs.take_error() {
+            return Err(error);
+        }
+        drop(merged_runs);
+
+        for (path, _) in batch {
+            std::fs::remove_file(path)?;
+        }
+        Ok(())
+    }
+
+    /// Write the sorted unique kmers to a new run file.
+    fn write_run(
+        &mut self,
+        kmers: impl Iterator<Item = std::io::Result<KmerType>>,
+    ) -> std::io::Result<()> {
+        if self.directory.is_none() {
+            let directory = std::env::temp_dir().join(format!(
+                "verify-same-kmer-content-spill-{}-{}",
+                std::process::id(),
+                SPILL_DIRECTORY_COUNT.fetch_add(1, Ordering::Relaxed)
+            ));
+            std::fs::create_dir(&directory)?;
+            self.directory = Some(directory);
+        }
+
+        let path = self
+            .directory
+            .as_ref()
+            .unwrap()
+            .join(format!("run-{}.kmers", self.run_file_count));
+        self.run_file_count += 1;
+
+        let mut output = BufWriter::new(File::create(&path)?);
+        let mut run_length = 0;
+        for kmer in kmers {
+            kmer?.write_packed(&mut output)?;
+            run_length += 1;
+        }
+        output.flush()?;
+        self.runs.push((path, run_length));
+        Ok(())
+    }
+
+    /// Open a spilled run of kmers of size `k` for reading.
+    fn read_run<'runs>(
+        path: &Path,
+        run_length: usize,
+        k: usize,
+    ) -> std::io::Result<Run<'runs, KmerType>>
+    where
+        KmerType: 'runs,
+    {
+        let mut input = BufReader::new(File::open(path)?);
+        Ok(Box::new(
+            (0..run_length).map(move |_| KmerType::read_packed(&mut input, k)),
+        ))
+    }
+}
+
+impl<KmerType> Drop for SpilledKmers<KmerType> {
+    fn drop(&mut self) {
+        if let Some(directory) = &self.directory {
+            std::fs::remove_dir_all(directory).ok();
+        }
+    }
+}
+
+/// A sorted run of unique kmers, which may fail to be read.
+type Run<'runs, KmerType> = Box<dyn Iterator<Item = std::io::Result<KmerType>> + 'runs>;
+
+/// A k-way merge of sorted runs of unique kmers.
+///
+/// Kmers contained in several runs are yielded once.
+pub struct MergedRuns<'runs, KmerType> {
+    runs: Vec<Run<'runs, KmerType>>,
+    /// The smallest remaining kmer of each non-exhausted run, with the index of the run.
+    heap: BinaryHeap<Reverse<(KmerType, usize)>>,
+    /// The first error that occurred while reading a run, after which the merge ends.
+    error: Option<std::io::Error>,
+}
+
+impl<'runs, KmerType: Ord> MergedRuns<'runs, KmerType> {
+    fn new(runs: Vec<Run<'runs, KmerType>>) -> Self {
+        let mut merged_runs = Self {
+            heap: BinaryHeap::with_capacity(runs.len()),
+            runs,
+            error: None,
+        };
+        for index in 0..merged_runs.runs.len() {
+            merged_runs.advance(index);
+        }
+        merged_runs
+    }
+
+    /// Returns the error that ended the merge early, if any.
+    pub fn take_error(&mut self) -> Option<std::io::Error> {
+        self.error.take()
+    }
+
+    /// Move the next kmer of the run into the heap.
+    fn advance(&mut self, index: usize) {
+        match self.runs[index].next() {
+            Some(Ok(kmer)) => self.heap.push(Reverse((kmer, index))),
+            Some(Err(error)) => {
+                self.error.get_or_insert(error);
+            }
+            None => {}
+        }
+    }
+}
+
+impl<KmerType: Ord> Iterator for MergedRuns<'_, KmerType> {
+    type Item = KmerType;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.error.is_some() {
+            return None;
+        }
+
+        let Reverse((kmer, index)) = self.heap.pop()?;
+        self.advance(index);
+        while let Some(Reverse((next_kmer, _))) = self.heap.peek() {
+            if *next_kmer != kmer {
+                break;
+            }
+            let Reverse((_, index)) = self.heap.pop().unwrap();
+            self.advance(index);
+        }
+
+        if self.error.is_some() {
+            None
+        } else {
+            Some(kmer)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::kmer::{BitPackedKmer, Kmer};
+    use crate::spill::{SpilledKmers, MAX_MERGED_RUN_COUNT};
+
+    fn kmers(kmers: &[&str]) -> Vec<BitPackedKmer<3, u8>> {
+        kmers
+            .iter()
+            .map(|kmer| BitPackedKmer::from_ascii(kmer.as_bytes()).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_spilled_kmers() {
+        let mut spilled_kmers = SpilledKmers::new(3);
+        assert_eq!(spilled_kmers.iter().unwrap().count(), 0);
+
+        spilled_kmers.spill(&kmers(&["AAA", "ACG", "TTT"])).unwrap();
+        spilled_kmers.spill(&kmers(&["ACG", "GCA"])).unwrap();
+        spilled_kmers.spill(&[]).unwrap();
+        spilled_kmers.keep_in_memory(kmers(&["AAC", "TTT"]));
+        assert_eq!(spilled_kmers.spilled_run_count(), 3);
+
+        let directory = spilled_kmers.directory.clone().unwrap();
+        assert!(directory.is_dir());
+        for _ in 0..2 {
+            let mut merged_runs = spilled_kmers.iter().unwrap();
+            assert_eq!(
+                merged_runs.by_ref().collect::<Vec<_>>(),
+                kmers(&["AAA", "AAC", "ACG", "GCA", "TTT"])
+            );
+            assert!(merged_runs.take_error().is_none());
+        }
+
+        drop(spilled_kmers);
+        assert!(!directory.exists());
+    }
+
+    #[test]
+    fn test_merge_spilled_runs() {
+        let mut spilled_kmers = SpilledKmers::new(3);
+        let run_kmers = kmers(&["AAA", "CCC", "GGG", "TTT"]);
+        for index in 0..=MAX_MERGED_RUN_COUNT {
+            spilled_kmers
+                .spill(&run_kmers[index % run_kmers.len()..][..1])
+                .unwrap();
+        }
+        assert_eq!(spilled_kmers.spilled_run_count(), 2);
+        assert_eq!(
+            std::fs::read_dir(spilled_kmers.directory.as_ref().unwrap())
+                .unwrap()
+                .count(),
+            2
+        );
+        assert_eq!(spilled_kmers.iter().unwrap().collect::<Vec<_>>(), run_kmers);
+    }
+
+    #[test]
+    fn test_spilled_run_read_error() {
+        let mut spilled_kmers = SpilledKmers::new(3);
+        spilled_kmers.spill(&kmers(&["AAA", "ACG"])).unwrap();
+        // Truncate the run, such that its second kmer cannot be read.
+        let (path, _) = &spilled_kmers.runs[0];
+        std::fs::write(path, &std::fs::read(path).unwrap()[..1]).unwrap();
+
+        let mut merged_runs = spilled_kmers.iter().unwrap();
+        assert_eq!(merged_runs.by_ref().count(), 0);
+        assert!(merged_runs.take_error().is_some());
+    }
+}
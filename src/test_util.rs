@@ -0,0 +1,50 @@
+use std::path::{Path, PathBuf};
+
+/// A xorshift generator of pseudo-random numbers, such that all test runs use the same inputs.
+pub struct Xorshift(u64);
+
+impl Xorshift {
+    /// Create a generator whose numbers depend on the given seed, which may be zero.
+    pub fn new(seed: u64) -> Self {
+        Self(0x2545_f491_4f6c_dd1d ^ seed)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    /// One of the characters `ACGT`.
+    pub fn next_nucleotide(&mut self) -> u8 {
+        b"ACGT"[(self.next_u64() % 4) as usize]
+    }
+}
+
+/// The path of a file in the temporary directory, which is removed when dropped, also if the test fails.
+pub struct TempFile {
+    path: PathBuf,
+}
+
+impl TempFile {
+    /// The file itself is not created.
+    pub fn new(name: &str) -> Self {
+        Self {
+            path: std::env::temp_dir().join(format!(
+                "verify-same-kmer-content-test-{name}-{}",
+                std::process::id()
+            )),
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for TempFile {
+    fn drop(&mut self) {
+        std::fs::remove_file(&self.path).ok();
+    }
+}
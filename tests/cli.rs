@@ -0,0 +1,378 @@
+//! End-to-end tests that run the binary on files written to a temporary directory.
+
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+
+/// A temporary directory that is removed when dropped.
+struct TempDir {
+    path: PathBuf,
+}
+
+impl TempDir {
+    fn new(name: &str) -> Self {
+        let path = std::env::temp_dir().join(format!(
+            "verify-same-kmer-content-cli-{name}-{}",
+            std::process::id()
+        ));
+        std::fs::create_dir_all(&path).unwrap();
+        Self { path }
+    }
+
+    fn write(&self, file_name: &str, content: &str) -> PathBuf {
+        let path = self.path.join(file_name);
+        std::fs::write(&path, content).unwrap();
+        path
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        std::fs::remove_dir_all(&self.path).ok();
+    }
+}
+
+fn run(k: usize, arguments: &[&str], unitigs: &Path, test_tigs: &Path) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_verify-same-kmer-content"))
+        .arg("-k")
+        .arg(k.to_string())
+        .args(arguments)
+        .arg(unitigs)
+        .arg(test_tigs)
+        .output()
+        .unwrap()
+}
+
+fn stdout(output: &Output) -> String {
+    String::from_utf8(output.stdout.clone()).unwrap()
+}
+
+/// A pseudo-random DNA sequence generated by a linear congruential generator.
+fn random_sequence(length: usize) -> String {
+    let mut state = 0x2545f4914f6cdd1du64;
+    (0..length)
+        .map(|_| {
+            state = state
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            ['A', 'C', 'G', 'T'][(state >> 62) as usize]
+        })
+        .collect()
+}
+
+#[test]
+fn test_matching_gfa_and_fasta() {
+    let directory = TempDir::new("matching");
+    let unitigs = directory.write("unitigs.gfa", "H\tVN:Z:1.0\nS\t1\tTAAACTG\n");
+    let test_tigs = directory.write("test_tigs.fa", ">\nTAAAC\n>\nCAGT\n");
+
+    let output = run(3, &[], &unitigs, &test_tigs);
+    let stdout = stdout(&output);
+    assert_eq!(output.status.code(), Some(0), "stdout:\n{stdout}");
+    assert!(stdout.contains("unique_kmer_count: 5\n"), "{stdout}");
+    assert!(stdout.contains("kmer_positions: 5\n"), "{stdout}");
+    assert!(stdout.contains("distinct_canonical_kmers: 5\n"), "{stdout}");
+    assert!(
+        stdout.contains("superfluous_unitig_kmer_count: 0\n"),
+        "{stdout}"
+    );
+    assert!(
+        stdout.contains("superfluous_test_tig_kmer_count: 0\n"),
+        "{stdout}"
+    );
+    assert!(stdout.contains("jaccard: 1\n"), "{stdout}");
+}
+
+#[test]
+fn test_missing_kmers() {
+    let directory = TempDir::new("missing");
+    let unitigs = directory.write("unitigs.fa", ">a\nTAAACTG\n");
+    let test_tigs = directory.write("test_tigs.fa", ">\nTAAAC\n");
+
+    let output = run(3, &[], &unitigs, &test_tigs);
+    let stdout = stdout(&output);
+    assert_eq!(output.status.code(), Some(2), "stdout:\n{stdout}");
+    assert!(
+        stdout.contains("superfluous_unitig_kmer_count: 2\n"),
+        "{stdout}"
+    );
+    assert!(
+        stdout.contains("superfluous_test_tig_kmer_count: 0\n"),
+        "{stdout}"
+    );
+}
+
+#[test]
+fn test_do_not_verify() {
+    let directory = TempDir::new("do-not-verify");
+    let unitigs = directory.write("unitigs.fa", ">a\nTAAACTG\n");
+    let test_tigs = directory.write("test_tigs.fa", ">\nTAAAC\n>\nCAGT\n");
+
+    let output = run(3, &["--do-not-verify"], &unitigs, &test_tigs);
+    let stdout = stdout(&output);
+    assert_eq!(output.status.code(), Some(0), "stdout:\n{stdout}");
+    assert!(stdout.contains("ground_truth_size:   7\n"), "{stdout}");
+    assert!(stdout.contains("test_size: 9\n"), "{stdout}");
+    assert!(stdout.contains("test_str_cnt: 2\n"), "{stdout}");
+    assert!(stdout.contains("unique_kmer_count: 5\n"), "{stdout}");
+}
+
+#[test]
+fn test_kmer_size_dispatch() {
+    let directory = TempDir::new("dispatch");
+    let sequence = random_sequence(400);
+    let unitigs = directory.write("unitigs.fa", &format!(">a\n{sequence}\n"));
+
+    // Covers the integer, array and vector kmer types.
+    for k in [31, 32, 33, 64, 65, 128, 129, 200] {
+        let split = sequence.len() / 2;
+        let test_tigs = directory.write(
+            &format!("test_tigs_{k}.fa"),
+            &format!(
+                ">\n{}\n>\n{}\n",
+                &sequence[..split + k - 1],
+                &sequence[split..]
+            ),
+        );
+        let output = run(k, &[], &unitigs, &test_tigs);
+        let stdout = stdout(&output);
+        assert_eq!(output.status.code(), Some(0), "k: {k}, stdout:\n{stdout}");
+        assert!(stdout.contains("jaccard: 1\n"), "k: {k}, stdout:\n{stdout}");
+
+        let missing_kmer_test_tigs = directory.write(
+            &format!("missing_kmer_test_tigs_{k}.fa"),
+            &format!(">\n{}\n>\n{}\n", &sequence[..split], &sequence[split..]),
+        );
+        let output = run(k, &[], &unitigs, &missing_kmer_test_tigs);
+        assert_eq!(output.status.code(), Some(2), "k: {k}");
+    }
+}
+
+#[test]
+fn test_k_range() {
+    let directory = TempDir::new("k-range");
+    let unitigs = directory.write("unitigs.fa", ">a\nACGTTGCA\n");
+    // The test tigs overlap by three characters, so they lack the kmers spanning the overlap for k > 4.
+    let test_tigs = directory.write("test_tigs.fa", ">\nACGTTG\n>\nTTGCA\n");
+
+    let output = Command::new(env!("CARGO_BIN_EXE_verify-same-kmer-content"))
+        .args(["--k-range", "3..6"])
+        .arg(&unitigs)
+        .arg(&test_tigs)
+        .output()
+        .unwrap();
+    let stdout = stdout(&output);
+    assert_eq!(output.status.code(), Some(2), "stdout:\n{stdout}");
+    assert!(
+        stdout.contains("k=3: ok, k=4: ok, k=5: mismatch, k=6: mismatch"),
+        "{stdout}"
+    );
+    assert!(
+        stdout.contains("The comparison fails first at k = 5"),
+        "{stdout}"
+    );
+
+    let output = run(3, &["--k-range", "3..6"], &unitigs, &test_tigs);
+    assert_eq!(output.status.code(), Some(1));
+}
+
+#[test]
+fn test_per_sequence_stats() {
+    let directory = TempDir::new("per-sequence-stats");
+    let unitigs = directory.write("unitigs.fa", ">a\nTAAACTG\n");
+    let test_tigs = directory.write("test_tigs.fa", ">\nTAAAC\n>\nCAGT\n>\nCA\n");
+    let stats_path = directory.path.join("per_sequence_stats.tsv");
+
+    let output = run(
+        3,
+        &["--per-sequence-stats", stats_path.to_str().unwrap()],
+        &unitigs,
+        &test_tigs,
+    );
+    assert_eq!(
+        output.status.code(),
+        Some(0),
+        "stdout:\n{}",
+        stdout(&output)
+    );
+    assert_eq!(
+        std::fs::read_to_string(&stats_path).unwrap(),
+        format!(
+            "# first input file\n0\t7\t5\n# test tigs {}\n0\t5\t3\n1\t4\t2\n2\t2\t0\n",
+            test_tigs.display()
+        )
+    );
+}
+
+#[test]
+fn test_unitigs_shards() {
+    let directory = TempDir::new("unitigs-shards");
+    // The first shard does not end with a newline, which must not join its last line with the next shard.
+    let unitigs = directory.write("unitigs_1.fa", ">a\nTAAAC");
+    let second_shard = directory.write("unitigs_2.fa", ">b\nACTG\n");
+    let test_tigs = directory.write("test_tigs.fa", ">\nTAAAC\n>\nCAGT\n");
+
+    let output = run(
+        3,
+        &["--unitigs-shard", second_shard.to_str().unwrap()],
+        &unitigs,
+        &test_tigs,
+    );
+    let stdout = stdout(&output);
+    assert_eq!(output.status.code(), Some(0), "stdout:\n{stdout}");
+    assert!(stdout.contains("ground_truth_size:   9\n"), "{stdout}");
+    assert!(stdout.contains("ground_truth_str_cnt:   2\n"), "{stdout}");
+    assert!(stdout.contains("distinct_canonical_kmers: 5\n"), "{stdout}");
+
+    // Without the second shard, the ground truth misses kmers.
+    let output = run(3, &[], &unitigs, &test_tigs);
+    assert_eq!(output.status.code(), Some(3));
+}
+
+#[test]
+fn test_terse() {
+    let directory = TempDir::new("terse");
+    let unitigs = directory.write("unitigs.fa", ">a\nTAAACTG\n");
+    let test_tigs = directory.write("test_tigs.fa", ">\nTAAAC\n>\nCAGT\n");
+    let missing_kmer_test_tigs = directory.write("missing_kmer_test_tigs.fa", ">\nTAAAC\n");
+
+    let output = run(3, &["--terse"], &unitigs, &test_tigs);
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(stdout(&output), "MATCH\n");
+
+    let output = run(3, &["--terse"], &unitigs, &missing_kmer_test_tigs);
+    assert_eq!(output.status.code(), Some(2));
+    assert_eq!(stdout(&output), "MISMATCH\n");
+
+    let output = run(3, &["--terse", "--scan-only"], &unitigs, &test_tigs);
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(stdout(&output), "");
+}
+
+#[test]
+fn test_two_bit_input() {
+    let directory = TempDir::new("two-bit-input");
+    // The kmers of TAAACTG, where the test tigs contain CAG instead of its reverse complement CTG.
+    let unitigs = directory.path.join("unitigs.bin");
+    std::fs::write(
+        &unitigs,
+        [0b11_00_00, 0b00_00_00, 0b00_00_01, 0b00_01_11, 0b01_11_10],
+    )
+    .unwrap();
+    let test_tigs = directory.path.join("test_tigs.bin");
+    std::fs::write(
+        &test_tigs,
+        [0b01_00_10, 0b00_01_11, 0b00_00_01, 0b00_00_00, 0b11_00_00],
+    )
+    .unwrap();
+    let missing_kmer_test_tigs = directory.path.join("missing_kmer_test_tigs.bin");
+    std::fs::write(&missing_kmer_test_tigs, [0b00_00_00, 0b00_00_01]).unwrap();
+
+    let output = run(3, &["--two-bit-input"], &unitigs, &test_tigs);
+    let stdout = stdout(&output);
+    assert_eq!(output.status.code(), Some(0), "stdout:\n{stdout}");
+    assert!(stdout.contains("distinct_canonical_kmers: 5\n"), "{stdout}");
+
+    let output = run(3, &["--two-bit-input"], &unitigs, &missing_kmer_test_tigs);
+    assert_eq!(output.status.code(), Some(2));
+
+    let output = run(65, &["--two-bit-input"], &unitigs, &test_tigs);
+    assert_eq!(output.status.code(), Some(1));
+}
+
+#[test]
+fn test_two_bit_input_trailing_bytes() {
+    let directory = TempDir::new("two-bit-input-trailing-bytes");
+    // Two kmers in the 2-byte integers of k = 5, followed by a trailing byte.
+    let trailing_byte_test_tigs = directory.path.join("trailing_byte_test_tigs.bin");
+    std::fs::write(&trailing_byte_test_tigs, [0, 0, 0, 0, 0]).unwrap();
+    let output = run(
+        5,
+        &["--two-bit-input"],
+        &trailing_byte_test_tigs,
+        &trailing_byte_test_tigs,
+    );
+    let stdout = stdout(&output);
+    assert_eq!(output.status.code(), Some(1), "stdout:\n{stdout}");
+    assert!(!stdout.contains("Success!"), "{stdout}");
+}
+
+#[test]
+fn test_report_gc_content() {
+    let directory = TempDir::new("report-gc-content");
+    let unitigs = directory.write("unitigs.fa", ">a\nTAAACTG\n");
+    let test_tigs = directory.write("test_tigs.fa", ">\nTAAAC\n>\nCAGT\n");
+
+    let output = run(3, &[], &unitigs, &test_tigs);
+    assert!(!stdout(&output).contains("_gc: "));
+
+    let output = run(3, &["--report-gc-content"], &unitigs, &test_tigs);
+    let stdout = stdout(&output);
+    assert_eq!(output.status.code(), Some(0), "stdout:\n{stdout}");
+    assert!(
+        stdout.contains(&format!("ground_truth_gc: {}\n", 2.0 / 7.0)),
+        "{stdout}"
+    );
+    assert!(
+        stdout.contains(&format!("test_gc: {}\n", 1.0 / 3.0)),
+        "{stdout}"
+    );
+}
+
+#[test]
+fn test_alphabet_acgtn() {
+    let directory = TempDir::new("alphabet-acgtn");
+    let unitigs = directory.write("unitigs.fa", ">a\nTANNCTG\n");
+    let test_tigs = directory.write("test_tigs.fa", ">\nTANNC\n>\nCAGNN\n");
+    let missing_kmer_test_tigs = directory.write("missing_kmer_test_tigs.fa", ">\nTANNCT\n");
+
+    // Without N as a character, only the kmer CTG remains.
+    let output = run(3, &[], &unitigs, &test_tigs);
+    assert_eq!(output.status.code(), Some(0));
+    assert!(stdout(&output).contains("distinct_canonical_kmers: 1\n"));
+
+    let output = run(
+        3,
+        &["--alphabet", "acgtn"],
+        &unitigs,
+        &missing_kmer_test_tigs,
+    );
+    assert_eq!(output.status.code(), Some(2));
+
+    let output = run(3, &["--alphabet", "acgtn"], &unitigs, &test_tigs);
+    let stdout = stdout(&output);
+    assert_eq!(output.status.code(), Some(0), "stdout:\n{stdout}");
+    assert!(stdout.contains("distinct_canonical_kmers: 5\n"), "{stdout}");
+}
+
+#[test]
+fn test_alphabet_acgtn_without_extensions() {
+    let directory = TempDir::new("alphabet-acgtn-without-extensions");
+    let unitigs = directory.write("unitigs.fa", ">a\nTANNCTG\n");
+
+    for argument in ["--allow-cuttlefish2-errors", "--count-k-plus-1"] {
+        let output = run(3, &["--alphabet", "acgtn", argument], &unitigs, &unitigs);
+        assert_eq!(output.status.code(), Some(1), "{argument}");
+    }
+}
+
+#[test]
+fn test_jaccard_with_duplicate_kmers() {
+    let directory = TempDir::new("jaccard-duplicates");
+    let unitigs = directory.write("unitigs.fa", ">a\nTAAACTG\n");
+    let test_tigs = directory.write("test_tigs.fa", ">\nTAAACTG\n>\nTAAACTG\n");
+
+    let output = run(3, &[], &unitigs, &test_tigs);
+    let stdout = stdout(&output);
+    assert!(stdout.contains("jaccard: 1\n"), "{stdout}");
+}
+
+#[test]
+fn test_jaccard_with_sample() {
+    let directory = TempDir::new("jaccard-sample");
+    let unitigs = directory.write("unitigs.fa", &format!(">a\n{}\n", random_sequence(5000)));
+
+    let output = run(21, &["--sample", "0.5"], &unitigs, &unitigs);
+    let stdout = stdout(&output);
+    assert_eq!(output.status.code(), Some(0), "stdout:\n{stdout}");
+    assert!(stdout.contains("jaccard: 1\n"), "{stdout}");
+}